@@ -0,0 +1,68 @@
+//! Feeds arbitrary sequences and enzymes through the restriction and ligation API. None of these
+//! calls may panic. Run with `cargo +nightly fuzz run digest_ligate`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use na_seq::{
+    ligation,
+    restriction_enzyme::{self, find_re_matches_topology, RestrictionEnzyme},
+    Nucleotide, NucleotideGeneral, SeqTopology,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+
+    let topology = if data[0] & 1 == 0 {
+        SeqTopology::Linear
+    } else {
+        SeqTopology::Circular
+    };
+    let site_len = (data[1] % 12) as usize;
+    let cut_after = data[2];
+    let data = &data[3..];
+
+    let site_len = site_len.min(data.len());
+    let cut_seq: Vec<_> = data[..site_len]
+        .iter()
+        .map(|b| NucleotideGeneral::try_from(b % 11).unwrap())
+        .collect();
+    let seq: Vec<_> = data[site_len..]
+        .iter()
+        .map(|b| Nucleotide::try_from(b & 0b11).unwrap())
+        .collect();
+
+    let offset_top = cut_after as i8;
+    let lib = vec![
+        RestrictionEnzyme::new("A", cut_seq.clone(), cut_after),
+        RestrictionEnzyme::new_type_iis("B", cut_seq, offset_top, offset_top.wrapping_add(4)),
+    ];
+
+    for re in &lib {
+        re.cut_depiction();
+        re.makes_blunt_ends();
+        for site_start in [0, seq.len() / 2, seq.len()] {
+            if let Some((left, right)) = re.cut_duplex(&seq, site_start) {
+                left.ligate_to(&right, true);
+                left.five_prime_overhang_right();
+                right.three_prime_overhang_left();
+            }
+        }
+    }
+
+    let matches = find_re_matches_topology(&seq, &lib, topology);
+    let fragments = restriction_enzyme::digest(&seq, &lib, topology);
+    ligation::ligate_fragments(&fragments, true);
+    ligation::ligate_all(&fragments[..fragments.len().min(4)], true);
+
+    let mut warnings = Vec::new();
+    let ligation_frags =
+        ligation::digest_with_warnings("fuzz", &lib, &matches, &lib, &seq, topology, &mut warnings);
+    ligation::ligate(&ligation_frags);
+
+    let match_set = vec![&matches];
+    let mut res = ligation::find_common_res_with_warnings(&match_set, &lib, false, &mut warnings);
+    ligation::filter_unique_cutters(&mut res, &match_set, &lib);
+});
@@ -0,0 +1,399 @@
+//! This module contains pairwise global (Needleman-Wunsch) and local (Smith-Waterman) alignment, with
+//! affine gap penalties (Gotoh). Scores are kept one row at a time; the traceback uses one byte per cell.
+//!
+//! Coordinates are 0-based, with exclusive ends. A gap of length `L` scores
+//! `gap_open + (L - 1) * gap_extend`.
+
+use crate::{alphabet::Alphabet, AminoAcid, Nucleotide};
+
+/// Scoring for nucleotide alignments. The defaults are similar to BLASTN's.
+#[derive(Clone, Debug)]
+pub struct Scoring {
+    pub match_score: i32,
+    pub mismatch_score: i32,
+    pub gap_open: i32,
+    pub gap_extend: i32,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            match_score: 2,
+            mismatch_score: -3,
+            gap_open: -7,
+            gap_extend: -2,
+        }
+    }
+}
+
+/// Gap scoring for amino acid alignments, which use `BLOSUM62` for substitutions. The defaults are
+/// similar to BLASTP's.
+#[derive(Clone, Debug)]
+pub struct ProteinScoring {
+    pub gap_open: i32,
+    pub gap_extend: i32,
+}
+
+impl Default for ProteinScoring {
+    fn default() -> Self {
+        Self {
+            gap_open: -12,
+            gap_extend: -1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Alignment {
+    /// The aligned region of each sequence, with '-' for gaps. These have the same length.
+    pub aligned_a: String,
+    pub aligned_b: String,
+    pub score: i32,
+    pub start_a: usize,
+    pub end_a: usize,
+    pub start_b: usize,
+    pub end_b: usize,
+}
+
+/// The amino acid order of `BLOSUM62`'s rows and columns, by one-letter code.
+pub const BLOSUM62_ORDER: &str = "ARNDCQEGHILKMFPSTWYV";
+
+/// The BLOSUM62 substitution matrix, in `BLOSUM62_ORDER`.
+#[rustfmt::skip]
+pub static BLOSUM62: [[i8; 20]; 20] = [
+    [ 4, -1, -2, -2,  0, -1, -1,  0, -2, -1, -1, -1, -1, -2, -1,  1,  0, -3, -2,  0],
+    [-1,  5,  0, -2, -3,  1,  0, -2,  0, -3, -2,  2, -1, -3, -2, -1, -1, -3, -2, -3],
+    [-2,  0,  6,  1, -3,  0,  0,  0,  1, -3, -3,  0, -2, -3, -2,  1,  0, -4, -2, -3],
+    [-2, -2,  1,  6, -3,  0,  2, -1, -1, -3, -4, -1, -3, -3, -1,  0, -1, -4, -3, -3],
+    [ 0, -3, -3, -3,  9, -3, -4, -3, -3, -1, -1, -3, -1, -2, -3, -1, -1, -2, -2, -1],
+    [-1,  1,  0,  0, -3,  5,  2, -2,  0, -3, -2,  1,  0, -3, -1,  0, -1, -2, -1, -2],
+    [-1,  0,  0,  2, -4,  2,  5, -2,  0, -3, -3,  1, -2, -3, -1,  0, -1, -3, -2, -2],
+    [ 0, -2,  0, -1, -3, -2, -2,  6, -2, -4, -4, -2, -3, -3, -2,  0, -2, -2, -3, -3],
+    [-2,  0,  1, -1, -3,  0,  0, -2,  8, -3, -3, -1, -2, -1, -2, -1, -2, -2,  2, -3],
+    [-1, -3, -3, -3, -1, -3, -3, -4, -3,  4,  2, -3,  1,  0, -3, -2, -1, -3, -1,  3],
+    [-1, -2, -3, -4, -1, -2, -3, -4, -3,  2,  4, -2,  2,  0, -3, -2, -1, -2, -1,  1],
+    [-1,  2,  0, -1, -3,  1,  1, -2, -1, -3, -2,  5, -1, -3, -1,  0, -1, -3, -2, -2],
+    [-1, -1, -2, -3, -1,  0, -2, -3, -2,  1,  2, -1,  5,  0, -2, -1, -1, -1, -1,  1],
+    [-2, -3, -3, -3, -2, -3, -3, -3, -1,  0,  0, -3,  0,  6, -4, -2, -2,  1,  3, -1],
+    [-1, -2, -2, -1, -3, -1, -1, -2, -2, -3, -3, -1, -2, -4,  7, -1, -1, -4, -3, -2],
+    [ 1, -1,  1,  0, -1,  0,  0,  0, -1, -2, -2,  0, -1, -2, -1,  4,  1, -3, -2, -2],
+    [ 0, -1,  0, -1, -1, -1, -1, -2, -2, -1, -1, -1, -1, -2, -1,  1,  5, -2, -2,  0],
+    [-3, -3, -4, -4, -2, -2, -3, -2, -2, -3, -2, -3, -1,  1, -4, -3, -2, 11,  2, -3],
+    [-2, -2, -2, -3, -2, -1, -2, -3,  2, -1, -1, -2, -1,  3, -3, -2, -2,  2,  7, -1],
+    [ 0, -3, -3, -3, -1, -2, -2, -3, -3,  3,  1, -2,  1, -1, -2, -2,  0, -3, -1,  4],
+];
+
+fn blosum62_index(aa: AminoAcid) -> usize {
+    // Selenocysteine isn't in the matrix; score it as cysteine.
+    let letter = match aa {
+        AminoAcid::Sec => b'C',
+        _ => aa.to_u8_letter(),
+    };
+
+    BLOSUM62_ORDER.bytes().position(|l| l == letter).unwrap()
+}
+
+/// The BLOSUM62 score for substituting one amino acid for another. Selenocysteine is scored as cysteine.
+pub fn blosum62(a: AminoAcid, b: AminoAcid) -> i32 {
+    BLOSUM62[blosum62_index(a)][blosum62_index(b)] as i32
+}
+
+/// Global alignment of two nucleotide sequences.
+pub fn align_global(a: &[Nucleotide], b: &[Nucleotide], scoring: &Scoring) -> Alignment {
+    align(
+        a,
+        b,
+        |x, y| nt_score(x, y, scoring),
+        scoring.gap_open,
+        scoring.gap_extend,
+        false,
+    )
+}
+
+/// Local alignment of two nucleotide sequences.
+pub fn align_local(a: &[Nucleotide], b: &[Nucleotide], scoring: &Scoring) -> Alignment {
+    align(
+        a,
+        b,
+        |x, y| nt_score(x, y, scoring),
+        scoring.gap_open,
+        scoring.gap_extend,
+        true,
+    )
+}
+
+/// Global alignment of two amino acid sequences, using `BLOSUM62`.
+pub fn align_global_protein(
+    a: &[AminoAcid],
+    b: &[AminoAcid],
+    scoring: &ProteinScoring,
+) -> Alignment {
+    align(a, b, blosum62, scoring.gap_open, scoring.gap_extend, false)
+}
+
+/// Local alignment of two amino acid sequences, using `BLOSUM62`.
+pub fn align_local_protein(
+    a: &[AminoAcid],
+    b: &[AminoAcid],
+    scoring: &ProteinScoring,
+) -> Alignment {
+    align(a, b, blosum62, scoring.gap_open, scoring.gap_extend, true)
+}
+
+fn nt_score(a: Nucleotide, b: Nucleotide, scoring: &Scoring) -> i32 {
+    if a == b {
+        scoring.match_score
+    } else {
+        scoring.mismatch_score
+    }
+}
+
+// Low enough to never be chosen, but with room to add penalties without overflowing.
+const NEG_INF: i32 = i32::MIN / 2;
+
+// Traceback sources. `M` is a match or mismatch, `X` a gap in `b`, and `Y` a gap in `a`.
+const FROM_M: u8 = 0;
+const FROM_X: u8 = 1;
+const FROM_Y: u8 = 2;
+/// The start of a local alignment.
+const FROM_START: u8 = 3;
+
+/// The best of the three states, and which it came from. Ties prefer `M`, then `X`.
+fn best(m: i32, x: i32, y: i32) -> (i32, u8) {
+    if m >= x && m >= y {
+        (m, FROM_M)
+    } else if x >= y {
+        (x, FROM_X)
+    } else {
+        (y, FROM_Y)
+    }
+}
+
+/// Gotoh's algorithm. Each traceback byte packs the source of the `M` state in bits 0-1, `X` in
+/// bits 2-3, and `Y` in bits 4-5.
+fn align<T: Alphabet>(
+    a: &[T],
+    b: &[T],
+    score: impl Fn(T, T) -> i32,
+    gap_open: i32,
+    gap_extend: i32,
+    local: bool,
+) -> Alignment {
+    let (n, m) = (a.len(), b.len());
+    let cols = m + 1;
+    let mut trace = vec![0_u8; (n + 1) * cols];
+
+    let mut prev_m = vec![NEG_INF; cols];
+    let mut prev_x = vec![NEG_INF; cols];
+    let mut prev_y = vec![NEG_INF; cols];
+    let mut cur_m = vec![NEG_INF; cols];
+    let mut cur_x = vec![NEG_INF; cols];
+    let mut cur_y = vec![NEG_INF; cols];
+
+    // Row 0: A leading gap in `a`, for global alignments.
+    prev_m[0] = 0;
+    if !local {
+        for (j, y) in prev_y.iter_mut().enumerate().skip(1) {
+            *y = gap_open + (j as i32 - 1) * gap_extend;
+        }
+    }
+
+    // The best local alignment so far, and the cell it ends at.
+    let mut local_best = (0, 0, 0);
+
+    for i in 1..=n {
+        cur_m[0] = NEG_INF;
+        cur_y[0] = NEG_INF;
+        cur_x[0] = if local {
+            NEG_INF
+        } else {
+            gap_open + (i as i32 - 1) * gap_extend
+        };
+
+        for j in 1..cols {
+            let (mut diag, mut m_src) = best(prev_m[j - 1], prev_x[j - 1], prev_y[j - 1]);
+            if local && diag <= 0 {
+                diag = 0;
+                m_src = FROM_START;
+            }
+            cur_m[j] = diag + score(a[i - 1], b[j - 1]);
+
+            let (x, x_src) = best(
+                prev_m[j] + gap_open,
+                prev_x[j] + gap_extend,
+                prev_y[j] + gap_open,
+            );
+            cur_x[j] = x;
+
+            let (y, y_src) = best(
+                cur_m[j - 1] + gap_open,
+                cur_x[j - 1] + gap_open,
+                cur_y[j - 1] + gap_extend,
+            );
+            cur_y[j] = y;
+
+            trace[i * cols + j] = m_src | (x_src << 2) | (y_src << 4);
+
+            if local && cur_m[j] > local_best.0 {
+                local_best = (cur_m[j], i, j);
+            }
+        }
+
+        std::mem::swap(&mut prev_m, &mut cur_m);
+        std::mem::swap(&mut prev_x, &mut cur_x);
+        std::mem::swap(&mut prev_y, &mut cur_y);
+    }
+
+    let (score, mut i, mut j, mut state) = if local {
+        (local_best.0, local_best.1, local_best.2, FROM_M)
+    } else {
+        let (score, state) = best(prev_m[m], prev_x[m], prev_y[m]);
+        (score, n, m, state)
+    };
+
+    let (end_a, end_b) = (i, j);
+    let mut aligned_a = Vec::new();
+    let mut aligned_b = Vec::new();
+
+    // An empty local alignment has nothing to trace back.
+    let traceback = !local || score > 0;
+    while traceback && (i > 0 || j > 0) {
+        let cell = trace[i * cols + j];
+        match state {
+            FROM_M => {
+                aligned_a.push(a[i - 1].to_u8_letter());
+                aligned_b.push(b[j - 1].to_u8_letter());
+                state = cell & 0b11;
+                i -= 1;
+                j -= 1;
+            }
+            FROM_X => {
+                aligned_a.push(a[i - 1].to_u8_letter());
+                aligned_b.push(b'-');
+                state = (cell >> 2) & 0b11;
+                i -= 1;
+            }
+            _ => {
+                aligned_a.push(b'-');
+                aligned_b.push(b[j - 1].to_u8_letter());
+                state = (cell >> 4) & 0b11;
+                j -= 1;
+            }
+        }
+
+        if state == FROM_START || (i == 0 && j == 0) {
+            break;
+        }
+        // Row and column 0 aren't in the traceback; they're all gaps.
+        if i == 0 {
+            state = FROM_Y;
+        } else if j == 0 {
+            state = FROM_X;
+        }
+    }
+
+    aligned_a.reverse();
+    aligned_b.reverse();
+
+    Alignment {
+        aligned_a: String::from_utf8(aligned_a).unwrap(),
+        aligned_b: String::from_utf8(aligned_b).unwrap(),
+        score,
+        start_a: i,
+        end_a,
+        start_b: j,
+        end_b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_aa_from_str, seq_from_str};
+
+    #[test]
+    fn identical() {
+        let seq = seq_from_str("GATTACAGATTACA");
+        let scoring = Scoring::default();
+
+        for alignment in [
+            align_global(&seq, &seq, &scoring),
+            align_local(&seq, &seq, &scoring),
+        ] {
+            assert_eq!(alignment.aligned_a, "GATTACAGATTACA");
+            assert_eq!(alignment.aligned_b, "GATTACAGATTACA");
+            assert_eq!(alignment.score, 14 * scoring.match_score);
+            assert_eq!((alignment.start_a, alignment.end_a), (0, 14));
+            assert_eq!((alignment.start_b, alignment.end_b), (0, 14));
+        }
+    }
+
+    #[test]
+    fn single_indel() {
+        let a = seq_from_str("CATCAGTCGA GGG CTACGTCAGC");
+        let b = seq_from_str("CATCAGTCGA CTACGTCAGC");
+
+        let alignment = align_global(&a, &b, &Scoring::default());
+        assert_eq!(alignment.aligned_a, "CATCAGTCGAGGGCTACGTCAGC");
+        assert_eq!(alignment.aligned_b, "CATCAGTCGA---CTACGTCAGC");
+        // One gap of 3 costs -7 - 2 - 2; splitting it would pay the opening penalty again.
+        assert_eq!(alignment.score, 20 * 2 - 11);
+
+        // Gaps at the ends of global alignments are scored too.
+        let alignment = align_global(&seq_from_str("ACG"), &[], &Scoring::default());
+        assert_eq!(alignment.aligned_a, "ACG");
+        assert_eq!(alignment.aligned_b, "---");
+        assert_eq!(alignment.score, -11);
+    }
+
+    #[test]
+    fn local_coords() {
+        let a = seq_from_str("TTTTTTTT GACGCAGCAG TTTTTTTT");
+        let b = seq_from_str("CCCC GACGCAGCAG CCCC");
+
+        let alignment = align_local(&a, &b, &Scoring::default());
+        assert_eq!(alignment.aligned_a, "GACGCAGCAG");
+        assert_eq!(alignment.aligned_b, "GACGCAGCAG");
+        assert_eq!(alignment.score, 20);
+        assert_eq!((alignment.start_a, alignment.end_a), (8, 18));
+        assert_eq!((alignment.start_b, alignment.end_b), (4, 14));
+
+        // Nothing in common.
+        let alignment = align_local(
+            &seq_from_str("AAAA"),
+            &seq_from_str("CCCC"),
+            &Scoring::default(),
+        );
+        assert_eq!(alignment.score, 0);
+        assert!(alignment.aligned_a.is_empty());
+    }
+
+    #[test]
+    fn protein() {
+        let a = seq_aa_from_str("MKWVTFISLL");
+        let b = seq_aa_from_str("PPWVTFPP");
+
+        // W, V, T and F score 11, 4, 5 and 6 against themselves.
+        let alignment = align_local_protein(&a, &b, &ProteinScoring::default());
+        assert_eq!(alignment.aligned_a, "WVTF");
+        assert_eq!(alignment.score, 26);
+        assert_eq!((alignment.start_a, alignment.end_a), (2, 6));
+        assert_eq!((alignment.start_b, alignment.end_b), (2, 6));
+
+        // A conservative substitution, I for V, scores 3.
+        let alignment = align_global_protein(
+            &seq_aa_from_str("WVTF"),
+            &seq_aa_from_str("WITF"),
+            &ProteinScoring::default(),
+        );
+        assert_eq!(alignment.aligned_b, "WITF");
+        assert_eq!(alignment.score, 11 + 3 + 5 + 6);
+
+        assert_eq!(blosum62(AminoAcid::Sec, AminoAcid::Cys), 9);
+        for (i, row) in BLOSUM62.iter().enumerate() {
+            for (j, score) in row.iter().enumerate() {
+                assert_eq!(*score, BLOSUM62[j][i]);
+            }
+        }
+    }
+}
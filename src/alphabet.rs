@@ -0,0 +1,254 @@
+//! This module contains the `Alphabet` trait, which lets code that doesn't depend on sequence
+//! chemistry operate on both nucleotide and amino acid sequences.
+
+use std::str::FromStr;
+
+use crate::{AminoAcid, Nucleotide, SeqError};
+
+/// A set of symbols that sequences are made of. Implemented by `Nucleotide` and `AminoAcid`.
+pub trait Alphabet: Copy + Eq + Sized + 'static {
+    /// All symbols, in index order.
+    const SYMBOLS: &'static [Self];
+    /// The number of distinct symbols.
+    const SIZE: usize = Self::SYMBOLS.len();
+    /// The number of bits needed to pack a symbol's index; e.g. 2 for nucleotides.
+    const BITS: u32 = usize::BITS - (Self::SIZE - 1).leading_zeros();
+    /// Used in error messages; e.g. "nucleotide".
+    const NAME: &'static str;
+
+    /// A dense index, in `0..SIZE`. Suitable for indexing count arrays.
+    fn to_index(self) -> usize;
+
+    fn from_index(i: usize) -> Option<Self> {
+        Self::SYMBOLS.get(i).copied()
+    }
+
+    /// The upper-case, single-letter code.
+    fn to_u8_letter(self) -> u8;
+
+    /// Parse a single-letter code. (Case insensitive)
+    fn from_u8_letter(val: u8) -> Result<Self, SeqError>;
+
+    /// The complementary symbol, for alphabets that have one. `None` for amino acids.
+    fn complement(self) -> Option<Self> {
+        None
+    }
+}
+
+impl Alphabet for Nucleotide {
+    const SYMBOLS: &'static [Self] = &[Nucleotide::T, Nucleotide::C, Nucleotide::A, Nucleotide::G];
+    const NAME: &'static str = "nucleotide";
+
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn to_u8_letter(self) -> u8 {
+        self.to_u8_upper()
+    }
+
+    fn from_u8_letter(val: u8) -> Result<Self, SeqError> {
+        Nucleotide::from_u8_letter(val)
+    }
+
+    fn complement(self) -> Option<Self> {
+        Some(Nucleotide::complement(self))
+    }
+}
+
+impl Alphabet for AminoAcid {
+    #[rustfmt::skip]
+    const SYMBOLS: &'static [Self] = {
+        use AminoAcid::*;
+        &[
+            Arg, His, Lys, Asp, Glu, Ser, Thr, Asn, Gln, Cys, Sec,
+            Gly, Pro, Ala, Val, Ile, Leu, Met, Phe, Tyr, Trp,
+        ]
+    };
+    const NAME: &'static str = "amino acid";
+
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn to_u8_letter(self) -> u8 {
+        self.to_u8_upper()
+    }
+
+    fn from_u8_letter(val: u8) -> Result<Self, SeqError> {
+        let letter = [val];
+        match std::str::from_utf8(&letter) {
+            Ok(s) => AminoAcid::from_str(s),
+            Err(_) => Err(SeqError::InvalidAminoAcid(
+                String::from_utf8_lossy(&letter).into_owned(),
+            )),
+        }
+    }
+}
+
+/// Convert a sequence to its upper-case, single-letter codes.
+pub fn seq_to_letters<T: Alphabet>(seq: &[T]) -> Vec<u8> {
+    seq.iter().map(|s| s.to_u8_letter()).collect()
+}
+
+/// Parse a sequence from single-letter codes. (Case insensitive) Whitespace is skipped; other invalid
+/// letters return an error that includes the letter, and its 0-based position.
+pub fn seq_from_letters<T: Alphabet>(letters: &[u8]) -> Result<Vec<T>, SeqError> {
+    let mut result = Vec::with_capacity(letters.len());
+
+    for (i, letter) in letters.iter().enumerate() {
+        if letter.is_ascii_whitespace() {
+            continue;
+        }
+
+        match T::from_u8_letter(*letter) {
+            Ok(s) => result.push(s),
+            Err(_) => {
+                return Err(SeqError::InvalidSymbol {
+                    symbol: *letter as char,
+                    position: i,
+                    alphabet: T::NAME,
+                })
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Count each symbol in a sequence. The result is indexed by `Alphabet::to_index`.
+pub fn composition<T: Alphabet>(seq: &[T]) -> Vec<usize> {
+    let mut result = vec![0; T::SIZE];
+    for s in seq {
+        result[s.to_index()] += 1;
+    }
+    result
+}
+
+/// The Shannon entropy of a sequence's composition, in bits. Ranges from 0 (a single repeated symbol),
+/// to log2 of the alphabet size (all symbols equally frequent). Returns 0 for an empty sequence.
+pub fn shannon_entropy<T: Alphabet>(seq: &[T]) -> f32 {
+    if seq.is_empty() {
+        return 0.;
+    }
+
+    let len = seq.len() as f32;
+
+    composition(seq)
+        .into_iter()
+        .filter(|count| *count > 0)
+        .map(|count| {
+            let p = count as f32 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The reverse complement of a sequence, for alphabets that have complements. `None` otherwise.
+pub fn reverse_complement<T: Alphabet>(seq: &[T]) -> Option<Vec<T>> {
+    // Check the alphabet, so empty sequences are handled consistently.
+    T::SYMBOLS[0].complement()?;
+
+    seq.iter().rev().map(|s| s.complement()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_aa_from_str, seq_from_str, test_utils::Rng};
+
+    #[test]
+    fn letters() {
+        assert_eq!(
+            seq_from_letters::<Nucleotide>(b"ga tC\n"),
+            Ok(seq_from_str("GATC"))
+        );
+        assert_eq!(
+            seq_from_letters::<Nucleotide>(b"GAXT"),
+            Err(SeqError::InvalidSymbol {
+                symbol: 'X',
+                position: 2,
+                alphabet: "nucleotide"
+            })
+        );
+        assert_eq!(
+            seq_from_letters::<AminoAcid>(b"mk W"),
+            Ok(seq_aa_from_str("MKW"))
+        );
+        assert_eq!(
+            seq_from_letters::<AminoAcid>(b"MK1"),
+            Err(SeqError::InvalidSymbol {
+                symbol: '1',
+                position: 2,
+                alphabet: "amino acid"
+            })
+        );
+
+        assert_eq!(seq_to_letters(&seq_from_str("GATTACA")), b"GATTACA");
+        assert_eq!(seq_to_letters(&seq_aa_from_str("MKWU")), b"MKWU");
+    }
+
+    #[test]
+    fn symbols_and_indices() {
+        assert_eq!((Nucleotide::SIZE, Nucleotide::BITS), (4, 2));
+        assert_eq!((AminoAcid::SIZE, AminoAcid::BITS), (21, 5));
+
+        for (i, nt) in Nucleotide::SYMBOLS.iter().enumerate() {
+            assert_eq!(nt.to_index(), i);
+            assert_eq!(Nucleotide::from_index(i), Some(*nt));
+        }
+        for (i, aa) in AminoAcid::SYMBOLS.iter().enumerate() {
+            assert_eq!(aa.to_index(), i);
+            assert_eq!(AminoAcid::from_u8_letter(aa.to_u8_letter()), Ok(*aa));
+        }
+        assert_eq!(AminoAcid::from_index(21), None);
+    }
+
+    #[test]
+    fn composition_counts() {
+        // Index order is T, C, A, G.
+        assert_eq!(composition(&seq_from_str("GATTACA")), vec![2, 1, 3, 1]);
+
+        let counts = composition(&seq_aa_from_str("MKKWK"));
+        assert_eq!(counts.len(), 21);
+        assert_eq!(counts[AminoAcid::Lys.to_index()], 3);
+        assert_eq!(counts[AminoAcid::Met.to_index()], 1);
+        assert_eq!(counts.iter().sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn entropy() {
+        assert_eq!(shannon_entropy(&seq_from_str("ACGT")), 2.);
+        assert_eq!(shannon_entropy(&seq_from_str("AAAA")), 0.);
+        assert_eq!(shannon_entropy::<Nucleotide>(&[]), 0.);
+        assert_eq!(shannon_entropy(&seq_from_str("AACC")), 1.);
+
+        assert_eq!(shannon_entropy(&seq_aa_from_str("WWWW")), 0.);
+        let all = AminoAcid::SYMBOLS;
+        assert!((shannon_entropy(all) - 21_f32.log2()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reverse_complements() {
+        assert_eq!(
+            reverse_complement(&seq_from_str("GATTACA")),
+            Some(seq_from_str("TGTAATC"))
+        );
+        assert_eq!(reverse_complement::<Nucleotide>(&[]), Some(Vec::new()));
+
+        assert_eq!(reverse_complement(&seq_aa_from_str("MKW")), None);
+        assert_eq!(reverse_complement::<AminoAcid>(&[]), None);
+    }
+
+    #[test]
+    fn random_symbols() {
+        let mut rng = Rng::new(0xA1FA);
+
+        let seq: Vec<Nucleotide> = rng.symbols(1_000);
+        assert!(composition(&seq).iter().all(|c| *c > 0));
+
+        let seq: Vec<AminoAcid> = rng.symbols(1_000);
+        assert_eq!(seq.len(), 1_000);
+        assert!(composition(&seq).iter().all(|c| *c > 0));
+    }
+}
@@ -1,8 +1,8 @@
-use std::{fmt, io, str::FromStr};
+use std::{fmt, str::FromStr};
 
 use bincode::{Decode, Encode};
 
-use crate::{Nucleotide, Nucleotide::*};
+use crate::{seq_complement, Nucleotide, Nucleotide::*, SeqError};
 
 #[derive(Clone, Copy, PartialEq, Encode, Decode)]
 pub enum AaIdent {
@@ -10,14 +10,60 @@ pub enum AaIdent {
     ThreeLetters,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum CodingResult {
     AminoAcid(AminoAcid),
     StopCodon,
 }
 
+/// The monoisotopic mass of water, in Da. A peptide weighs the sum of its residues, plus one water.
+pub const WATER_WEIGHT_MONO: f64 = 18.010_565;
+/// The average mass of water, in Da.
+pub const WATER_WEIGHT_AVG: f64 = 18.015_28;
+
+/// A set of pKa values, for calculating charge and isoelectric point.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PkaSet {
+    /// From EMBOSS's `iep`.
+    #[default]
+    Emboss,
+    /// Bjellqvist et al, 1993; used by ExPASy's Compute pI/Mw. Terminal pKas depend on the terminal residue.
+    Bjellqvist,
+}
+
+impl PkaSet {
+    /// The pKa of the N-terminal amino group, given the first residue.
+    pub fn n_term(self, first: AminoAcid) -> f32 {
+        match self {
+            Self::Emboss => 8.6,
+            Self::Bjellqvist => match first {
+                AminoAcid::Ala => 7.59,
+                AminoAcid::Met => 7.0,
+                AminoAcid::Ser => 6.93,
+                AminoAcid::Pro => 8.36,
+                AminoAcid::Thr => 6.82,
+                AminoAcid::Val => 7.44,
+                AminoAcid::Glu => 7.7,
+                _ => 7.5,
+            },
+        }
+    }
+
+    /// The pKa of the C-terminal carboxyl group, given the last residue.
+    pub fn c_term(self, last: AminoAcid) -> f32 {
+        match self {
+            Self::Emboss => 3.6,
+            Self::Bjellqvist => match last {
+                AminoAcid::Asp => 4.55,
+                AminoAcid::Glu => 4.75,
+                _ => 3.55,
+            },
+        }
+    }
+}
+
 /// This struct and its methods are largely copied from the `peptide` project.
-#[derive(Clone, Copy, PartialEq, Encode, Decode)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Encode, Decode)]
 pub enum AminoAcid {
     Arg,
     His,
@@ -154,35 +200,98 @@ impl AminoAcid {
         format!(" {} ", self.to_str(AaIdent::OneLetter))
     }
 
-    /// Return the molecular weight, in Da.
-    /// Source: https://www.promega.com/resources/tools/amino-acid-chart-amino-acid-structure/
-    /// todo: This table is not very precise; consider updating with a better source.
+    /// The average molecular weight of the free amino acid, in Da: Its residue weight, plus water. For
+    /// the weight of a peptide or protein, use `protein::protein_weight`.
     pub fn weight(&self) -> f32 {
+        (self.residue_weight_avg() + WATER_WEIGHT_AVG) as f32
+    }
+
+    /// The average mass of this amino acid as a residue in a peptide chain (i.e. less water), in Da.
+    /// Source: [ExPASy](https://web.expasy.org/findmod/findmod_masses.html)
+    pub fn residue_weight_avg(&self) -> f64 {
         match self {
-            Self::Arg => 174.,
-            Self::His => 155.,
-            Self::Lys => 146.,
-            Self::Asp => 133.,
-            Self::Glu => 147.,
-            Self::Ser => 105.,
-            Self::Thr => 119.,
-            Self::Asn => 132.,
-            Self::Gln => 146.,
-            Self::Cys => 121.,
-            Self::Sec => 168.06,
-            Self::Gly => 75.,
-            Self::Pro => 115.,
-            Self::Ala => 89.,
-            Self::Val => 117.,
-            Self::Ile => 131.,
-            Self::Leu => 131.,
-            Self::Met => 149.,
-            Self::Phe => 165.,
-            Self::Tyr => 181.,
-            Self::Trp => 204.,
+            Self::Arg => 156.1875,
+            Self::His => 137.1411,
+            Self::Lys => 128.1741,
+            Self::Asp => 115.0886,
+            Self::Glu => 129.1155,
+            Self::Ser => 87.0782,
+            Self::Thr => 101.1051,
+            Self::Asn => 114.1038,
+            Self::Gln => 128.1307,
+            Self::Cys => 103.1388,
+            Self::Sec => 150.0388,
+            Self::Gly => 57.0519,
+            Self::Pro => 97.1167,
+            Self::Ala => 71.0788,
+            Self::Val => 99.1326,
+            Self::Ile => 113.1594,
+            Self::Leu => 113.1594,
+            Self::Met => 131.1926,
+            Self::Phe => 147.1766,
+            Self::Tyr => 163.176,
+            Self::Trp => 186.2132,
         }
     }
 
+    /// The monoisotopic mass of this amino acid as a residue in a peptide chain (i.e. less water), in Da.
+    /// Source: [ExPASy](https://web.expasy.org/findmod/findmod_masses.html)
+    pub fn residue_weight_mono(&self) -> f64 {
+        match self {
+            Self::Arg => 156.10111,
+            Self::His => 137.05891,
+            Self::Lys => 128.09496,
+            Self::Asp => 115.02694,
+            Self::Glu => 129.04259,
+            Self::Ser => 87.03203,
+            Self::Thr => 101.04768,
+            Self::Asn => 114.04293,
+            Self::Gln => 128.05858,
+            Self::Cys => 103.00919,
+            Self::Sec => 150.95364,
+            Self::Gly => 57.02146,
+            Self::Pro => 97.05276,
+            Self::Ala => 71.03711,
+            Self::Val => 99.06841,
+            Self::Ile => 113.08406,
+            Self::Leu => 113.08406,
+            Self::Met => 131.04049,
+            Self::Phe => 147.06841,
+            Self::Tyr => 163.06333,
+            Self::Trp => 186.07931,
+        }
+    }
+
+    /// The side chain pKa, for ionizable amino acids. `None` for others. Sec's selenol (pKa about 5.2)
+    /// isn't in either set; we include it for both.
+    pub fn pka(&self, set: PkaSet) -> Option<f32> {
+        let result = match set {
+            PkaSet::Emboss => match self {
+                Self::Asp => 3.9,
+                Self::Glu => 4.1,
+                Self::Cys => 8.5,
+                Self::Tyr => 10.1,
+                Self::His => 6.5,
+                Self::Lys => 10.8,
+                Self::Arg => 12.5,
+                Self::Sec => 5.2,
+                _ => return None,
+            },
+            PkaSet::Bjellqvist => match self {
+                Self::Asp => 4.05,
+                Self::Glu => 4.45,
+                Self::Cys => 9.0,
+                Self::Tyr => 10.0,
+                Self::His => 5.98,
+                Self::Lys => 10.0,
+                Self::Arg => 12.0,
+                Self::Sec => 5.2,
+                _ => return None,
+            },
+        };
+        Some(result)
+    }
+
     /// Used for determining protein hydropathy. High (eg positive) values intdicate hydrophilic
     /// AAs. (Seems to not be completely true from some example checks? Some traditionally hydrophilic
     /// proteins like Proline (-1.6) and Glycine (-4) are on the list, but the very negative values
@@ -214,19 +323,55 @@ impl AminoAcid {
         }
     }
 
+    /// All codons that code for this amino acid, in the standard genetic code.
     /// https://en.wikipedia.org/wiki/DNA_and_RNA_codon_tables#/media/File:Aminoacids_table.svg
-    /// If a codon has less than 3 nucleotides, it means the third can be any; this may have both conciseness,
-    /// and performance advantages.
-    pub fn codons(&self) -> Vec<Vec<Nucleotide>> {
+    ///
+    /// Note: Selenocysteine is encoded by TGA, which is normally a stop codon; it's only read as Sec
+    /// in the context of a SECIS element. `from_codons` returns `StopCodon` for it.
+    pub fn codons(&self) -> Vec<[Nucleotide; 3]> {
         match self {
-            // todo: Should we do wildcards etc, to speed up matching? Ie Arg is just [C, G].
-            Self::Arg => vec![vec![C, G]],
-            Self::Gln => vec![vec![C, A, G], vec![C, A, A]],
-            Self::His => vec![vec![C, A, C], vec![C, A, T]],
-            Self::Pro => vec![vec![C, C]],
-            Self::Leu => vec![vec![C, T]],
-            Self::Met => vec![vec![A, T, G]],
-            _ => Vec::new(),
+            Self::Arg => vec![
+                [C, G, T],
+                [C, G, C],
+                [C, G, A],
+                [C, G, G],
+                [A, G, A],
+                [A, G, G],
+            ],
+            Self::His => vec![[C, A, T], [C, A, C]],
+            Self::Lys => vec![[A, A, A], [A, A, G]],
+            Self::Asp => vec![[G, A, T], [G, A, C]],
+            Self::Glu => vec![[G, A, A], [G, A, G]],
+            Self::Ser => vec![
+                [T, C, T],
+                [T, C, C],
+                [T, C, A],
+                [T, C, G],
+                [A, G, T],
+                [A, G, C],
+            ],
+            Self::Thr => vec![[A, C, T], [A, C, C], [A, C, A], [A, C, G]],
+            Self::Asn => vec![[A, A, T], [A, A, C]],
+            Self::Gln => vec![[C, A, A], [C, A, G]],
+            Self::Cys => vec![[T, G, T], [T, G, C]],
+            Self::Sec => vec![[T, G, A]],
+            Self::Gly => vec![[G, G, T], [G, G, C], [G, G, A], [G, G, G]],
+            Self::Pro => vec![[C, C, T], [C, C, C], [C, C, A], [C, C, G]],
+            Self::Ala => vec![[G, C, T], [G, C, C], [G, C, A], [G, C, G]],
+            Self::Val => vec![[G, T, T], [G, T, C], [G, T, A], [G, T, G]],
+            Self::Ile => vec![[A, T, T], [A, T, C], [A, T, A]],
+            Self::Leu => vec![
+                [C, T, T],
+                [C, T, C],
+                [C, T, A],
+                [C, T, G],
+                [T, T, A],
+                [T, T, G],
+            ],
+            Self::Met => vec![[A, T, G]],
+            Self::Phe => vec![[T, T, T], [T, T, C]],
+            Self::Tyr => vec![[T, A, T], [T, A, C]],
+            Self::Trp => vec![[T, G, G]],
         }
     }
 
@@ -284,7 +429,7 @@ impl AminoAcid {
 }
 
 impl FromStr for AminoAcid {
-    type Err = io::Error;
+    type Err = SeqError;
 
     fn from_str(val: &str) -> Result<Self, Self::Err> {
         Ok(match val.to_uppercase().as_str() {
@@ -309,12 +454,7 @@ impl FromStr for AminoAcid {
             "F" | "PHE" => Self::Phe,
             "Y" | "TYR" => Self::Tyr,
             "W" | "TRP" => Self::Trp,
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Invalid amino acid string provided",
-                ))
-            }
+            _ => return Err(SeqError::InvalidAminoAcid(val.to_owned())),
         })
     }
 }
@@ -330,3 +470,243 @@ impl fmt::Display for AminoAcid {
         write!(f, "{}", v)
     }
 }
+
+/// Translate a nucleotide sequence into a sequence of coding results, reading codons starting at
+/// `frame` (0, 1, or 2). Stop codons are included. A trailing partial codon is dropped.
+pub fn translate(seq: &[Nucleotide], frame: usize) -> Vec<CodingResult> {
+    if frame >= seq.len() {
+        return Vec::new();
+    }
+
+    translate_iter(&seq[frame..]).collect()
+}
+
+/// Iterate over a sequence's codons, from its start. A trailing partial codon is dropped.
+pub fn codons(seq: &[Nucleotide]) -> impl ExactSizeIterator<Item = [Nucleotide; 3]> + '_ {
+    seq.chunks_exact(3).map(|c| [c[0], c[1], c[2]])
+}
+
+/// Like `translate`, from the start of the sequence, but yields results lazily, without allocating.
+pub fn translate_iter(seq: &[Nucleotide]) -> impl ExactSizeIterator<Item = CodingResult> + '_ {
+    codons(seq).map(AminoAcid::from_codons)
+}
+
+/// Translate a sequence into a protein, from its start. If `stop_at_first_stop` is set, the protein ends
+/// at the first stop codon; otherwise stop codons are skipped.
+///
+/// This is named `translate_protein` rather than `translate`, since that name is taken by the
+/// frame-based translation above, which keeps stop codons.
+pub fn translate_protein(seq: &[Nucleotide], stop_at_first_stop: bool) -> Vec<AminoAcid> {
+    let mut result = Vec::with_capacity(seq.len() / 3);
+
+    for coding in translate_iter(seq) {
+        match coding {
+            CodingResult::AminoAcid(aa) => result.push(aa),
+            CodingResult::StopCodon if stop_at_first_stop => break,
+            CodingResult::StopCodon => (),
+        }
+    }
+
+    result
+}
+
+/// Translate the reverse complement of a sequence, reading codons starting at `frame` (0, 1, or 2)
+/// from its 5' end. Together with `translate`, this gives access to all six reading frames.
+pub fn translate_reverse(seq: &[Nucleotide], frame: usize) -> Vec<CodingResult> {
+    translate(&seq_complement(seq), frame)
+}
+
+/// Translate all six reading frames. Frames are ordered forward 0, 1, 2, then reverse 0, 1, 2, where
+/// reverse frames are offsets from the 5' end of the reverse complement.
+pub fn translate_six_frames(seq: &[Nucleotide]) -> [Vec<CodingResult>; 6] {
+    let seq_rev = seq_complement(seq);
+
+    [
+        translate(seq, 0),
+        translate(seq, 1),
+        translate(seq, 2),
+        translate(&seq_rev, 0),
+        translate(&seq_rev, 1),
+        translate(&seq_rev, 2),
+    ]
+}
+
+/// Translate a nucleotide sequence starting at `frame` (0, 1, or 2), stopping at the first stop codon.
+/// The stop codon itself is not included.
+pub fn translate_until_stop(seq: &[Nucleotide], frame: usize) -> Vec<AminoAcid> {
+    let mut result = Vec::new();
+
+    for coding_result in translate(seq, frame) {
+        match coding_result {
+            CodingResult::AminoAcid(aa) => result.push(aa),
+            CodingResult::StopCodon => break,
+        }
+    }
+
+    result
+}
+
+/// Amino acids serialize as their one-letter code, e.g. `"R"`, in human-readable formats like JSON, and
+/// deserialize from either case. In binary formats, they use their index, as in `Alphabet::to_index`.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::AminoAcid;
+    use crate::alphabet::Alphabet;
+
+    impl Serialize for AminoAcid {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.serialize_str(&(self.to_u8_upper() as char).to_string())
+            } else {
+                serializer.serialize_u8(self.to_index() as u8)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for AminoAcid {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                let val = String::deserialize(deserializer)?;
+                match val.as_bytes() {
+                    [letter] => {
+                        <Self as Alphabet>::from_u8_letter(*letter).map_err(de::Error::custom)
+                    }
+                    _ => Err(de::Error::custom(format!(
+                        "Expected a single amino acid letter; got \"{val}\""
+                    ))),
+                }
+            } else {
+                let val = u8::deserialize(deserializer)?;
+                Self::from_index(val as usize)
+                    .ok_or_else(|| de::Error::custom(format!("Invalid amino acid index: {val}")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_aa_from_str, seq_from_str};
+
+    const ALL: [AminoAcid; 21] = [
+        AminoAcid::Arg,
+        AminoAcid::His,
+        AminoAcid::Lys,
+        AminoAcid::Asp,
+        AminoAcid::Glu,
+        AminoAcid::Ser,
+        AminoAcid::Thr,
+        AminoAcid::Asn,
+        AminoAcid::Gln,
+        AminoAcid::Cys,
+        AminoAcid::Sec,
+        AminoAcid::Gly,
+        AminoAcid::Pro,
+        AminoAcid::Ala,
+        AminoAcid::Val,
+        AminoAcid::Ile,
+        AminoAcid::Leu,
+        AminoAcid::Met,
+        AminoAcid::Phe,
+        AminoAcid::Tyr,
+        AminoAcid::Trp,
+    ];
+
+    /// Coding results from one-letter codes, with `*` for a stop codon.
+    fn coded(aas: &str) -> Vec<CodingResult> {
+        aas.chars()
+            .map(|c| match c {
+                '*' => CodingResult::StopCodon,
+                _ => CodingResult::AminoAcid(seq_aa_from_str(&c.to_string())[0]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn codons_round_trip() {
+        for aa in ALL {
+            let codons = aa.codons();
+            assert!(!codons.is_empty(), "{aa:?}");
+
+            for codon in codons {
+                let expected = match aa {
+                    // Only read as Sec with a SECIS element.
+                    AminoAcid::Sec => CodingResult::StopCodon,
+                    _ => CodingResult::AminoAcid(aa),
+                };
+                assert_eq!(AminoAcid::from_codons(codon), expected, "{aa:?} {codon:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn codons_cover_genetic_code() {
+        let nts = [T, C, A, G];
+        let mut stops = 0;
+
+        for a in nts {
+            for b in nts {
+                for c in nts {
+                    let codon: [Nucleotide; 3] = [a, b, c];
+                    match AminoAcid::from_codons(codon) {
+                        CodingResult::AminoAcid(aa) => assert!(aa.codons().contains(&codon)),
+                        CodingResult::StopCodon => stops += 1,
+                    }
+
+                    // Each codon belongs to exactly one amino acid, aside from TGA, which is shared by Sec.
+                    let count = ALL.iter().filter(|aa| aa.codons().contains(&codon)).count();
+                    let expected = match codon {
+                        [T, A, A] | [T, A, G] => 0,
+                        _ => 1,
+                    };
+                    assert_eq!(count, expected, "{codon:?}");
+                }
+            }
+        }
+
+        assert_eq!(stops, 3);
+    }
+
+    #[test]
+    fn six_frames() {
+        // Its reverse complement is CTTACATGG.
+        let seq = seq_from_str("CCATGTAAG");
+
+        let frames = translate_six_frames(&seq);
+        assert_eq!(frames[0], coded("PCK"));
+        assert_eq!(frames[1], coded("HV"));
+        assert_eq!(frames[2], coded("M*"));
+        assert_eq!(frames[3], coded("LTW"));
+        assert_eq!(frames[4], coded("LH"));
+        assert_eq!(frames[5], coded("YM"));
+
+        for frame in 0..3 {
+            assert_eq!(frames[frame], translate(&seq, frame));
+            assert_eq!(frames[frame + 3], translate_reverse(&seq, frame));
+        }
+    }
+
+    #[test]
+    fn translate_iter_len() {
+        for len in 0..10 {
+            let seq = vec![A; len];
+            let iter = translate_iter(&seq);
+
+            assert_eq!(iter.len(), len / 3);
+            assert_eq!(iter.count(), len / 3);
+        }
+    }
+
+    #[test]
+    fn translate_protein_stops() {
+        // ATG AAA TAA GGG TGA CCC T; the trailing T is a partial codon.
+        let seq = seq_from_str("ATGAAATAAGGGTGACCCT");
+
+        assert_eq!(translate_protein(&seq, true), seq_aa_from_str("MK"));
+        assert_eq!(translate_protein(&seq, false), seq_aa_from_str("MKGP"));
+        assert_eq!(translate_protein(&seq[6..], true), Vec::new());
+    }
+}
@@ -0,0 +1,358 @@
+//! This module contains codon usage tables, reverse translation (protein to DNA) using them, and
+//! codon-level metrics: The codon adaptation index, and GC3 content.
+//!
+//! Default tables are relative codon frequencies per amino acid, from the
+//! [Kazusa codon usage database](https://www.kazusa.or.jp/codon/).
+
+use std::collections::HashMap;
+
+use crate::{
+    amino_acids::{AminoAcid, CodingResult},
+    calc_gc,
+    rng::Rng,
+    seq_from_str, Nucleotide, Seq, SeqError,
+};
+
+/// Organisms with built-in codon usage tables.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Organism {
+    /// Escherichia coli K-12.
+    #[default]
+    EColi,
+    /// Homo sapiens.
+    Human,
+}
+
+/// Relative codon frequencies, for each amino acid. Frequencies for a given amino acid sum to 1.
+#[derive(Clone, Debug, Default)]
+pub struct CodonUsage {
+    pub freqs: HashMap<AminoAcid, Vec<([Nucleotide; 3], f32)>>,
+}
+
+/// (Codon, fraction among codons for its amino acid)
+#[rustfmt::skip]
+const E_COLI: [(&str, f32); 61] = [
+    ("GCG", 0.36), ("GCC", 0.27), ("GCA", 0.21), ("GCT", 0.16),
+    ("CGC", 0.40), ("CGT", 0.38), ("CGG", 0.10), ("CGA", 0.06), ("AGA", 0.04), ("AGG", 0.02),
+    ("AAC", 0.55), ("AAT", 0.45),
+    ("GAT", 0.63), ("GAC", 0.37),
+    ("TGC", 0.56), ("TGT", 0.44),
+    ("CAG", 0.65), ("CAA", 0.35),
+    ("GAA", 0.69), ("GAG", 0.31),
+    ("GGC", 0.40), ("GGT", 0.34), ("GGG", 0.15), ("GGA", 0.11),
+    ("CAT", 0.57), ("CAC", 0.43),
+    ("ATT", 0.51), ("ATC", 0.42), ("ATA", 0.07),
+    ("CTG", 0.50), ("TTA", 0.13), ("TTG", 0.13), ("CTT", 0.10), ("CTC", 0.10), ("CTA", 0.04),
+    ("AAA", 0.76), ("AAG", 0.24),
+    ("ATG", 1.),
+    ("TTT", 0.57), ("TTC", 0.43),
+    ("CCG", 0.52), ("CCA", 0.19), ("CCT", 0.16), ("CCC", 0.12),
+    ("AGC", 0.28), ("TCT", 0.15), ("TCC", 0.15), ("AGT", 0.15), ("TCG", 0.15), ("TCA", 0.12),
+    ("ACC", 0.44), ("ACG", 0.27), ("ACT", 0.17), ("ACA", 0.13),
+    ("TGG", 1.),
+    ("TAT", 0.57), ("TAC", 0.43),
+    ("GTG", 0.37), ("GTT", 0.26), ("GTC", 0.22), ("GTA", 0.15),
+];
+
+#[rustfmt::skip]
+const HUMAN: [(&str, f32); 61] = [
+    ("GCC", 0.40), ("GCT", 0.26), ("GCA", 0.23), ("GCG", 0.11),
+    ("CGG", 0.21), ("AGA", 0.20), ("AGG", 0.20), ("CGC", 0.19), ("CGA", 0.11), ("CGT", 0.08),
+    ("AAC", 0.54), ("AAT", 0.46),
+    ("GAC", 0.54), ("GAT", 0.46),
+    ("TGC", 0.55), ("TGT", 0.45),
+    ("CAG", 0.75), ("CAA", 0.25),
+    ("GAG", 0.58), ("GAA", 0.42),
+    ("GGC", 0.34), ("GGA", 0.25), ("GGG", 0.25), ("GGT", 0.16),
+    ("CAC", 0.59), ("CAT", 0.41),
+    ("ATC", 0.48), ("ATT", 0.36), ("ATA", 0.16),
+    ("CTG", 0.41), ("CTC", 0.20), ("CTT", 0.13), ("TTG", 0.13), ("TTA", 0.07), ("CTA", 0.07),
+    ("AAG", 0.58), ("AAA", 0.42),
+    ("ATG", 1.),
+    ("TTC", 0.55), ("TTT", 0.45),
+    ("CCC", 0.33), ("CCT", 0.28), ("CCA", 0.27), ("CCG", 0.11),
+    ("AGC", 0.24), ("TCC", 0.22), ("TCT", 0.18), ("TCA", 0.15), ("AGT", 0.15), ("TCG", 0.06),
+    ("ACC", 0.36), ("ACA", 0.28), ("ACT", 0.24), ("ACG", 0.12),
+    ("TGG", 1.),
+    ("TAC", 0.57), ("TAT", 0.43),
+    ("GTG", 0.47), ("GTC", 0.24), ("GTT", 0.18), ("GTA", 0.11),
+];
+
+impl CodonUsage {
+    fn from_table(table: &[(&str, f32)]) -> Self {
+        let counts: Vec<_> = table
+            .iter()
+            .map(|(codon, freq)| {
+                let codon = seq_from_str(codon);
+                ([codon[0], codon[1], codon[2]], *freq)
+            })
+            .collect();
+
+        Self::from_counts(&counts)
+    }
+
+    /// Escherichia coli K-12.
+    pub fn e_coli() -> Self {
+        Self::from_table(&E_COLI)
+    }
+
+    /// Homo sapiens.
+    pub fn human() -> Self {
+        Self::from_table(&HUMAN)
+    }
+
+    /// The built-in table for an organism.
+    pub fn for_organism(organism: Organism) -> Self {
+        match organism {
+            Organism::EColi => Self::e_coli(),
+            Organism::Human => Self::human(),
+        }
+    }
+
+    /// Create a table from codon counts, or un-normalized frequencies, e.g. from a set of highly-expressed
+    /// genes. Stop codons are ignored.
+    pub fn from_counts(counts: &[([Nucleotide; 3], f32)]) -> Self {
+        let mut freqs: HashMap<AminoAcid, Vec<([Nucleotide; 3], f32)>> = HashMap::new();
+
+        for (codon, count) in counts {
+            if let CodingResult::AminoAcid(aa) = AminoAcid::from_codons(*codon) {
+                freqs.entry(aa).or_default().push((*codon, *count));
+            }
+        }
+
+        for codons in freqs.values_mut() {
+            let total: f32 = codons.iter().map(|(_, c)| c).sum();
+            if total > 0. {
+                for (_, c) in codons.iter_mut() {
+                    *c /= total;
+                }
+            }
+            codons.sort_by(|a, b| b.1.total_cmp(&a.1));
+        }
+
+        Self { freqs }
+    }
+
+    /// The most frequently-used codon for an amino acid. Falls back to the first codon from
+    /// `AminoAcid::codons` if the table has no entry for it.
+    pub fn most_frequent(&self, aa: AminoAcid) -> [Nucleotide; 3] {
+        match self.freqs.get(&aa).and_then(|c| c.first()) {
+            Some((codon, _)) => *codon,
+            None => aa.codons()[0],
+        }
+    }
+}
+
+/// Convert a protein sequence to DNA, using the most frequent codon for each amino acid.
+///
+/// Note: Selenocysteine is encoded as TGA, its only codon. This is normally a stop codon, so the result
+/// has an in-frame stop wherever the protein has Sec; expressing it requires a SECIS element.
+pub fn reverse_translate(seq: &[AminoAcid], table: &CodonUsage) -> Seq {
+    seq.iter().flat_map(|aa| table.most_frequent(*aa)).collect()
+}
+
+/// Convert a protein sequence to DNA, sampling codons in proportion to their frequency. The same seed
+/// always produces the same sequence. Like `reverse_translate`, Sec is encoded as TGA.
+pub fn reverse_translate_sampled(seq: &[AminoAcid], table: &CodonUsage, seed: u64) -> Seq {
+    let mut rng = Rng::new(seed);
+    let mut result = Vec::with_capacity(seq.len() * 3);
+
+    for aa in seq {
+        let codon = match table.freqs.get(aa) {
+            Some(codons) if !codons.is_empty() => {
+                let mut val = rng.next_f32();
+                let mut chosen = codons[codons.len() - 1].0;
+
+                for (codon, freq) in codons {
+                    if val < *freq {
+                        chosen = *codon;
+                        break;
+                    }
+                    val -= freq;
+                }
+                chosen
+            }
+            _ => table.most_frequent(*aa),
+        };
+
+        result.extend(codon);
+    }
+
+    result
+}
+
+/// Relative adaptiveness assigned to codons the table gives no usage for, as suggested by Sharp and Li.
+const CAI_WEIGHT_MISSING: f32 = 0.5;
+
+/// The codon adaptation index (Sharp and Li, 1987) of a coding sequence: The geometric mean, over its
+/// codons, of each codon's frequency relative to the most frequent codon for its amino acid. Ranges from
+/// near 0 to 1; higher values indicate codon usage closer to the table's.
+///
+/// The sequence is read in frame, from its start, and must be a whole number of codons. Stop codons, and
+/// amino acids with only one codon (Met, Trp), are skipped. Returns 0 if no codons remain.
+pub fn codon_adaptation_index(seq: &[Nucleotide], table: &CodonUsage) -> Result<f32, SeqError> {
+    check_codon_aligned(seq)?;
+
+    let mut log_sum = 0.;
+    let mut count = 0;
+
+    for codon in seq.chunks_exact(3) {
+        let codon = [codon[0], codon[1], codon[2]];
+
+        let CodingResult::AminoAcid(aa) = AminoAcid::from_codons(codon) else {
+            continue;
+        };
+        if aa.codons().len() < 2 {
+            continue;
+        }
+
+        let weight = match table.freqs.get(&aa) {
+            Some(codons) if !codons.is_empty() => {
+                // Codons are sorted by frequency, most frequent first.
+                let max = codons[0].1;
+                let freq = codons
+                    .iter()
+                    .find(|(c, _)| *c == codon)
+                    .map(|(_, f)| *f)
+                    .unwrap_or_default();
+
+                if freq > 0. && max > 0. {
+                    freq / max
+                } else {
+                    CAI_WEIGHT_MISSING
+                }
+            }
+            _ => CAI_WEIGHT_MISSING,
+        };
+
+        log_sum += weight.ln();
+        count += 1;
+    }
+
+    if count == 0 {
+        return Ok(0.);
+    }
+
+    Ok((log_sum / count as f32).exp())
+}
+
+fn check_codon_aligned(seq: &[Nucleotide]) -> Result<(), SeqError> {
+    if !seq.len().is_multiple_of(3) {
+        return Err(SeqError::IncompleteCodon(seq.len()));
+    }
+    Ok(())
+}
+
+/// The GC content at third codon positions, on a scale of 0 to 1. The sequence is read in frame, from its
+/// start, and must be a whole number of codons. Returns 0 for an empty sequence.
+pub fn gc3_content(seq: &[Nucleotide]) -> Result<f32, SeqError> {
+    check_codon_aligned(seq)?;
+
+    if seq.is_empty() {
+        return Ok(0.);
+    }
+
+    let third: Vec<_> = seq.iter().skip(2).step_by(3).copied().collect();
+    Ok(calc_gc(&third))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        amino_acids::{codons, translate_protein},
+        seq_aa_from_str,
+    };
+
+    #[test]
+    fn reverse_translate_round_trip() {
+        // Human insulin B chain.
+        let protein = seq_aa_from_str("FVNQHLCGSHLVEALYLVCGERGFFYTPKT");
+
+        for table in [CodonUsage::e_coli(), CodonUsage::human()] {
+            let dna = reverse_translate(&protein, &table);
+
+            assert_eq!(dna.len(), protein.len() * 3);
+            assert_eq!(translate_protein(&dna, true), protein);
+        }
+
+        assert_eq!(
+            reverse_translate(&seq_aa_from_str("MK"), &CodonUsage::e_coli()),
+            seq_from_str("ATGAAA")
+        );
+    }
+
+    #[test]
+    fn reverse_translate_sec() {
+        // Sec has no entry in the tables, and falls back to TGA: an in-frame stop codon.
+        let protein = [AminoAcid::Met, AminoAcid::Sec, AminoAcid::Lys];
+        let dna = reverse_translate(&protein, &CodonUsage::e_coli());
+
+        assert_eq!(dna, seq_from_str("ATGTGAAAA"));
+        assert_eq!(
+            AminoAcid::from_codons([dna[3], dna[4], dna[5]]),
+            CodingResult::StopCodon
+        );
+        assert_eq!(translate_protein(&dna, true), vec![AminoAcid::Met]);
+    }
+
+    #[test]
+    fn cai() {
+        let table = CodonUsage::e_coli();
+        let protein = seq_aa_from_str("MKVLAAGIRNDESTQWYFHPC");
+
+        let optimal = reverse_translate(&protein, &table);
+        let cai = codon_adaptation_index(&optimal, &table).unwrap();
+        assert!((cai - 1.).abs() < 1e-6);
+
+        // Met is skipped; Leu CTA has 0.04 / 0.50 the usage of CTG.
+        let cai = codon_adaptation_index(&seq_from_str("ATGCTGCTA"), &table).unwrap();
+        assert!((cai - 0.08_f32.sqrt()).abs() < 1e-6);
+
+        // Only single-codon amino acids and stops.
+        assert_eq!(
+            codon_adaptation_index(&seq_from_str("ATGTGGTAA"), &table),
+            Ok(0.)
+        );
+    }
+
+    #[test]
+    fn gc3() {
+        // Third positions: G, C, A, G
+        assert_eq!(gc3_content(&seq_from_str("ATGGCCAAATTG")), Ok(0.75));
+        assert_eq!(gc3_content(&seq_from_str("AAAAAT")), Ok(0.));
+        assert_eq!(gc3_content(&[]), Ok(0.));
+    }
+
+    #[test]
+    fn incomplete_codon() {
+        let seq = seq_from_str("ATGGCCAA");
+
+        assert_eq!(gc3_content(&seq), Err(SeqError::IncompleteCodon(8)));
+        assert_eq!(
+            codon_adaptation_index(&seq, &CodonUsage::human()),
+            Err(SeqError::IncompleteCodon(8))
+        );
+    }
+
+    #[test]
+    fn reverse_translate_sampled_deterministic() {
+        let table = CodonUsage::human();
+        let protein = vec![AminoAcid::Leu; 200];
+
+        let dna = reverse_translate_sampled(&protein, &table, 7);
+        assert_eq!(dna, reverse_translate_sampled(&protein, &table, 7));
+        assert_ne!(dna, reverse_translate_sampled(&protein, &table, 8));
+
+        let leu_codons = AminoAcid::Leu.codons();
+        let mut used = Vec::new();
+        for codon in codons(&dna) {
+            assert!(leu_codons.contains(&codon));
+            if !used.contains(&codon) {
+                used.push(codon);
+            }
+        }
+        // Sampling, rather than always picking CTG.
+        assert!(used.len() > 1);
+    }
+}
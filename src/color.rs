@@ -0,0 +1,302 @@
+//! This module contains color schemes for displaying nucleotide and amino acid sequences. Colors are
+//! (r, g, b), on a scale of 0 to 1.
+
+use crate::{
+    AminoAcid::{self, *},
+    Nucleotide,
+};
+
+pub type Color = (f32, f32, f32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NtColorScheme {
+    /// A green, C blue, G orange, T red.
+    #[default]
+    Classic,
+    /// Distinguishable with common forms of color blindness. From the Okabe-Ito palette.
+    ColorblindSafe,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum AaColorScheme {
+    /// ClustalW residue classes: Hydrophobic red, acidic blue, basic magenta, other green.
+    #[default]
+    Clustal,
+    /// Physicochemical classes, as in Jalview.
+    Zappo,
+    /// Kyte-Doolittle hydropathicity, on a ramp from blue (hydrophilic), through white, to red (hydrophobic).
+    Hydrophobicity,
+    /// Side-chain charge at neutral pH: Positive blue, negative red, His light blue, uncharged grey.
+    Charge,
+    /// Taylor, 1997: A distinct color for each residue.
+    Taylor,
+}
+
+const fn hex(val: u32) -> Color {
+    (
+        ((val >> 16) & 0xff) as f32 / 255.,
+        ((val >> 8) & 0xff) as f32 / 255.,
+        (val & 0xff) as f32 / 255.,
+    )
+}
+
+/// Linearly interpolate between control points, evenly spaced from 0 to 1. `t` is clamped to this range.
+fn ramp(points: &[Color], t: f32) -> Color {
+    let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+
+    let last = points.len() - 1;
+    let pos = t * last as f32;
+    let i = pos as usize;
+    // Interpolating towards a point past the end would only approximate the last one.
+    if i >= last {
+        return points[last];
+    }
+    let frac = pos - i as f32;
+
+    let (a, b) = (points[i], points[i + 1]);
+    (
+        a.0 + (b.0 - a.0) * frac,
+        a.1 + (b.1 - a.1) * frac,
+        a.2 + (b.2 - a.2) * frac,
+    )
+}
+
+// Kyte-Doolittle extremes: Arg and Ile.
+const HYDROPATHICITY_MIN: f32 = -4.5;
+const HYDROPATHICITY_MAX: f32 = 4.5;
+
+const HYDROPHOBICITY_RAMP: [Color; 3] = [(0., 0., 1.), (1., 1., 1.), (1., 0., 0.)];
+
+/// Samples of the viridis colormap, which is perceptually uniform, and readable in grayscale.
+const VIRIDIS: [Color; 5] = [
+    (0.267, 0.005, 0.329),
+    (0.229, 0.322, 0.546),
+    (0.128, 0.567, 0.551),
+    (0.369, 0.789, 0.383),
+    (0.993, 0.906, 0.144),
+];
+
+impl Nucleotide {
+    pub fn color(self, scheme: NtColorScheme) -> Color {
+        match scheme {
+            NtColorScheme::Classic => match self {
+                Self::A => hex(0x00_cc_00),
+                Self::C => hex(0x00_00_cc),
+                Self::G => hex(0xff_a5_00),
+                Self::T => hex(0xcc_00_00),
+            },
+            NtColorScheme::ColorblindSafe => match self {
+                Self::A => hex(0x00_9e_73), // Bluish green
+                Self::C => hex(0x00_72_b2), // Blue
+                Self::G => hex(0xe6_9f_00), // Orange
+                Self::T => hex(0xd5_5e_00), // Vermillion
+            },
+        }
+    }
+}
+
+impl AminoAcid {
+    /// Sec, which isn't covered by most schemes, is colored as Cys.
+    pub fn color(self, scheme: AaColorScheme) -> Color {
+        match scheme {
+            AaColorScheme::Clustal => match self {
+                Ala | Val | Phe | Pro | Met | Ile | Leu | Trp => hex(0xff_00_00),
+                Asp | Glu => hex(0x00_00_ff),
+                Arg | Lys => hex(0xff_00_ff),
+                Ser | Thr | Tyr | His | Cys | Sec | Asn | Gly | Gln => hex(0x00_ff_00),
+            },
+            AaColorScheme::Zappo => match self {
+                Ile | Leu | Val | Ala | Met => hex(0xff_af_af),
+                Phe | Trp | Tyr => hex(0xff_c8_00),
+                Lys | Arg | His => hex(0x64_64_ff),
+                Asp | Glu => hex(0xff_00_00),
+                Ser | Thr | Asn | Gln => hex(0x00_ff_00),
+                Pro | Gly => hex(0xff_00_ff),
+                Cys | Sec => hex(0xff_ff_00),
+            },
+            AaColorScheme::Hydrophobicity => {
+                let aa = if self == Sec { Cys } else { self };
+                let t = (aa.hydropathicity() - HYDROPATHICITY_MIN)
+                    / (HYDROPATHICITY_MAX - HYDROPATHICITY_MIN);
+                ramp(&HYDROPHOBICITY_RAMP, t)
+            }
+            AaColorScheme::Charge => match self {
+                Lys | Arg => hex(0x00_00_ff),
+                Asp | Glu => hex(0xff_00_00),
+                His => hex(0x80_80_ff),
+                _ => hex(0xc8_c8_c8),
+            },
+            AaColorScheme::Taylor => match self {
+                Ala => hex(0xcc_ff_00),
+                Arg => hex(0x00_00_ff),
+                Asn => hex(0xcc_00_ff),
+                Asp => hex(0xff_00_00),
+                Cys | Sec => hex(0xff_ff_00),
+                Gln => hex(0xff_00_cc),
+                Glu => hex(0xff_00_66),
+                Gly => hex(0xff_99_00),
+                His => hex(0x00_66_ff),
+                Ile => hex(0x66_ff_00),
+                Leu => hex(0x33_ff_00),
+                Lys => hex(0x66_00_ff),
+                Met => hex(0x00_ff_00),
+                Phe => hex(0x00_ff_66),
+                Pro => hex(0xff_cc_00),
+                Ser => hex(0xff_33_00),
+                Thr => hex(0xff_66_00),
+                Trp => hex(0x00_cc_ff),
+                Tyr => hex(0x00_ff_cc),
+                Val => hex(0x99_ff_00),
+            },
+        }
+    }
+}
+
+/// Map a conservation score, from 0 (unconserved) to 1 (fully conserved), to a color, using the viridis
+/// colormap: Dark purple, through teal, to yellow. Scores outside this range are clamped.
+pub fn conservation_color(score: f32) -> Color {
+    ramp(&VIRIDIS, score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        (r as f32 / 255., g as f32 / 255., b as f32 / 255.)
+    }
+
+    fn assert_close(a: Color, b: Color) {
+        let close = |x: f32, y: f32| (x - y).abs() < 1e-6;
+        assert!(
+            close(a.0, b.0) && close(a.1, b.1) && close(a.2, b.2),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn nucleotide_schemes() {
+        use Nucleotide::*;
+
+        assert_eq!(A.color(NtColorScheme::Classic), rgb(0, 204, 0));
+        assert_eq!(C.color(NtColorScheme::Classic), rgb(0, 0, 204));
+        assert_eq!(G.color(NtColorScheme::Classic), rgb(255, 165, 0));
+        assert_eq!(T.color(NtColorScheme::Classic), rgb(204, 0, 0));
+
+        // Okabe-Ito
+        assert_eq!(A.color(NtColorScheme::ColorblindSafe), rgb(0, 158, 115));
+        assert_eq!(C.color(NtColorScheme::ColorblindSafe), rgb(0, 114, 178));
+        assert_eq!(G.color(NtColorScheme::ColorblindSafe), rgb(230, 159, 0));
+        assert_eq!(T.color(NtColorScheme::ColorblindSafe), rgb(213, 94, 0));
+    }
+
+    #[test]
+    fn amino_acid_schemes() {
+        let cases = [
+            (AaColorScheme::Clustal, Leu, rgb(255, 0, 0)),
+            (AaColorScheme::Clustal, Glu, rgb(0, 0, 255)),
+            (AaColorScheme::Clustal, Lys, rgb(255, 0, 255)),
+            (AaColorScheme::Clustal, Gly, rgb(0, 255, 0)),
+            (AaColorScheme::Zappo, Ile, rgb(255, 175, 175)),
+            (AaColorScheme::Zappo, Trp, rgb(255, 200, 0)),
+            (AaColorScheme::Zappo, His, rgb(100, 100, 255)),
+            (AaColorScheme::Zappo, Asp, rgb(255, 0, 0)),
+            (AaColorScheme::Zappo, Asn, rgb(0, 255, 0)),
+            (AaColorScheme::Zappo, Pro, rgb(255, 0, 255)),
+            (AaColorScheme::Zappo, Cys, rgb(255, 255, 0)),
+            (AaColorScheme::Charge, Arg, rgb(0, 0, 255)),
+            (AaColorScheme::Charge, Asp, rgb(255, 0, 0)),
+            (AaColorScheme::Charge, His, rgb(128, 128, 255)),
+            (AaColorScheme::Charge, Ala, rgb(200, 200, 200)),
+            (AaColorScheme::Taylor, Ala, rgb(204, 255, 0)),
+            (AaColorScheme::Taylor, Glu, rgb(255, 0, 102)),
+            (AaColorScheme::Taylor, His, rgb(0, 102, 255)),
+            (AaColorScheme::Taylor, Trp, rgb(0, 204, 255)),
+            (AaColorScheme::Taylor, Val, rgb(153, 255, 0)),
+        ];
+        for (scheme, aa, color) in cases {
+            assert_eq!(aa.color(scheme), color, "{scheme:?} {aa:?}");
+        }
+
+        // Sec is colored as Cys in every scheme.
+        for scheme in [
+            AaColorScheme::Clustal,
+            AaColorScheme::Zappo,
+            AaColorScheme::Hydrophobicity,
+            AaColorScheme::Charge,
+            AaColorScheme::Taylor,
+        ] {
+            assert_eq!(Sec.color(scheme), Cys.color(scheme), "{scheme:?}");
+        }
+    }
+
+    #[test]
+    fn ramp_endpoints() {
+        let points = [(0., 0., 0.), (1., 0.5, 0.), (1., 1., 1.)];
+
+        assert_eq!(ramp(&points, 0.), points[0]);
+        assert_eq!(ramp(&points, 0.5), points[1]);
+        assert_eq!(ramp(&points, 1.), points[2]);
+        assert_close(ramp(&points, 0.25), (0.5, 0.25, 0.));
+
+        // Clamped, and NaN is treated as 0.
+        assert_eq!(ramp(&points, -1.), points[0]);
+        assert_eq!(ramp(&points, 2.), points[2]);
+        assert_eq!(ramp(&points, f32::NAN), points[0]);
+    }
+
+    #[test]
+    fn ramp_monotonic() {
+        // Control points that increase in every channel give colors that do too.
+        let points = [(0., 0.2, 0.), (0.5, 0.2, 0.1), (1., 0.9, 0.2)];
+        let mut prev = ramp(&points, 0.);
+
+        for i in 1..=100 {
+            let color = ramp(&points, i as f32 / 100.);
+            assert!(color.0 >= prev.0 && color.1 >= prev.1 && color.2 >= prev.2);
+            prev = color;
+        }
+    }
+
+    #[test]
+    fn conservation() {
+        assert_eq!(conservation_color(0.), VIRIDIS[0]);
+        assert_eq!(conservation_color(1.), VIRIDIS[4]);
+        assert_eq!(conservation_color(-0.5), VIRIDIS[0]);
+        assert_eq!(conservation_color(1.5), VIRIDIS[4]);
+        assert_close(conservation_color(0.5), VIRIDIS[2]);
+
+        // Viridis brightens steadily; green increases throughout.
+        let mut prev = conservation_color(0.);
+        for i in 1..=100 {
+            let color = conservation_color(i as f32 / 100.);
+            assert!(color.1 > prev.1);
+            prev = color;
+        }
+    }
+
+    #[test]
+    fn hydrophobicity() {
+        // The Kyte-Doolittle extremes are the ends of the ramp.
+        assert_eq!(Ile.color(AaColorScheme::Hydrophobicity), (1., 0., 0.));
+        assert_eq!(Arg.color(AaColorScheme::Hydrophobicity), (0., 0., 1.));
+
+        // Sorted from hydrophilic to hydrophobic, colors move from blue to red. Sec is colored as Cys,
+        // and checked above.
+        let mut aas: Vec<_> = AminoAcid::SYMBOLS
+            .iter()
+            .filter(|&&aa| aa != Sec)
+            .copied()
+            .collect();
+        aas.sort_by(|a, b| a.hydropathicity().total_cmp(&b.hydropathicity()));
+
+        for pair in aas.windows(2) {
+            let (a, b) = (
+                pair[0].color(AaColorScheme::Hydrophobicity),
+                pair[1].color(AaColorScheme::Hydrophobicity),
+            );
+            assert!(b.0 >= a.0 && b.2 <= a.2, "{:?} {:?}", pair[0], pair[1]);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! This module contains edit distances between sequences, e.g. for clustering reads, or matching
+//! barcodes. These work on any `Alphabet`, so apply to both nucleotide and amino acid sequences.
+
+use crate::{alphabet::Alphabet, SeqError};
+
+/// The number of positions at which two equal-length sequences differ. Returns an error if their lengths
+/// differ.
+pub fn hamming<T: Alphabet>(a: &[T], b: &[T]) -> Result<usize, SeqError> {
+    if a.len() != b.len() {
+        return Err(SeqError::LengthMismatch {
+            len_a: a.len(),
+            len_b: b.len(),
+        });
+    }
+
+    Ok(a.iter().zip(b).filter(|(x, y)| x != y).count())
+}
+
+/// The minimum number of single-symbol insertions, deletions, and substitutions that turn `a` into `b`.
+/// Uses memory proportional to the shorter sequence.
+pub fn levenshtein<T: Alphabet>(a: &[T], b: &[T]) -> usize {
+    // Iterate over the longer sequence, keeping one row of the DP matrix, sized by the shorter.
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    let mut row: Vec<usize> = (0..=short.len()).collect();
+
+    for (i, x) in long.iter().enumerate() {
+        // The value diagonally up-left of the current cell.
+        let mut diag = row[0];
+        row[0] = i + 1;
+
+        for (j, y) in short.iter().enumerate() {
+            let substitution = diag + usize::from(x != y);
+            diag = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diag + 1);
+        }
+    }
+
+    row[short.len()]
+}
+
+/// Like `levenshtein`, but returns `None` if the distance is greater than `max`. Only cells within `max`
+/// of the diagonal are computed, and it exits early once a whole row exceeds `max`, so this is much
+/// faster for similar sequences; eg a sequenced clone against its expected sequence.
+pub fn levenshtein_bounded<T: Alphabet>(a: &[T], b: &[T], max: usize) -> Option<usize> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > max {
+        return None;
+    }
+
+    // Cells outside the band are treated as this, which is over the limit.
+    let over = max + 1;
+    let mut row: Vec<usize> = (0..=short.len()).map(|j| j.min(over)).collect();
+
+    for (i, x) in long.iter().enumerate() {
+        let lo = (i + 1).saturating_sub(max);
+        let hi = (i + 1 + max).min(short.len());
+
+        let mut diag = row[lo.saturating_sub(1)];
+        if lo == 0 {
+            row[0] = (i + 1).min(over);
+        } else {
+            // The cell left of the band.
+            row[lo - 1] = over;
+        }
+
+        let mut row_min = if lo == 0 { row[0] } else { over };
+        for j in lo.max(1)..=hi {
+            let substitution = diag + usize::from(*x != short[j - 1]);
+            diag = row[j];
+            row[j] = substitution.min(row[j - 1] + 1).min(diag + 1).min(over);
+            row_min = row_min.min(row[j]);
+        }
+
+        if row_min > max {
+            return None;
+        }
+    }
+
+    let result = row[short.len()];
+    if result <= max {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+/// The percentage of positions that match, from 0 to 100, based on the Levenshtein distance and the
+/// longer sequence's length. Two empty sequences are 100% identical.
+pub fn percent_identity<T: Alphabet>(a: &[T], b: &[T]) -> f32 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 100.;
+    }
+
+    (1. - levenshtein(a, b) as f64 / len as f64) as f32 * 100.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_aa_from_str, seq_from_str, test_utils::Rng, Nucleotide};
+
+    #[test]
+    fn hamming_known() {
+        let d = |a, b| hamming(&seq_from_str(a), &seq_from_str(b));
+
+        assert_eq!(d("", ""), Ok(0));
+        assert_eq!(d("ACGT", "ACGT"), Ok(0));
+        assert_eq!(d("ACGT", "ACGA"), Ok(1));
+        assert_eq!(d("GATTACA", "CTTTAGA"), Ok(3));
+        assert_eq!(
+            d("ACG", "ACGT"),
+            Err(SeqError::LengthMismatch { len_a: 3, len_b: 4 })
+        );
+    }
+
+    #[test]
+    fn levenshtein_known() {
+        let d = |a, b| levenshtein(&seq_from_str(a), &seq_from_str(b));
+
+        assert_eq!(d("", ""), 0);
+        assert_eq!(d("", "ACGT"), 4);
+        assert_eq!(d("ACGT", ""), 4);
+        assert_eq!(d("ACGT", "ACGT"), 0);
+        assert_eq!(d("ACGT", "AGT"), 1);
+        assert_eq!(d("ACGT", "ACCGT"), 1);
+        assert_eq!(d("GATTACA", "GCATGCT"), 4);
+        assert_eq!(d("AAAA", "TTTT"), 4);
+        // Symmetric.
+        assert_eq!(d("GCATGCT", "GATTACA"), 4);
+
+        // Amino acids: The classic "kitten" -> "sitting", with valid one-letter codes.
+        let aa = |s| seq_aa_from_str(s);
+        assert_eq!(levenshtein(&aa("KITTEN"), &aa("SITTING")), 3);
+    }
+
+    #[test]
+    fn percent_identity_known() {
+        let p = |a, b| percent_identity(&seq_from_str(a), &seq_from_str(b));
+
+        assert_eq!(p("", ""), 100.);
+        assert_eq!(p("ACGT", "ACGT"), 100.);
+        assert_eq!(p("ACGT", "ACGA"), 75.);
+        assert_eq!(p("", "ACGT"), 0.);
+    }
+
+    #[test]
+    fn levenshtein_bounded_matches_unbounded() {
+        let mut rng = Rng::new(0x5EED);
+
+        for _ in 0..500 {
+            let len = rng.below(40);
+            let a = rng.seq(len);
+            // Mostly similar pairs, to land near the threshold, plus some unrelated ones.
+            let b = if rng.below(4) == 0 {
+                let len = rng.below(40);
+                rng.seq(len)
+            } else {
+                let edits = rng.below(8);
+                rng.mutate(&a, edits)
+            };
+
+            let dist = levenshtein(&a, &b);
+            for max in [0, 1, 2, 3, 5, 8, 13, 50] {
+                let expected = if dist <= max { Some(dist) } else { None };
+                assert_eq!(
+                    levenshtein_bounded(&a, &b, max),
+                    expected,
+                    "{a:?} {b:?} {max}"
+                );
+            }
+            // Right at, and either side of, the threshold.
+            assert_eq!(levenshtein_bounded(&a, &b, dist), Some(dist));
+            assert_eq!(levenshtein_bounded(&a, &b, dist + 1), Some(dist));
+            if dist > 0 {
+                assert_eq!(levenshtein_bounded(&a, &b, dist - 1), None);
+            }
+        }
+    }
+
+    #[test]
+    fn levenshtein_bounded_empty() {
+        let seq = seq_from_str("ACGT");
+
+        assert_eq!(levenshtein_bounded::<Nucleotide>(&[], &[], 0), Some(0));
+        assert_eq!(levenshtein_bounded(&seq, &[], 4), Some(4));
+        assert_eq!(levenshtein_bounded(&[], &seq, 3), None);
+    }
+}
@@ -0,0 +1,253 @@
+//! This module contains a double-stranded sequence model, with explicit top and bottom strands, and
+//! the overhangs between them.
+//!
+//! Overhangs are in top-strand sense (5' to 3' on the top strand), consistent with `FragmentEnd`.
+
+use crate::{
+    restriction_enzyme::{EndKind, Fragment},
+    seq_complement, Nucleotide, Seq,
+};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DuplexSeq {
+    /// 5' to 3'.
+    pub top: Seq,
+    /// 5' to 3'; ie reading right to left, under the top strand.
+    pub bottom: Seq,
+    /// The position of the bottom strand's left (3') end, relative to the top strand's left (5') end.
+    /// Positive if the top strand protrudes on the left, and negative if the bottom one does. Example:
+    /// AATTCGG     (top)
+    ///     GCCTTAA (bottom, 3' to 5')  offset=4.
+    pub offset: isize,
+}
+
+impl DuplexSeq {
+    /// A fully-annealed duplex, with blunt ends.
+    pub fn from_blunt(seq: &[Nucleotide]) -> Self {
+        Self {
+            top: seq.to_vec(),
+            bottom: seq_complement(seq),
+            offset: 0,
+        }
+    }
+
+    /// The position of the bottom strand's right (5') end, in top-strand coordinates.
+    fn bottom_end(&self) -> isize {
+        self.offset + self.bottom.len() as isize
+    }
+
+    /// The bottom strand in top-strand sense, covering `offset..bottom_end()`.
+    fn bottom_top_sense(&self) -> Seq {
+        seq_complement(&self.bottom)
+    }
+
+    /// Nucleotides of the bottom strand in top-strand sense, in top-strand coordinates.
+    fn bottom_range(&self, start: isize, end: isize) -> Seq {
+        let bottom = self.bottom_top_sense();
+        bottom[(start - self.offset) as usize..(end - self.offset) as usize].to_vec()
+    }
+
+    /// The top strand's single-stranded 5' end. Empty if there isn't one.
+    pub fn five_prime_overhang_left(&self) -> Seq {
+        if self.offset <= 0 {
+            return Vec::new();
+        }
+        let end = (self.offset as usize).min(self.top.len());
+        self.top[..end].to_vec()
+    }
+
+    /// The bottom strand's single-stranded 5' end, in top-strand sense. Empty if there isn't one.
+    pub fn five_prime_overhang_right(&self) -> Seq {
+        let top_len = self.top.len() as isize;
+        if self.bottom_end() <= top_len {
+            return Vec::new();
+        }
+        self.bottom_range(top_len.max(self.offset), self.bottom_end())
+    }
+
+    /// The bottom strand's single-stranded 3' end, in top-strand sense. Empty if there isn't one.
+    pub fn three_prime_overhang_left(&self) -> Seq {
+        if self.offset >= 0 {
+            return Vec::new();
+        }
+        self.bottom_range(self.offset, self.bottom_end().min(0))
+    }
+
+    /// The top strand's single-stranded 3' end. Empty if there isn't one.
+    pub fn three_prime_overhang_right(&self) -> Seq {
+        let top_len = self.top.len() as isize;
+        if self.bottom_end() >= top_len {
+            return Vec::new();
+        }
+        let start = self.bottom_end().max(0) as usize;
+        self.top[start..].to_vec()
+    }
+
+    /// Both ends are blunt, and the strands are complementary throughout.
+    pub fn is_fully_annealed(&self) -> bool {
+        self.offset == 0 && self.bottom_top_sense() == self.top
+    }
+
+    /// Whether this duplex's right end can ligate to `other`'s left end: Protruding on the same strand,
+    /// with the same overhang, or both blunt, if `allow_blunt` is set.
+    fn right_compatible_with(&self, other: &Self, allow_blunt: bool) -> bool {
+        let five_prime = self.five_prime_overhang_right();
+        let three_prime = self.three_prime_overhang_right();
+
+        if five_prime.is_empty() && three_prime.is_empty() && !allow_blunt {
+            return false;
+        }
+
+        five_prime == other.five_prime_overhang_left()
+            && three_prime == other.three_prime_overhang_left()
+    }
+
+    /// Ligate `other` to the right end of this duplex. Returns `None` if the ends aren't compatible. Blunt
+    /// ends are only joined if `allow_blunt` is set. The flag is true if the product's own ends are
+    /// compatible, ie it can close into a circle, under the same rule.
+    pub fn ligate_to(&self, other: &Self, allow_blunt: bool) -> Option<(Self, bool)> {
+        if !self.right_compatible_with(other, allow_blunt) {
+            return None;
+        }
+
+        let mut top = self.top.clone();
+        top.extend(&other.top);
+
+        let mut bottom = other.bottom.clone();
+        bottom.extend(&self.bottom);
+
+        let product = Self {
+            top,
+            bottom,
+            offset: self.offset,
+        };
+        let circular = product.can_circularize(allow_blunt);
+
+        Some((product, circular))
+    }
+
+    /// Whether this duplex's ends are compatible with each other, so it can self-ligate into a circle.
+    /// Blunt ends only count if `allow_blunt` is set.
+    pub fn can_circularize(&self, allow_blunt: bool) -> bool {
+        self.right_compatible_with(self, allow_blunt)
+    }
+}
+
+impl From<&Fragment> for DuplexSeq {
+    fn from(frag: &Fragment) -> Self {
+        let overhang_len = frag.left.overhang.len() as isize;
+        let offset = match frag.left.kind {
+            EndKind::Blunt => 0,
+            EndKind::FivePrime => overhang_len,
+            EndKind::ThreePrime => -overhang_len,
+        };
+
+        Self {
+            top: frag.strand_top.clone(),
+            bottom: frag.strand_bottom.clone(),
+            offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{re_lib::find_by_name, restriction_enzyme::digest, seq_from_str, SeqTopology};
+
+    /// Cut `seq` with an enzyme, at the first site.
+    fn cut(name: &str, seq: &str, site: usize) -> (DuplexSeq, DuplexSeq) {
+        find_by_name(name)
+            .unwrap()
+            .cut_duplex(&seq_from_str(seq), site)
+            .unwrap()
+    }
+
+    #[test]
+    fn blunt() {
+        let duplex = DuplexSeq::from_blunt(&seq_from_str("GATTACA"));
+
+        assert!(duplex.is_fully_annealed());
+        assert!(duplex.five_prime_overhang_left().is_empty());
+        assert!(duplex.five_prime_overhang_right().is_empty());
+        assert!(duplex.three_prime_overhang_left().is_empty());
+        assert!(duplex.three_prime_overhang_right().is_empty());
+        assert!(duplex.can_circularize(true));
+        assert!(!duplex.can_circularize(false));
+    }
+
+    #[test]
+    fn five_prime_overhangs() {
+        // G^AATTC
+        let (left, right) = cut("EcoRI", "CCGAATTCGG", 2);
+
+        assert_eq!(left.top, seq_from_str("CCG"));
+        assert_eq!(left.five_prime_overhang_right(), seq_from_str("AATT"));
+        assert!(left.three_prime_overhang_right().is_empty());
+        assert!(left.five_prime_overhang_left().is_empty());
+
+        assert_eq!(right.top, seq_from_str("AATTCGG"));
+        assert_eq!(right.offset, 4);
+        assert_eq!(right.five_prime_overhang_left(), seq_from_str("AATT"));
+        assert!(right.three_prime_overhang_left().is_empty());
+        assert!(!right.is_fully_annealed());
+    }
+
+    #[test]
+    fn three_prime_overhangs() {
+        // CTGCA^G
+        let (left, right) = cut("PstI", "AACTGCAGTT", 2);
+
+        assert_eq!(left.top, seq_from_str("AACTGCA"));
+        assert_eq!(left.three_prime_overhang_right(), seq_from_str("TGCA"));
+        assert!(left.five_prime_overhang_right().is_empty());
+
+        assert_eq!(right.top, seq_from_str("GTT"));
+        assert_eq!(right.offset, -4);
+        assert_eq!(right.three_prime_overhang_left(), seq_from_str("TGCA"));
+        assert!(right.five_prime_overhang_left().is_empty());
+    }
+
+    #[test]
+    fn ligate_to() {
+        let seq = seq_from_str("CCGAATTCGG");
+        let (left, right) = cut("EcoRI", "CCGAATTCGG", 2);
+
+        // Re-ligating the cut gives back the original, blunt-ended, duplex.
+        let (product, circular) = left.ligate_to(&right, false).unwrap();
+        assert_eq!(product, DuplexSeq::from_blunt(&seq));
+        assert!(!circular);
+
+        // With blunt ends allowed, the product's ends are compatible.
+        assert!(left.ligate_to(&right, true).unwrap().1);
+
+        // The wrong way round, only the blunt ends meet.
+        assert!(right.ligate_to(&left, false).is_none());
+        assert!(right.ligate_to(&left, true).is_some());
+
+        // Incompatible overhangs never join.
+        let (_, pst_right) = cut("PstI", "AACTGCAGTT", 2);
+        assert!(left.ligate_to(&pst_right, true).is_none());
+    }
+
+    #[test]
+    fn ligate_to_circular() {
+        // The fragment between two EcoRI sites has compatible ends, so it circularizes.
+        let seq = seq_from_str("CCGAATTCAAAGAATTCGG");
+        let ecori = find_by_name("EcoRI").unwrap().clone();
+        let frags = digest(&seq, &[ecori], SeqTopology::Linear);
+        let frags: Vec<_> = frags.iter().map(DuplexSeq::from).collect();
+        assert_eq!(frags.len(), 3);
+
+        assert!(!frags[0].can_circularize(false));
+        assert!(frags[1].can_circularize(false));
+        assert!(frags[1].ligate_to(&frags[1], false).unwrap().1);
+
+        // Rebuilding the original sequence doesn't.
+        let (product, circular) = frags[0].ligate_to(&frags[1], false).unwrap();
+        assert!(!circular);
+        let (product, circular) = product.ligate_to(&frags[2], false).unwrap();
+        assert!(!circular);
+        assert_eq!(product, DuplexSeq::from_blunt(&seq));
+    }
+}
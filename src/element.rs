@@ -0,0 +1,558 @@
+//! This module contains chemical elements, for use in molecular formulas: Their periodic table
+//! properties, and masses.
+
+use std::collections::HashMap;
+
+use bincode::{Decode, Encode};
+
+use crate::{
+    protein::MassMode,
+    warnings::{Warning, WarningCode, Warnings},
+    SeqError,
+};
+
+/// A chemical element, for use in molecular formulas.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Element {
+    Carbon,
+    Hydrogen,
+    Nitrogen,
+    Oxygen,
+    Phosphorus,
+    Sulfur,
+    Sodium,
+    Magnesium,
+    Potassium,
+    Calcium,
+    Manganese,
+    Iron,
+    Cobalt,
+    Nickel,
+    Copper,
+    Zinc,
+    Molybdenum,
+    Cadmium,
+}
+
+impl Element {
+    /// All elements included here.
+    pub const ALL: [Self; 18] = [
+        Self::Carbon,
+        Self::Hydrogen,
+        Self::Nitrogen,
+        Self::Oxygen,
+        Self::Phosphorus,
+        Self::Sulfur,
+        Self::Sodium,
+        Self::Magnesium,
+        Self::Potassium,
+        Self::Calcium,
+        Self::Manganese,
+        Self::Iron,
+        Self::Cobalt,
+        Self::Nickel,
+        Self::Copper,
+        Self::Zinc,
+        Self::Molybdenum,
+        Self::Cadmium,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Carbon => "C",
+            Self::Hydrogen => "H",
+            Self::Nitrogen => "N",
+            Self::Oxygen => "O",
+            Self::Phosphorus => "P",
+            Self::Sulfur => "S",
+            Self::Sodium => "Na",
+            Self::Magnesium => "Mg",
+            Self::Potassium => "K",
+            Self::Calcium => "Ca",
+            Self::Manganese => "Mn",
+            Self::Iron => "Fe",
+            Self::Cobalt => "Co",
+            Self::Nickel => "Ni",
+            Self::Copper => "Cu",
+            Self::Zinc => "Zn",
+            Self::Molybdenum => "Mo",
+            Self::Cadmium => "Cd",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Carbon => "Carbon",
+            Self::Hydrogen => "Hydrogen",
+            Self::Nitrogen => "Nitrogen",
+            Self::Oxygen => "Oxygen",
+            Self::Phosphorus => "Phosphorus",
+            Self::Sulfur => "Sulfur",
+            Self::Sodium => "Sodium",
+            Self::Magnesium => "Magnesium",
+            Self::Potassium => "Potassium",
+            Self::Calcium => "Calcium",
+            Self::Manganese => "Manganese",
+            Self::Iron => "Iron",
+            Self::Cobalt => "Cobalt",
+            Self::Nickel => "Nickel",
+            Self::Copper => "Copper",
+            Self::Zinc => "Zinc",
+            Self::Molybdenum => "Molybdenum",
+            Self::Cadmium => "Cadmium",
+        }
+    }
+
+    pub fn atomic_number(&self) -> u8 {
+        match self {
+            Self::Carbon => 6,
+            Self::Hydrogen => 1,
+            Self::Nitrogen => 7,
+            Self::Oxygen => 8,
+            Self::Phosphorus => 15,
+            Self::Sulfur => 16,
+            Self::Sodium => 11,
+            Self::Magnesium => 12,
+            Self::Potassium => 19,
+            Self::Calcium => 20,
+            Self::Manganese => 25,
+            Self::Iron => 26,
+            Self::Cobalt => 27,
+            Self::Nickel => 28,
+            Self::Copper => 29,
+            Self::Zinc => 30,
+            Self::Molybdenum => 42,
+            Self::Cadmium => 48,
+        }
+    }
+
+    /// Electronegativity, on the Pauling scale. Values in this and the other periodic table accessors
+    /// are from the CRC Handbook of Chemistry and Physics. `None` for elements without one, eg most
+    /// noble gases.
+    pub fn electronegativity(&self) -> Option<f32> {
+        let result = match self {
+            Self::Carbon => 2.55,
+            Self::Hydrogen => 2.20,
+            Self::Nitrogen => 3.04,
+            Self::Oxygen => 3.44,
+            Self::Phosphorus => 2.19,
+            Self::Sulfur => 2.58,
+            Self::Sodium => 0.93,
+            Self::Magnesium => 1.31,
+            Self::Potassium => 0.82,
+            Self::Calcium => 1.00,
+            Self::Manganese => 1.55,
+            Self::Iron => 1.83,
+            Self::Cobalt => 1.88,
+            Self::Nickel => 1.91,
+            Self::Copper => 1.90,
+            Self::Zinc => 1.65,
+            Self::Molybdenum => 2.16,
+            Self::Cadmium => 1.69,
+        };
+
+        Some(result)
+    }
+
+    /// The periodic table row.
+    pub fn period(&self) -> u8 {
+        match self.atomic_number() {
+            1..=2 => 1,
+            3..=10 => 2,
+            11..=18 => 3,
+            19..=36 => 4,
+            37..=54 => 5,
+            55..=86 => 6,
+            _ => 7,
+        }
+    }
+
+    /// The periodic table column, from 1 to 18. `None` for lanthanides and actinides.
+    pub fn group(&self) -> Option<u8> {
+        let result = match self {
+            Self::Hydrogen | Self::Sodium | Self::Potassium => 1,
+            Self::Magnesium | Self::Calcium => 2,
+            Self::Molybdenum => 6,
+            Self::Manganese => 7,
+            Self::Iron => 8,
+            Self::Cobalt => 9,
+            Self::Nickel => 10,
+            Self::Copper => 11,
+            Self::Zinc | Self::Cadmium => 12,
+            Self::Carbon => 14,
+            Self::Nitrogen | Self::Phosphorus => 15,
+            Self::Oxygen | Self::Sulfur => 16,
+        };
+
+        Some(result)
+    }
+
+    /// The ground-state electron configuration, in noble gas notation, eg "[Ar] 3d6 4s2" for iron.
+    /// Subshells are in order of principal quantum number.
+    pub fn electron_configuration(&self) -> &'static str {
+        match self {
+            Self::Hydrogen => "1s1",
+            Self::Carbon => "[He] 2s2 2p2",
+            Self::Nitrogen => "[He] 2s2 2p3",
+            Self::Oxygen => "[He] 2s2 2p4",
+            Self::Sodium => "[Ne] 3s1",
+            Self::Magnesium => "[Ne] 3s2",
+            Self::Phosphorus => "[Ne] 3s2 3p3",
+            Self::Sulfur => "[Ne] 3s2 3p4",
+            Self::Potassium => "[Ar] 4s1",
+            Self::Calcium => "[Ar] 4s2",
+            Self::Manganese => "[Ar] 3d5 4s2",
+            Self::Iron => "[Ar] 3d6 4s2",
+            Self::Cobalt => "[Ar] 3d7 4s2",
+            Self::Nickel => "[Ar] 3d8 4s2",
+            Self::Copper => "[Ar] 3d10 4s1",
+            Self::Zinc => "[Ar] 3d10 4s2",
+            Self::Molybdenum => "[Kr] 4d5 5s1",
+            Self::Cadmium => "[Kr] 4d10 5s2",
+        }
+    }
+
+    pub fn is_metal(&self) -> bool {
+        matches!(
+            self,
+            Self::Sodium
+                | Self::Magnesium
+                | Self::Potassium
+                | Self::Calcium
+                | Self::Manganese
+                | Self::Iron
+                | Self::Cobalt
+                | Self::Nickel
+                | Self::Copper
+                | Self::Zinc
+                | Self::Molybdenum
+                | Self::Cadmium
+        )
+    }
+
+    /// The inverse of `atomic_number`. Returns `None` for elements not included here.
+    pub fn from_atomic_number(z: u8) -> Option<Self> {
+        match z {
+            1 => Some(Self::Hydrogen),
+            6 => Some(Self::Carbon),
+            7 => Some(Self::Nitrogen),
+            8 => Some(Self::Oxygen),
+            11 => Some(Self::Sodium),
+            12 => Some(Self::Magnesium),
+            15 => Some(Self::Phosphorus),
+            16 => Some(Self::Sulfur),
+            19 => Some(Self::Potassium),
+            20 => Some(Self::Calcium),
+            25 => Some(Self::Manganese),
+            26 => Some(Self::Iron),
+            27 => Some(Self::Cobalt),
+            28 => Some(Self::Nickel),
+            29 => Some(Self::Copper),
+            30 => Some(Self::Zinc),
+            42 => Some(Self::Molybdenum),
+            48 => Some(Self::Cadmium),
+            _ => None,
+        }
+    }
+
+    /// Parse an element symbol, as produced by `symbol`. Case insensitive, with surrounding whitespace
+    /// and trailing charges ignored, to handle symbols from PDB files, eg " N", "FE", or "ZN2+".
+    pub fn from_symbol(symbol: &str) -> Result<Self, SeqError> {
+        let symbol = symbol
+            .trim()
+            .trim_end_matches(|c: char| c == '+' || c == '-' || c == '−' || c.is_ascii_digit());
+
+        Self::ALL
+            .into_iter()
+            .find(|el| el.symbol().eq_ignore_ascii_case(symbol))
+            .ok_or_else(|| SeqError::InvalidElement(symbol.to_owned()))
+    }
+
+    /// Infer the element from a PDB atom name, eg " CA " for an alpha carbon, or "CA  " for calcium; for
+    /// files without the element column. Given the full 4-character field, the PDB alignment rule resolves
+    /// this: Two-letter symbols start in the first column, and one-letter ones in the second, unless the
+    /// name fills all four. A leading digit, as in hydrogen names like "1HB2", is skipped.
+    ///
+    /// Trimmed names like "CA" are ambiguous. These resolve to the one-letter element, as in standard
+    /// residue atom names, with an `AmbiguousElement` warning.
+    pub fn from_pdb_atom_name(name: &str, warnings: &mut Warnings) -> Option<Self> {
+        // Element symbols are letters; the rest of the name is a position label, eg the "B2" in "HB2".
+        let symbol = |s: Option<&str>| {
+            s.filter(|s| s.chars().all(|c| c.is_ascii_alphabetic()))
+                .and_then(|s| Self::from_symbol(s).ok())
+        };
+
+        if name.len() == 4 && name.is_ascii() {
+            let first = name.as_bytes()[0];
+            if first == b' ' || first.is_ascii_digit() {
+                return symbol(name.get(1..2));
+            }
+            return symbol(name.get(..2)).or_else(|| symbol(name.get(..1)));
+        }
+
+        let name = name.trim().trim_start_matches(|c: char| c.is_ascii_digit());
+
+        match (symbol(name.get(..1)), symbol(name.get(..2))) {
+            (Some(one), Some(two)) => {
+                warnings.push(Warning::new(
+                    WarningCode::AmbiguousElement,
+                    &format!(
+                        "Atom name {name} could be {} or {}; using {}",
+                        one.name(),
+                        two.name(),
+                        one.name()
+                    ),
+                    None,
+                ));
+                Some(one)
+            }
+            (one, two) => two.or(one),
+        }
+    }
+
+    /// Parse an element's name, eg "Magnesium". Case insensitive.
+    pub fn from_name(name: &str) -> Result<Self, SeqError> {
+        let name = name.trim();
+
+        Self::ALL
+            .into_iter()
+            .find(|el| el.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| SeqError::InvalidElement(name.to_owned()))
+    }
+
+    /// Standard atomic weight, in Da.
+    pub fn average_mass(&self) -> f64 {
+        match self {
+            Self::Carbon => 12.0107,
+            Self::Hydrogen => 1.00794,
+            Self::Nitrogen => 14.0067,
+            Self::Oxygen => 15.9994,
+            Self::Phosphorus => 30.973762,
+            Self::Sulfur => 32.065,
+            Self::Sodium => 22.98976928,
+            Self::Magnesium => 24.305,
+            Self::Potassium => 39.0983,
+            Self::Calcium => 40.078,
+            Self::Manganese => 54.938045,
+            Self::Iron => 55.845,
+            Self::Cobalt => 58.933195,
+            Self::Nickel => 58.6934,
+            Self::Copper => 63.546,
+            Self::Zinc => 65.38,
+            Self::Molybdenum => 95.96,
+            Self::Cadmium => 112.411,
+        }
+    }
+
+    /// The mass of the most abundant isotope, in Da.
+    pub fn monoisotopic_mass(&self) -> f64 {
+        match self {
+            Self::Carbon => 12.,
+            Self::Hydrogen => 1.00782503207,
+            Self::Nitrogen => 14.0030740048,
+            Self::Oxygen => 15.99491461956,
+            Self::Phosphorus => 30.97376163,
+            Self::Sulfur => 31.97207100,
+            Self::Sodium => 22.9897692809,
+            Self::Magnesium => 23.985041700,
+            Self::Potassium => 38.96370668,
+            Self::Calcium => 39.96259098,
+            Self::Manganese => 54.9380451,
+            Self::Iron => 55.9349375,
+            Self::Cobalt => 58.9331950,
+            Self::Nickel => 57.9353429,
+            Self::Copper => 62.9295975,
+            Self::Zinc => 63.9291422,
+            Self::Molybdenum => 97.9054082,
+            Self::Cadmium => 113.9033585,
+        }
+    }
+}
+
+/// The mass of a molecular formula, in Da.
+pub fn formula_mass(formula: &HashMap<Element, usize>, mode: MassMode) -> f64 {
+    formula
+        .iter()
+        .map(|(el, count)| {
+            let mass = match mode {
+                MassMode::Average => el.average_mass(),
+                MassMode::Monoisotopic => el.monoisotopic_mass(),
+            };
+            mass * *count as f64
+        })
+        .sum()
+}
+
+/// Render a molecular formula in Hill order, eg "C39H50N15O22P3". Counts of 1 are omitted.
+pub fn formula_string(formula: &HashMap<Element, usize>) -> String {
+    let mut elements: Vec<_> = formula.iter().filter(|(_, count)| **count > 0).collect();
+    // Hill order: carbon, then hydrogen, then the rest alphabetically. Without carbon, all elements,
+    // including hydrogen, are alphabetical.
+    let has_carbon = formula.get(&Element::Carbon).is_some_and(|c| *c > 0);
+    elements.sort_by_key(|(el, _)| match el {
+        Element::Carbon => (0, ""),
+        Element::Hydrogen if has_carbon => (1, ""),
+        _ => (2, el.symbol()),
+    });
+
+    let mut result = String::new();
+    for (el, count) in elements {
+        result.push_str(el.symbol());
+        if *count > 1 {
+            result.push_str(&count.to_string());
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse an electron configuration into (principal quantum number, subshell letter, electron count),
+    /// expanding the noble gas core.
+    fn subshells(config: &str) -> Vec<(u8, char, u8)> {
+        let mut result = Vec::new();
+
+        for part in config.split_whitespace() {
+            let core = match part {
+                "[He]" => "1s2",
+                "[Ne]" => "1s2 2s2 2p6",
+                "[Ar]" => "1s2 2s2 2p6 3s2 3p6",
+                "[Kr]" => "1s2 2s2 2p6 3s2 3p6 3d10 4s2 4p6",
+                _ => {
+                    let n = part[..1].parse().unwrap();
+                    let letter = part[1..].chars().next().unwrap();
+                    result.push((n, letter, part[2..].parse().unwrap()));
+                    continue;
+                }
+            };
+            result.extend(subshells(core));
+        }
+
+        result
+    }
+
+    #[test]
+    fn periodic_table_consistency() {
+        let mut elements = Element::ALL.to_vec();
+        elements.sort_by_key(|el| el.atomic_number());
+
+        for pair in elements.windows(2) {
+            assert!(pair[0].atomic_number() < pair[1].atomic_number());
+            assert!(pair[0].period() <= pair[1].period());
+        }
+
+        for el in Element::ALL {
+            assert_eq!(Element::from_atomic_number(el.atomic_number()), Some(el));
+            assert_eq!(Element::from_symbol(el.symbol()), Ok(el));
+            assert_eq!(Element::from_name(el.name()), Ok(el));
+
+            let shells = subshells(el.electron_configuration());
+
+            // Electron count matches atomic number, for neutral atoms.
+            let electrons: u8 = shells.iter().map(|s| s.2).sum();
+            assert_eq!(electrons, el.atomic_number(), "{el:?}");
+
+            // The outermost shell is the period.
+            let outer = shells.iter().map(|s| s.0).max().unwrap();
+            assert_eq!(outer, el.period(), "{el:?}");
+
+            // Group, from the outer s and p electrons, and the d electrons below them.
+            let count = |n: u8, letter: char| {
+                shells
+                    .iter()
+                    .filter(|s| s.0 == n && s.1 == letter)
+                    .map(|s| s.2)
+                    .sum::<u8>()
+            };
+            let (s, p) = (count(outer, 's'), count(outer, 'p'));
+            let d = if outer > 3 { count(outer - 1, 'd') } else { 0 };
+            let group = match (p, d) {
+                (0, 0) => s,
+                (0, d) => s + d,
+                (p, _) => 12 + p,
+            };
+            assert_eq!(el.group(), Some(group), "{el:?}");
+        }
+    }
+
+    #[test]
+    fn formula_string_hill_order() {
+        use Element::*;
+
+        // Calcium hydroxide: No carbon, so hydrogen isn't moved ahead.
+        let formula = HashMap::from([(Calcium, 1), (Oxygen, 2), (Hydrogen, 2)]);
+        assert_eq!(formula_string(&formula), "CaH2O2");
+
+        let formula = HashMap::from([(Sulfur, 1), (Oxygen, 4), (Hydrogen, 2)]);
+        assert_eq!(formula_string(&formula), "H2O4S");
+
+        // Glucose
+        let formula = HashMap::from([(Oxygen, 6), (Hydrogen, 12), (Carbon, 6)]);
+        assert_eq!(formula_string(&formula), "C6H12O6");
+
+        // Zero counts are skipped, and a count of one has no number.
+        let formula = HashMap::from([(Carbon, 0), (Hydrogen, 1), (Sodium, 1)]);
+        assert_eq!(formula_string(&formula), "HNa");
+
+        assert_eq!(formula_string(&HashMap::new()), "");
+    }
+
+    #[test]
+    fn from_pdb_atom_name() {
+        let mut warnings = Vec::new();
+        let mut parse = |name| Element::from_pdb_atom_name(name, &mut warnings);
+
+        // Full fields are resolved by alignment.
+        assert_eq!(parse(" CA "), Some(Element::Carbon));
+        assert_eq!(parse("CA  "), Some(Element::Calcium));
+        assert_eq!(parse("FE  "), Some(Element::Iron));
+        assert_eq!(parse(" OD1"), Some(Element::Oxygen));
+        assert_eq!(parse("1HB2"), Some(Element::Hydrogen));
+        // Four-character hydrogen names that aren't a known two-letter symbol.
+        assert_eq!(parse("HD21"), Some(Element::Hydrogen));
+        assert_eq!(parse(" XX "), None);
+        assert!(warnings.is_empty());
+
+        // Trimmed names that only match one way.
+        assert_eq!(
+            Element::from_pdb_atom_name("ZN", &mut warnings),
+            Some(Element::Zinc)
+        );
+        assert_eq!(
+            Element::from_pdb_atom_name("NZ", &mut warnings),
+            Some(Element::Nitrogen)
+        );
+        assert_eq!(
+            Element::from_pdb_atom_name("C1", &mut warnings),
+            Some(Element::Carbon)
+        );
+        assert_eq!(Element::from_pdb_atom_name("", &mut warnings), None);
+        assert!(warnings.is_empty());
+
+        // Trimmed names that match both ways.
+        for (name, el) in [
+            ("CA", Element::Carbon),
+            ("NA", Element::Nitrogen),
+            ("CD1", Element::Carbon),
+        ] {
+            assert_eq!(Element::from_pdb_atom_name(name, &mut warnings), Some(el));
+        }
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings
+            .iter()
+            .all(|w| w.code == WarningCode::AmbiguousElement));
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(Element::Oxygen.electronegativity(), Some(3.44));
+        assert_eq!(Element::Iron.group(), Some(8));
+        assert_eq!(Element::Iron.electron_configuration(), "[Ar] 3d6 4s2");
+        assert!(Element::Zinc.is_metal());
+        assert!(!Element::Sulfur.is_metal());
+    }
+}
@@ -0,0 +1,367 @@
+//! This module contains code for reading and writing FASTA files.
+//!
+//! [FASTA format](https://en.wikipedia.org/wiki/FASTA_format)
+
+use std::{
+    io,
+    io::{BufRead, ErrorKind, Write},
+    marker::PhantomData,
+};
+
+use crate::{
+    alphabet::{seq_to_letters, Alphabet},
+    Nucleotide,
+};
+
+/// A FASTA record. By default, this contains a nucleotide sequence; use `FastaRecord<AminoAcid>`
+/// for proteins.
+#[derive(Clone, Debug)]
+pub struct FastaRecord<T = Nucleotide> {
+    /// The header text before the first whitespace, excluding `>`.
+    pub id: String,
+    /// The header text after the first whitespace.
+    pub description: String,
+    pub seq: Vec<T>,
+}
+
+impl<T> Default for FastaRecord<T> {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            description: String::new(),
+            seq: Vec::new(),
+        }
+    }
+}
+
+/// Reads FASTA records one at a time, without loading the whole file into memory.
+pub struct FastaReader<R: BufRead, T = Nucleotide> {
+    reader: R,
+    /// Reused between lines, to avoid allocating for each.
+    line: Vec<u8>,
+    line_num: usize,
+    /// The header line of the next record, if we've already read it.
+    next_header: Option<String>,
+    /// Set after reaching EOF, or an error.
+    done: bool,
+    alphabet: PhantomData<T>,
+}
+
+impl<R: BufRead> FastaReader<R> {
+    /// Create a reader for nucleotide sequences.
+    pub fn new(reader: R) -> Self {
+        Self::with_alphabet(reader)
+    }
+}
+
+impl<R: BufRead, T: Alphabet> FastaReader<R, T> {
+    /// Create a reader for sequences of any alphabet; e.g. `FastaReader::<_, AminoAcid>::with_alphabet()`
+    /// for proteins.
+    pub fn with_alphabet(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            line_num: 0,
+            next_header: None,
+            done: false,
+            alphabet: PhantomData,
+        }
+    }
+
+    /// Read the next line into our buffer, without its line ending. Returns false at EOF.
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_num += 1;
+
+        while matches!(self.line.last(), Some(b'\n' | b'\r')) {
+            self.line.pop();
+        }
+
+        Ok(true)
+    }
+
+    fn error(&self, msg: &str) -> io::Error {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{msg}, on line {}", self.line_num),
+        )
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<FastaRecord<T>>> {
+        let header = match self.next_header.take() {
+            Some(h) => h,
+            None => loop {
+                if !self.read_line()? {
+                    return Ok(None);
+                }
+                if self.line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                match self.line.strip_prefix(b">") {
+                    Some(h) => break String::from_utf8_lossy(h).into_owned(),
+                    None => return Err(self.error("Expected a FASTA header")),
+                }
+            },
+        };
+
+        let header = header.trim();
+        let (id, description) = match header.split_once(char::is_whitespace) {
+            Some((id, desc)) => (id.to_owned(), desc.trim().to_owned()),
+            None => (header.to_owned(), String::new()),
+        };
+
+        let mut seq = Vec::new();
+
+        while self.read_line()? {
+            if let Some(h) = self.line.strip_prefix(b">") {
+                self.next_header = Some(String::from_utf8_lossy(h).into_owned());
+                break;
+            }
+
+            for (col, byte) in self.line.iter().enumerate() {
+                if byte.is_ascii_whitespace() {
+                    continue;
+                }
+
+                match T::from_u8_letter(*byte) {
+                    Ok(s) => seq.push(s),
+                    Err(_) => {
+                        return Err(self.error(&format!(
+                            "Invalid {} '{}' in record {id}, at column {}",
+                            T::NAME,
+                            *byte as char,
+                            col + 1
+                        )))
+                    }
+                }
+            }
+        }
+
+        Ok(Some(FastaRecord {
+            id,
+            description,
+            seq,
+        }))
+    }
+}
+
+impl<R: BufRead, T: Alphabet> Iterator for FastaReader<R, T> {
+    type Item = io::Result<FastaRecord<T>>;
+
+    /// Returns `None` after the last record, or after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_record();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+/// Read all records from a FASTA file containing nucleotide sequences. Handles multi-record files,
+/// lower-case letters, and Windows line endings. Letters other than A, C, T, and G (e.g. `N`) are an
+/// error, which includes their line and column.
+pub fn read_fasta(reader: impl BufRead) -> io::Result<Vec<FastaRecord>> {
+    FastaReader::new(reader).collect()
+}
+
+/// Write records in FASTA format. Sequences are upper case, and wrapped at `line_width` characters;
+/// a `line_width` of 0 means no wrapping.
+pub fn write_fasta<W: Write, T: Alphabet>(
+    w: &mut W,
+    records: &[FastaRecord<T>],
+    line_width: usize,
+) -> io::Result<()> {
+    for record in records {
+        if record.description.is_empty() {
+            writeln!(w, ">{}", record.id)?;
+        } else {
+            writeln!(w, ">{} {}", record.id, record.description)?;
+        }
+
+        let seq = seq_to_letters(&record.seq);
+        let line_width = if line_width == 0 {
+            seq.len().max(1)
+        } else {
+            line_width
+        };
+
+        for line in seq.chunks(line_width) {
+            w.write_all(line)?;
+            w.write_all(b"\n")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_from_str, test_utils::Rng, AminoAcid};
+
+    /// Wrap `seq` at `width` characters, as FASTA.
+    fn wrapped(seq: &str, width: usize) -> String {
+        let lines: Vec<_> = seq.as_bytes().chunks(width).collect();
+        lines.join(&b'\n').into_iter().map(char::from).collect()
+    }
+
+    #[test]
+    fn multi_record() {
+        let data = b">seq1 First record\nGATTACA\nTTAG\n\n>seq2\ngattaca\n>seq3  Third  \n";
+        let records: Vec<_> = FastaReader::new(&data[..])
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].id, "seq1");
+        assert_eq!(records[0].description, "First record");
+        assert_eq!(records[0].seq, seq_from_str("GATTACATTAG"));
+        assert_eq!(records[1].id, "seq2");
+        assert!(records[1].description.is_empty());
+        assert_eq!(records[1].seq, seq_from_str("GATTACA"));
+        assert_eq!(records[2].description, "Third");
+        assert!(records[2].seq.is_empty());
+    }
+
+    #[test]
+    fn crlf() {
+        let data = b">seq1 desc\r\nGATT\r\nACA\r\n>seq2\r\nCC\r\n";
+        let records = read_fasta(&data[..]).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "seq1");
+        assert_eq!(records[0].description, "desc");
+        assert_eq!(records[0].seq, seq_from_str("GATTACA"));
+        assert_eq!(records[1].seq, seq_from_str("CC"));
+    }
+
+    #[test]
+    fn wrapped_60_and_70() {
+        let seq = "GATTACA".repeat(31);
+
+        for width in [60, 70] {
+            let data = format!(">wrapped\n{}\n", wrapped(&seq, width));
+            let records = read_fasta(data.as_bytes()).unwrap();
+
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].seq, seq_from_str(&seq));
+        }
+    }
+
+    #[test]
+    fn proteins() {
+        let data = b">sp|P69905 Hemoglobin\nMVLSPADK\nTNVKAAWG\n";
+        let records: Vec<FastaRecord<AminoAcid>> = FastaReader::with_alphabet(&data[..])
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(records[0].id, "sp|P69905");
+        assert_eq!(records[0].seq.len(), 16);
+    }
+
+    #[test]
+    fn invalid_letter() {
+        let data = b">seq1\nGATTACA\n>seq2\nGATT\nACNA\n";
+        let mut reader = FastaReader::new(&data[..]);
+
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "Invalid nucleotide 'N' in record seq2, at column 3, on line 5"
+        );
+        // The reader stops after an error.
+        assert!(reader.next().is_none());
+
+        let err = read_fasta(&b"GATTACA\n"[..]).unwrap_err();
+        assert_eq!(err.to_string(), "Expected a FASTA header, on line 1");
+    }
+
+    #[test]
+    fn write_read_round_trip() {
+        let records = vec![
+            FastaRecord {
+                id: "seq1".to_owned(),
+                description: "First record".to_owned(),
+                seq: seq_from_str("GATTACA"),
+            },
+            FastaRecord {
+                id: "seq2".to_owned(),
+                description: String::new(),
+                seq: seq_from_str(&"ACGT".repeat(40)),
+            },
+            FastaRecord {
+                id: "seq3".to_owned(),
+                description: "Empty, with a description".to_owned(),
+                seq: Vec::new(),
+            },
+        ];
+
+        let mut buf = Vec::new();
+        write_fasta(&mut buf, &records, 60).unwrap();
+        let read = read_fasta(&buf[..]).unwrap();
+
+        assert_eq!(read.len(), records.len());
+        for (a, b) in read.iter().zip(&records) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.description, b.description);
+            assert_eq!(a.seq, b.seq);
+        }
+    }
+
+    #[test]
+    fn write_wrapped_round_trip() {
+        let mut rng = Rng::new(0xFA57A);
+
+        for width in [60, 70, 80] {
+            let records: Vec<_> = (0..20)
+                .map(|i| {
+                    let len = rng.below(300);
+                    FastaRecord {
+                        id: format!("seq{i}"),
+                        description: String::new(),
+                        seq: rng.seq(len),
+                    }
+                })
+                .collect();
+
+            let mut buf = Vec::new();
+            write_fasta(&mut buf, &records, width).unwrap();
+
+            for line in buf.split(|&b| b == b'\n') {
+                assert!(line.len() <= width || line.starts_with(b">"));
+            }
+
+            let read = read_fasta(&buf[..]).unwrap();
+            assert_eq!(read.len(), records.len());
+            for (a, b) in read.iter().zip(&records) {
+                assert_eq!(a.seq, b.seq);
+            }
+        }
+    }
+
+    #[test]
+    fn write_unwrapped() {
+        let record = FastaRecord {
+            id: "long".to_owned(),
+            description: String::new(),
+            seq: seq_from_str(&"GATTACA".repeat(30)),
+        };
+
+        let mut buf = Vec::new();
+        write_fasta(&mut buf, &[record], 0).unwrap();
+
+        let expected = format!(">long\n{}\n", "GATTACA".repeat(30));
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+    }
+}
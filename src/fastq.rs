@@ -0,0 +1,281 @@
+//! This module contains code for reading FASTQ files, and working with their quality scores.
+//!
+//! [FASTQ format](https://en.wikipedia.org/wiki/FASTQ_format)
+
+use std::{
+    io,
+    io::{BufRead, ErrorKind},
+};
+
+use crate::{Nucleotide, Seq};
+
+/// The Phred quality offset for Sanger, and Illumina 1.8+ encodings.
+pub const PHRED_OFFSET_SANGER: u8 = 33;
+/// The Phred quality offset for Illumina 1.3 - 1.7 encodings.
+pub const PHRED_OFFSET_ILLUMINA_64: u8 = 64;
+
+#[derive(Clone, Debug, Default)]
+pub struct FastqRecord {
+    /// The header text after `@`, up to the first whitespace.
+    pub id: String,
+    pub seq: Seq,
+    /// Raw ASCII-encoded quality scores, one per nucleotide.
+    pub qual: Vec<u8>,
+}
+
+impl FastqRecord {
+    /// Convert ASCII-encoded quality to Phred scores. Use `PHRED_OFFSET_SANGER` (33) for Sanger and
+    /// Illumina 1.8+ data, and `PHRED_OFFSET_ILLUMINA_64` for older Illumina data. Characters below the
+    /// offset give 0.
+    pub fn phred_scores(&self, offset: u8) -> Vec<u8> {
+        self.qual.iter().map(|q| q.saturating_sub(offset)).collect()
+    }
+
+    /// The mean Phred score, assuming an offset of 33. Returns 0 for an empty record.
+    pub fn mean_quality(&self) -> f32 {
+        if self.qual.is_empty() {
+            return 0.;
+        }
+
+        let sum: u32 = self
+            .phred_scores(PHRED_OFFSET_SANGER)
+            .iter()
+            .map(|q| *q as u32)
+            .sum();
+
+        sum as f32 / self.qual.len() as f32
+    }
+}
+
+/// Reads FASTQ records one at a time, using the 4-line record format. (Sequence and quality may not be
+/// wrapped across multiple lines.)
+pub struct FastqReader<R: BufRead> {
+    reader: R,
+    /// Reused between lines, to avoid allocating for each.
+    line: Vec<u8>,
+    line_num: usize,
+    /// Set after reaching EOF, or an error.
+    done: bool,
+}
+
+impl<R: BufRead> FastqReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            line_num: 0,
+            done: false,
+        }
+    }
+
+    /// Read the next line into our buffer, without its line ending. Returns false at EOF.
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_num += 1;
+
+        while matches!(self.line.last(), Some(b'\n' | b'\r')) {
+            self.line.pop();
+        }
+
+        Ok(true)
+    }
+
+    /// Read a line that must be present, as part of a record.
+    fn read_line_required(&mut self, id: &str) -> io::Result<()> {
+        if !self.read_line()? {
+            return Err(self.error(&format!("Unexpected end of file in record {id}")));
+        }
+        Ok(())
+    }
+
+    fn error(&self, msg: &str) -> io::Error {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{msg}, on line {}", self.line_num),
+        )
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<FastqRecord>> {
+        // Skip blank lines between records, e.g. at the end of the file.
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            if !self.line.iter().all(u8::is_ascii_whitespace) {
+                break;
+            }
+        }
+
+        let Some(header) = self.line.strip_prefix(b"@") else {
+            return Err(self.error("Expected a FASTQ header starting with '@'"));
+        };
+        let header = String::from_utf8_lossy(header);
+        let id = header
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+
+        self.read_line_required(&id)?;
+        let mut seq = Vec::with_capacity(self.line.len());
+        for byte in &self.line {
+            match Nucleotide::from_u8_letter(*byte) {
+                Ok(nt) => seq.push(nt),
+                Err(_) => {
+                    return Err(self.error(&format!(
+                        "Invalid nucleotide '{}' in record {id}",
+                        *byte as char
+                    )))
+                }
+            }
+        }
+
+        self.read_line_required(&id)?;
+        if !self.line.starts_with(b"+") {
+            return Err(self.error(&format!("Expected a '+' separator in record {id}")));
+        }
+
+        self.read_line_required(&id)?;
+        let qual = self.line.clone();
+
+        if qual.len() != seq.len() {
+            return Err(self.error(&format!(
+                "Sequence length ({}) and quality length ({}) don't match in record {id}",
+                seq.len(),
+                qual.len()
+            )));
+        }
+
+        Ok(Some(FastqRecord { id, seq, qual }))
+    }
+}
+
+impl<R: BufRead> Iterator for FastqReader<R> {
+    type Item = io::Result<FastqRecord>;
+
+    /// Returns `None` after the last record, or after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.read_record();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_from_str;
+
+    fn read(data: &[u8]) -> io::Result<Vec<FastqRecord>> {
+        FastqReader::new(data).collect()
+    }
+
+    #[test]
+    fn records() {
+        let data = b"@read1 extra\nGATTACA\n+\nIIIII##\n\n@read2\r\nAC\r\n+read2\r\n!5\r\n";
+        let records = read(data).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "read1");
+        assert_eq!(records[0].seq, seq_from_str("GATTACA"));
+        assert_eq!(records[0].qual, b"IIIII##");
+        assert_eq!(records[1].id, "read2");
+        assert_eq!(records[1].qual, b"!5");
+    }
+
+    #[test]
+    fn phred_scores() {
+        let record = FastqRecord {
+            id: "read".to_owned(),
+            seq: seq_from_str("ACGT"),
+            qual: b"!5I@".to_vec(),
+        };
+
+        assert_eq!(
+            record.phred_scores(PHRED_OFFSET_SANGER),
+            vec![0, 20, 40, 31]
+        );
+        // Characters below the offset saturate at 0.
+        assert_eq!(
+            record.phred_scores(PHRED_OFFSET_ILLUMINA_64),
+            vec![0, 0, 9, 0]
+        );
+
+        let record = FastqRecord {
+            qual: b"@Th".to_vec(),
+            ..record
+        };
+        assert_eq!(
+            record.phred_scores(PHRED_OFFSET_ILLUMINA_64),
+            vec![0, 20, 40]
+        );
+    }
+
+    #[test]
+    fn mean_quality() {
+        let record = FastqRecord {
+            id: "read".to_owned(),
+            seq: seq_from_str("ACGT"),
+            qual: b"!5I?".to_vec(),
+        };
+        // (0 + 20 + 40 + 30) / 4
+        assert_eq!(record.mean_quality(), 22.5);
+
+        assert_eq!(FastqRecord::default().mean_quality(), 0.);
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let err = read(b"@read1\nGATTACA\n+\nIIII\n").unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "Sequence length (7) and quality length (4) don't match in record read1, on line 4"
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let mut reader = FastqReader::new(&b"@read1\nACGT\n+\nIIII\n@read2\nACGT\n"[..]);
+
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected end of file in record read2, on line 6"
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn malformed() {
+        let err = read(b">read1\nACGT\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected a FASTQ header starting with '@', on line 1"
+        );
+
+        let err = read(b"@read1\nACGT\nIIII\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected a '+' separator in record read1, on line 3"
+        );
+
+        let err = read(b"@read1\nACNT\n+\nIIII\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid nucleotide 'N' in record read1, on line 2"
+        );
+    }
+}
@@ -0,0 +1,662 @@
+//! This module contains code for reading and writing GenBank flat files. It handles the LOCUS line,
+//! DEFINITION, the FEATURES table, and the ORIGIN sequence; other sections are ignored.
+//!
+//! [GenBank format](https://www.ncbi.nlm.nih.gov/Sitemap/samplerecord.html)
+
+use std::{
+    io,
+    io::{BufRead, ErrorKind, Write},
+};
+
+use crate::{Nucleotide, Seq, SeqTopology};
+
+/// One entry in the FEATURES table.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Feature {
+    /// E.g. `CDS`, `gene`, `promoter`.
+    pub key: String,
+    /// The location, as written; e.g. `complement(join(12..80,140..200))`. Continuation lines are joined.
+    pub location: String,
+    /// Qualifier names (without `/`) and values (without quotes), in order. Qualifiers without a value,
+    /// like `/pseudo`, have an empty value.
+    pub qualifiers: Vec<(String, String)>,
+}
+
+impl Feature {
+    /// The value of the first qualifier with this name, if present.
+    pub fn qualifier(&self, name: &str) -> Option<&str> {
+        self.qualifiers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct GenbankRecord {
+    /// The locus name, from the LOCUS line.
+    pub locus: String,
+    pub definition: String,
+    /// The date from the LOCUS line, e.g. `15-MAR-2005`. Empty if there isn't one; `write_genbank` writes
+    /// `DATE_DEFAULT` in that case.
+    pub date: String,
+    pub topology: SeqTopology,
+    pub features: Vec<Feature>,
+    pub seq: Seq,
+}
+
+/// Qualifier lines, and continuation lines, are indented at least this far. Feature keys are indented
+/// less. (Officially, 21 and 5 columns; we're lenient, since not all software follows this exactly.)
+const QUALIFIER_INDENT_MIN: usize = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Header,
+    Features,
+    Origin,
+}
+
+fn error(msg: &str, line_num: usize) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, format!("{msg}, on line {line_num}"))
+}
+
+/// Whether a qualifier value has an opening quote, but no closing one yet. Quotes inside values are
+/// doubled, so a complete quoted value has an even number of them.
+fn quote_open(val: &str) -> bool {
+    val.starts_with('"') && val.matches('"').count() % 2 == 1
+}
+
+/// A date in GenBank's format, e.g. `15-MAR-2005`.
+fn is_date(val: &str) -> bool {
+    let bytes = val.as_bytes();
+    bytes.len() == 11
+        && bytes[2] == b'-'
+        && bytes[6] == b'-'
+        && bytes[..2].iter().chain(&bytes[7..]).all(u8::is_ascii_digit)
+        && bytes[3..6].iter().all(u8::is_ascii_alphabetic)
+}
+
+/// Remove surrounding quotes from qualifier values.
+fn finish_qualifier(feature: &mut Feature) {
+    if let Some((_, val)) = feature.qualifiers.last_mut() {
+        if val.len() >= 2 && val.starts_with('"') && val.ends_with('"') {
+            *val = val[1..val.len() - 1].replace("\"\"", "\"");
+        }
+    }
+}
+
+/// Add a continuation line to a qualifier value. Lines are joined with a space, except in translations,
+/// which are wrapped without them. This matches `write_genbank`.
+fn push_continuation(name: &str, val: &mut String, content: &str) {
+    if name != "translation" {
+        val.push(' ');
+    }
+    val.push_str(content);
+}
+
+/// Read a GenBank record. If the file contains more than one, only the first is read.
+pub fn read_genbank<R: BufRead>(reader: R) -> io::Result<GenbankRecord> {
+    let mut result = GenbankRecord::default();
+    let mut found_locus = false;
+    let mut section = Section::Header;
+    // Set while reading the DEFINITION, which may continue onto indented lines.
+    let mut in_definition = false;
+    let mut locus_len = None;
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        let line_num = i + 1;
+
+        if line.starts_with("//") {
+            break;
+        }
+
+        // Non-indented lines start a new section.
+        if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+            if let Some(feature) = result.features.last_mut() {
+                finish_qualifier(feature);
+            }
+
+            let mut tokens = line.split_whitespace();
+            let keyword = tokens.next().unwrap_or_default();
+            in_definition = keyword == "DEFINITION";
+
+            section = match keyword {
+                "LOCUS" => {
+                    found_locus = true;
+                    result.locus = tokens.next().unwrap_or_default().to_owned();
+
+                    let tokens: Vec<_> = tokens.collect();
+                    // E.g. "2686 bp"; some exporters omit the space.
+                    locus_len = tokens.iter().enumerate().find_map(|(i, t)| {
+                        let num = t.strip_suffix("bp").unwrap_or(t);
+                        let is_bp = t.ends_with("bp")
+                            || tokens.get(i + 1).is_some_and(|next| *next == "bp");
+                        if is_bp {
+                            num.parse::<usize>().ok()
+                        } else {
+                            None
+                        }
+                    });
+
+                    result.topology = if tokens.iter().any(|t| t.eq_ignore_ascii_case("circular")) {
+                        SeqTopology::Circular
+                    } else {
+                        SeqTopology::Linear
+                    };
+
+                    if let Some(date) = tokens.last().filter(|t| is_date(t)) {
+                        result.date = date.to_string();
+                    }
+                    Section::Header
+                }
+                "DEFINITION" => {
+                    result.definition = line["DEFINITION".len()..].trim().to_owned();
+                    Section::Header
+                }
+                "FEATURES" => Section::Features,
+                "ORIGIN" => Section::Origin,
+                _ => Section::Header,
+            };
+            continue;
+        }
+
+        match section {
+            Section::Header => {
+                if in_definition && !line.trim().is_empty() {
+                    result.definition.push(' ');
+                    result.definition.push_str(line.trim());
+                }
+            }
+            Section::Features => {
+                let indent = line.len() - line.trim_start().len();
+                let content = line.trim_start();
+
+                if content.is_empty() {
+                    continue;
+                }
+
+                // Inside a quoted value, every line continues it; even ones starting with `/`.
+                if let Some((name, val)) = result
+                    .features
+                    .last_mut()
+                    .and_then(|f| f.qualifiers.last_mut())
+                    .filter(|(_, val)| quote_open(val))
+                {
+                    push_continuation(name, val, content);
+                    continue;
+                }
+
+                if indent < QUALIFIER_INDENT_MIN {
+                    if let Some(feature) = result.features.last_mut() {
+                        finish_qualifier(feature);
+                    }
+
+                    let (key, location) = content
+                        .split_once(char::is_whitespace)
+                        .unwrap_or((content, ""));
+
+                    result.features.push(Feature {
+                        key: key.to_owned(),
+                        location: location.trim().to_owned(),
+                        qualifiers: Vec::new(),
+                    });
+                    continue;
+                }
+
+                let Some(feature) = result.features.last_mut() else {
+                    return Err(error("Feature qualifier without a feature", line_num));
+                };
+
+                if let Some(qual) = content.strip_prefix('/') {
+                    finish_qualifier(feature);
+
+                    let (name, val) = qual.split_once('=').unwrap_or((qual, ""));
+                    feature.qualifiers.push((name.to_owned(), val.to_owned()));
+                } else if let Some((name, val)) = feature.qualifiers.last_mut() {
+                    push_continuation(name, val, content);
+                } else {
+                    // Continuation of the location.
+                    feature.location.push_str(content);
+                }
+            }
+            Section::Origin => {
+                for byte in line.bytes() {
+                    if byte.is_ascii_whitespace() || byte.is_ascii_digit() {
+                        continue;
+                    }
+
+                    match Nucleotide::from_u8_letter(byte) {
+                        Ok(nt) => result.seq.push(nt),
+                        Err(_) => {
+                            return Err(error(
+                                &format!("Invalid nucleotide '{}'", byte as char),
+                                line_num,
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if !found_locus {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "No LOCUS line found",
+        ));
+    }
+
+    if let Some(feature) = result.features.last_mut() {
+        finish_qualifier(feature);
+    }
+
+    if let Some(len) = locus_len {
+        if !result.seq.is_empty() && result.seq.len() != len {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The LOCUS line gives a length of {len}, but the sequence has {} nucleotides",
+                    result.seq.len()
+                ),
+            ));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Qualifier and location lines start at this column.
+const QUALIFIER_INDENT: usize = 21;
+const DEFINITION_INDENT: usize = 12;
+const LINE_WIDTH: usize = 79;
+
+/// Written on the LOCUS line of records without a date.
+pub const DATE_DEFAULT: &str = "01-JAN-1980";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Wrap {
+    /// Break lines at spaces, which the reader replaces. Words longer than a line aren't split, so
+    /// that values like URLs read back intact; their lines run past `LINE_WIDTH`.
+    Words,
+    /// Break lines anywhere, preferring after a comma. The reader joins these without a separator.
+    /// For locations and translations, which don't contain spaces.
+    Chars,
+}
+
+/// The byte index at which to end the first line of `text`, and where the next line starts, or `None`
+/// if it fits on one line.
+fn line_break(text: &str, width: usize, wrap: Wrap) -> Option<(usize, usize)> {
+    if text.chars().count() <= width {
+        return None;
+    }
+
+    // Byte indices of the first `width` chars, plus the one after.
+    let bounds: Vec<_> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .take(width + 1)
+        .collect();
+    let split = bounds[width];
+
+    match wrap {
+        Wrap::Words => {
+            // A space within the line, or right after it. Avoid starting a line with `/`, so it can't
+            // be mistaken for a new qualifier.
+            let spaces: Vec<_> = bounds
+                .iter()
+                .copied()
+                .filter(|&i| i > 0 && text.as_bytes()[i] == b' ')
+                .collect();
+            let not_slash = |i: &&usize| !text[**i + 1..].starts_with('/');
+
+            let space = spaces
+                .iter()
+                .rev()
+                .find(not_slash)
+                .or(spaces.last())
+                .copied()
+                // A word longer than the line. Keep it whole.
+                .or_else(|| text[split..].find(' ').map(|i| i + split));
+
+            space
+                .map(|i| (i, i + 1))
+                .filter(|(_, next)| *next < text.len())
+        }
+        Wrap::Chars => {
+            let i = text[..split].rfind(',').map(|i| i + 1).unwrap_or(split);
+            Some((i, i))
+        }
+    }
+}
+
+/// Write text starting at column `indent`, after `first_prefix` on the first line. Long lines are wrapped,
+/// as described by `wrap`.
+fn write_wrapped<W: Write>(
+    w: &mut W,
+    first_prefix: &str,
+    indent: usize,
+    text: &str,
+    wrap: Wrap,
+) -> io::Result<()> {
+    let width = LINE_WIDTH - indent;
+    let mut prefix = first_prefix;
+    let mut remaining = text;
+
+    loop {
+        let (line, rest) = match line_break(remaining, width, wrap) {
+            Some((end, next)) => (&remaining[..end], &remaining[next..]),
+            None => (remaining, ""),
+        };
+
+        writeln!(w, "{prefix:<indent$}{line}")?;
+
+        if rest.is_empty() {
+            break;
+        }
+        prefix = "";
+        remaining = rest;
+    }
+
+    Ok(())
+}
+
+/// Write a record in GenBank format. The sequence is written in lower case, 60 nucleotides per line.
+/// Qualifier values are quoted, unless they're integers, or empty. Reading the result with `read_genbank`
+/// produces the same record, as long as its text doesn't contain runs of spaces or newlines.
+pub fn write_genbank<W: Write>(w: &mut W, record: &GenbankRecord) -> io::Result<()> {
+    let topology = match record.topology {
+        SeqTopology::Linear => "linear",
+        SeqTopology::Circular => "circular",
+    };
+
+    writeln!(
+        w,
+        "LOCUS       {:<16} {:>11} bp    DNA     {:<8} UNA {}",
+        record.locus,
+        record.seq.len(),
+        topology,
+        if record.date.is_empty() {
+            DATE_DEFAULT
+        } else {
+            &record.date
+        }
+    )?;
+
+    let definition = if record.definition.is_empty() {
+        "."
+    } else {
+        &record.definition
+    };
+    write_wrapped(w, "DEFINITION", DEFINITION_INDENT, definition, Wrap::Words)?;
+
+    writeln!(w, "FEATURES             Location/Qualifiers")?;
+    for feature in &record.features {
+        write_wrapped(
+            w,
+            &format!("     {}", feature.key),
+            QUALIFIER_INDENT,
+            &feature.location,
+            Wrap::Chars,
+        )?;
+
+        for (name, val) in &feature.qualifiers {
+            let qual = if val.is_empty() {
+                format!("/{name}")
+            } else if val.parse::<i64>().is_ok() {
+                format!("/{name}={val}")
+            } else {
+                format!("/{name}=\"{}\"", val.replace('"', "\"\""))
+            };
+            let wrap = if name == "translation" {
+                Wrap::Chars
+            } else {
+                Wrap::Words
+            };
+            write_wrapped(w, "", QUALIFIER_INDENT, &qual, wrap)?;
+        }
+    }
+
+    writeln!(w, "ORIGIN")?;
+    for (i, line) in record.seq.chunks(60).enumerate() {
+        write!(w, "{:>9}", i * 60 + 1)?;
+        for group in line.chunks(10) {
+            let group: String = group.iter().map(|nt| nt.to_u8_lower() as char).collect();
+            write!(w, " {group}")?;
+        }
+        writeln!(w)?;
+    }
+    writeln!(w, "//")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{amino_acids::translate_protein, seq_aa_to_str};
+
+    /// A small record in NCBI's layout, with a spliced CDS.
+    const RECORD: &str = "\
+LOCUS       TST0001                  120 bp    DNA     circular SYN 14-FEB-2023
+DEFINITION  Synthetic construct tst gene, complete cds, with a definition long
+            enough to wrap.
+ACCESSION   TST0001
+VERSION     TST0001.1
+KEYWORDS    .
+SOURCE      synthetic construct
+  ORGANISM  synthetic construct
+            other sequences; artificial sequences.
+REFERENCE   1  (bases 1 to 120)
+  AUTHORS   Doe,J.
+  TITLE     Direct Submission
+  JOURNAL   Submitted (14-FEB-2023)
+FEATURES             Location/Qualifiers
+     source          1..120
+                     /organism=\"synthetic construct\"
+                     /mol_type=\"other DNA\"
+     gene            11..55
+                     /gene=\"tst\"
+     CDS             join(11..25,41..55)
+                     /gene=\"tst\"
+                     /codon_start=1
+                     /product=\"test protein\"
+                     /note=\"a note that is long enough that it wraps onto a second
+                     line\"
+                     /translation=\"MAKEGLFRK\"
+     misc_feature    complement(join(1..3,5..7,9..11,13..15,17..19,21..23,
+                     25..27))
+                     /note=\"spread out\"
+ORIGIN      
+        1 gtcgacaagc atggctaaag aaggtgtaag tctttcatag ctgtttcgca aataaccgta
+       61 atgcctttcc ctaacagagt ttttcgaact cgtgttgtcg agcgacggaa ttagatcagt
+//
+";
+
+    #[test]
+    fn read_ncbi_record() {
+        let record = read_genbank(RECORD.as_bytes()).unwrap();
+
+        assert_eq!(record.locus, "TST0001");
+        assert_eq!(record.topology, SeqTopology::Circular);
+        assert_eq!(
+            record.definition,
+            "Synthetic construct tst gene, complete cds, with a definition long enough to wrap."
+        );
+        assert_eq!(record.seq.len(), 120);
+        assert_eq!(record.features.len(), 4);
+
+        let cds = &record.features[2];
+        assert_eq!(cds.key, "CDS");
+        assert_eq!(cds.location, "join(11..25,41..55)");
+        assert_eq!(cds.qualifier("codon_start"), Some("1"));
+        assert_eq!(cds.qualifier("product"), Some("test protein"));
+        assert_eq!(
+            cds.qualifier("note"),
+            Some("a note that is long enough that it wraps onto a second line")
+        );
+
+        // Splice the exons, and check they translate to the annotated protein.
+        let mut coding = record.seq[10..25].to_vec();
+        coding.extend(&record.seq[40..55]);
+        assert_eq!(
+            seq_aa_to_str(&translate_protein(&coding, true)),
+            cds.qualifier("translation").unwrap()
+        );
+
+        let misc = &record.features[3];
+        assert_eq!(
+            misc.location,
+            "complement(join(1..3,5..7,9..11,13..15,17..19,21..23,25..27))"
+        );
+        assert_eq!(misc.qualifier("note"), Some("spread out"));
+    }
+
+    #[test]
+    fn read_linear_and_errors() {
+        let linear = RECORD.replace("circular", "linear");
+        let record = read_genbank(linear.as_bytes()).unwrap();
+        assert_eq!(record.topology, SeqTopology::Linear);
+
+        let wrong_len = RECORD.replace("120 bp", "121 bp");
+        assert!(read_genbank(wrong_len.as_bytes()).is_err());
+
+        let bad_nt = RECORD.replace("gtcgacaagc", "gtcgacaxgc");
+        assert!(read_genbank(bad_nt.as_bytes()).is_err());
+
+        assert!(read_genbank("ORIGIN\n//\n".as_bytes()).is_err());
+    }
+
+    /// As exported by SnapGene: `ds-DNA`, CRLF line endings, and a blank line before ORIGIN.
+    const SNAPGENE: &str =
+        "LOCUS       pTest                     60 bp ds-DNA     circular SYN 10-JUN-2020\r
+DEFINITION  .\r
+FEATURES             Location/Qualifiers\r
+     source          1..60\r
+                     /organism=\"synthetic DNA construct\"\r
+                     /mol_type=\"other DNA\"\r
+     promoter        complement(5..20)\r
+                     /label=lac promoter\r
+                     /note=\"E. coli lac promoter; see \"\"lacZ\"\"\"\r
+\r
+ORIGIN\r
+        1 gtcgacaagc atggctaaag aaggtgtaag tctttcatag ctgtttcgca aataaccgta\r
+//\r
+";
+
+    /// As exported by Benchling: No space before "bp", wider columns, and unwrapped qualifiers.
+    const BENCHLING: &str = "LOCUS       pTest 60bp DNA circular 10-JUN-2020
+DEFINITION  .
+FEATURES             Location/Qualifiers
+     source          1..60
+                     /organism=\"synthetic DNA construct\"
+                     /mol_type=\"other DNA\"
+     promoter        complement(5..20)
+                     /label=lac promoter
+                     /note=\"E. coli lac promoter; see \"\"lacZ\"\"\"
+ORIGIN
+        1  gtcgacaagc  atggctaaag  aaggtgtaag  tctttcatag  ctgtttcgca  aataaccgta
+//
+";
+
+    fn round_trip(record: &GenbankRecord) -> GenbankRecord {
+        let mut data = Vec::new();
+        write_genbank(&mut data, record).unwrap();
+        read_genbank(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn exporters() {
+        let snapgene = read_genbank(SNAPGENE.as_bytes()).unwrap();
+        let benchling = read_genbank(BENCHLING.as_bytes()).unwrap();
+        assert_eq!(snapgene, benchling);
+
+        assert_eq!(snapgene.locus, "pTest");
+        assert_eq!(snapgene.date, "10-JUN-2020");
+        assert_eq!(snapgene.topology, SeqTopology::Circular);
+        assert_eq!(snapgene.seq.len(), 60);
+        assert_eq!(snapgene.features[1].location, "complement(5..20)");
+        assert_eq!(
+            snapgene.features[1].qualifier("label"),
+            Some("lac promoter")
+        );
+        assert_eq!(
+            snapgene.features[1].qualifier("note"),
+            Some("E. coli lac promoter; see \"lacZ\"")
+        );
+
+        for record in [snapgene, benchling] {
+            assert_eq!(round_trip(&record), record);
+        }
+
+        let record = read_genbank(RECORD.as_bytes()).unwrap();
+        assert_eq!(record.date, "14-FEB-2023");
+        assert_eq!(round_trip(&record), record);
+    }
+
+    #[test]
+    fn write_long_values() {
+        let url = format!("https://example.com/{}", "a".repeat(80));
+        let record = GenbankRecord {
+            locus: "long".to_owned(),
+            definition: "A record with long values.".to_owned(),
+            features: vec![Feature {
+                key: "CDS".to_owned(),
+                location: format!(
+                    "join({})",
+                    (0..20)
+                        .map(|i| format!("{}..{}", i * 10 + 1, i * 10 + 5))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+                qualifiers: vec![
+                    ("note".to_owned(), format!("See {url} for details")),
+                    (
+                        "note".to_owned(),
+                        format!("{} /gene=\"fake\" /other", "word ".repeat(10).trim()),
+                    ),
+                    ("translation".to_owned(), "MAKEGLFRK".repeat(12)),
+                ],
+            }],
+            ..Default::default()
+        };
+
+        let mut data = Vec::new();
+        write_genbank(&mut data, &record).unwrap();
+        let text = String::from_utf8(data).unwrap();
+
+        assert!(text.starts_with("LOCUS") && text.lines().next().unwrap().ends_with(DATE_DEFAULT));
+        for line in text.lines() {
+            // Only the URL may run past the line width.
+            assert!(line.len() <= LINE_WIDTH || line.contains(&url), "{line}");
+        }
+        // Qualifiers are the only lines that start with `/`, other than the terminator.
+        let slash_lines = text
+            .lines()
+            .filter(|l| l.trim_start().starts_with('/') && *l != "//")
+            .count();
+        assert_eq!(slash_lines, 3);
+
+        let mut read = read_genbank(text.as_bytes()).unwrap();
+        assert_eq!(read.date, DATE_DEFAULT);
+        read.date = String::new();
+        assert_eq!(read, record);
+    }
+
+    #[test]
+    fn read_slash_in_quoted_value() {
+        let text = RECORD.replace(
+            "/note=\"spread out\"",
+            "/note=\"spread out over\n                     /several lines\"",
+        );
+        let record = read_genbank(text.as_bytes()).unwrap();
+
+        let misc = &record.features[3];
+        assert_eq!(misc.qualifiers.len(), 1);
+        assert_eq!(
+            misc.qualifier("note"),
+            Some("spread out over /several lines")
+        );
+    }
+}
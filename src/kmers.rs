@@ -0,0 +1,137 @@
+//! This module contains code for counting k-mers, using a compact encoding. Each k-mer is stored in a
+//! `u64`, with symbol `i` at bits `Alphabet::BITS * i` and up, using its `Alphabet` index. For nucleotides,
+//! this is 2 bits per nucleotide, with the same conventions as `serialize_seq_bin`, and k-mers of up to 32.
+
+use std::collections::HashMap;
+
+use crate::alphabet::Alphabet;
+
+/// The longest nucleotide k-mer that fits in a `u64`.
+pub const KMER_LEN_MAX: usize = 32;
+
+/// The longest k-mer of an alphabet that fits in a `u64`; e.g. 32 for nucleotides, and 12 for amino acids.
+pub fn kmer_len_max<T: Alphabet>() -> usize {
+    (u64::BITS / T::BITS) as usize
+}
+
+/// Encode a k-mer. Returns `None` if it's longer than `kmer_len_max`.
+pub fn seq_to_kmer<T: Alphabet>(seq: &[T]) -> Option<u64> {
+    if seq.len() > kmer_len_max::<T>() {
+        return None;
+    }
+
+    Some(seq.iter().enumerate().fold(0, |code, (i, s)| {
+        code | (s.to_index() as u64) << (T::BITS as usize * i)
+    }))
+}
+
+/// Decode a k-mer of length `k`. `k` is clamped to `kmer_len_max`. Panics if `code` contains an index
+/// that isn't a symbol; this can't happen for nucleotides, or for codes from `seq_to_kmer`.
+pub fn kmer_to_seq<T: Alphabet>(code: u64, k: usize) -> Vec<T> {
+    let mask = (1 << T::BITS) - 1;
+
+    (0..k.min(kmer_len_max::<T>()))
+        .map(|i| T::from_index(((code >> (T::BITS as usize * i)) & mask) as usize).unwrap())
+        .collect()
+}
+
+/// Count each k-mer in a sequence, including overlapping ones. Keys are encoded as described in the
+/// module docs. Returns an empty map if `k` is 0, greater than `kmer_len_max`, or longer than the sequence.
+pub fn count_kmers<T: Alphabet>(seq: &[T], k: usize) -> HashMap<u64, u32> {
+    let mut result = HashMap::new();
+
+    if k == 0 || k > kmer_len_max::<T>() || k > seq.len() {
+        return result;
+    }
+
+    // Roll the encoding along the sequence: Drop the first symbol from the low bits, and add the
+    // next one at the high end.
+    let bits = T::BITS as usize;
+    let high_shift = bits * (k - 1);
+    let mut code = seq_to_kmer(&seq[..k - 1]).unwrap() << bits;
+
+    for s in &seq[k - 1..] {
+        code = (code >> bits) | (s.to_index() as u64) << high_shift;
+        *result.entry(code).or_insert(0) += 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_aa_from_str, seq_from_str, AminoAcid, Nucleotide};
+
+    fn count(counts: &HashMap<u64, u32>, kmer: &str) -> u32 {
+        let code = seq_to_kmer(&seq_from_str(kmer)).unwrap();
+        counts.get(&code).copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn overlapping_repeats() {
+        let counts = count_kmers(&seq_from_str("AAAAA"), 3);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(count(&counts, "AAA"), 3);
+
+        let counts = count_kmers(&seq_from_str("ACACACA"), 2);
+        assert_eq!(count(&counts, "AC"), 3);
+        assert_eq!(count(&counts, "CA"), 3);
+        assert_eq!(count(&counts, "AA"), 0);
+
+        let counts = count_kmers(&seq_from_str("GATTACATTAC"), 4);
+        assert_eq!(count(&counts, "TTAC"), 2);
+        assert_eq!(count(&counts, "ATTA"), 2);
+        assert_eq!(counts.values().sum::<u32>(), 8);
+    }
+
+    #[test]
+    fn count_edge_cases() {
+        let seq = seq_from_str("ACGT");
+        assert!(count_kmers(&seq, 0).is_empty());
+        assert!(count_kmers(&seq, 5).is_empty());
+        assert_eq!(count(&count_kmers(&seq, 4), "ACGT"), 1);
+    }
+
+    #[test]
+    fn round_trip() {
+        let seq = seq_from_str("GATTACACGTTGCAGTCCATGGATCCGAATTCAAGCTT");
+
+        for k in [3, 16, 32] {
+            let counts = count_kmers(&seq, k);
+            assert_eq!(counts.values().sum::<u32>() as usize, seq.len() - k + 1);
+
+            for window in seq.windows(k) {
+                let code = seq_to_kmer(window).unwrap();
+                assert_eq!(kmer_to_seq::<Nucleotide>(code, k), window);
+                assert!(counts.contains_key(&code));
+            }
+        }
+
+        assert_eq!(seq_to_kmer(&seq[..33]), None);
+        assert_eq!(kmer_len_max::<Nucleotide>(), KMER_LEN_MAX);
+    }
+
+    #[test]
+    fn amino_acids() {
+        let seq = seq_aa_from_str("MKVLAAMKVLWYMKV");
+        assert_eq!(kmer_len_max::<AminoAcid>(), 12);
+
+        let counts = count_kmers(&seq, 3);
+        let code = seq_to_kmer(&seq_aa_from_str("MKV")).unwrap();
+        assert_eq!(counts[&code], 3);
+        assert_eq!(counts.values().sum::<u32>() as usize, seq.len() - 2);
+
+        for k in [1, 5, 12] {
+            let counts = count_kmers(&seq, k);
+            for window in seq.windows(k) {
+                let code = seq_to_kmer(window).unwrap();
+                assert_eq!(kmer_to_seq::<AminoAcid>(code, k), window);
+                assert!(counts.contains_key(&code));
+            }
+        }
+
+        assert!(count_kmers(&seq, 13).is_empty());
+        assert_eq!(seq_to_kmer(&seq[..13]), None);
+    }
+}
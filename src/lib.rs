@@ -1,24 +1,144 @@
-use std::{io, io::ErrorKind};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, io,
+    io::{ErrorKind, Read, Write},
+};
 
 use bincode::{Decode, Encode};
 
-use crate::Nucleotide::*;
+use crate::{
+    alphabet::{seq_to_letters, Alphabet},
+    warnings::{Warning, WarningCode, Warnings},
+    Nucleotide::*,
+};
 pub use crate::{
     amino_acids::{AaIdent, AminoAcid, CodingResult},
+    element::{formula_mass, formula_string, Element},
     nucleotide::{Nucleotide, NucleotideGeneral},
+    packed_seq::PackedSeq,
     restriction_enzyme::RestrictionEnzyme,
+    seq_buf::SeqBuf,
 };
 
+pub mod align;
+pub mod alphabet;
 pub mod amino_acids;
+pub mod codon_usage;
+pub mod color;
+pub mod distance;
+pub mod duplex;
+pub mod element;
+pub mod fasta;
+pub mod fastq;
+pub mod genbank;
+pub mod kmers;
 pub mod ligation;
+pub mod mutation;
 pub mod nucleotide;
+pub mod orf;
+pub mod packed_seq;
+pub mod primer;
+pub mod protein;
 pub mod re_lib;
 pub mod restriction_enzyme;
+mod rng;
+pub mod seq_buf;
+pub mod sniff;
+#[cfg(test)]
+mod test_utils;
+pub mod twobit;
+pub mod warnings;
 
 // Index 0: 5' end.
 pub type Seq = Vec<Nucleotide>;
 
-pub struct IndexError {}
+/// An out-of-range index. `index` is as passed by the caller; e.g. 1-based for `insert_into_seq`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IndexError {
+    pub index: usize,
+    /// The length of the sequence indexed.
+    pub len: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Index {} is out of range for a sequence of length {}",
+            self.index, self.len
+        )
+    }
+}
+
+impl Error for IndexError {}
+
+/// An error from parsing or decoding a sequence, or a single nucleotide or amino acid. Converts to
+/// `io::Error`, with kind `InvalidData`, for use in IO code.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SeqError {
+    /// A byte that isn't a valid nucleotide letter.
+    InvalidNucleotide(u8),
+    /// A string that isn't a valid amino acid identifier.
+    InvalidAminoAcid(String),
+    /// A string that isn't a valid element symbol or name.
+    InvalidElement(String),
+    /// An invalid character in a sequence string, and its 0-based position. `alphabet` is e.g. "nucleotide".
+    InvalidSymbol {
+        symbol: char,
+        position: usize,
+        alphabet: &'static str,
+    },
+    /// Binary data is shorter than required. Lengths are in bytes.
+    TruncatedData { expected: usize, found: usize },
+    /// A stored length is too large to represent on this platform.
+    LengthOverflow(u64),
+    /// Two sequences that must be the same length aren't.
+    LengthMismatch { len_a: usize, len_b: usize },
+    /// A coding sequence whose length, given here, isn't a multiple of 3.
+    IncompleteCodon(usize),
+    /// An invalid element of a motif pattern, e.g. PROSITE syntax.
+    InvalidMotif(String),
+}
+
+impl fmt::Display for SeqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNucleotide(val) => {
+                write!(f, "Invalid nucleotide letter '{}'", *val as char)
+            }
+            Self::InvalidAminoAcid(val) => write!(f, "Invalid amino acid '{val}'"),
+            Self::InvalidElement(val) => write!(f, "Invalid element '{val}'"),
+            Self::InvalidSymbol {
+                symbol,
+                position,
+                alphabet,
+            } => write!(f, "Invalid {alphabet} '{symbol}' at position {position}"),
+            Self::TruncatedData { expected, found } => write!(
+                f,
+                "Sequence data is truncated: expected {expected} bytes; found {found}"
+            ),
+            Self::LengthOverflow(len) => {
+                write!(f, "Length {len} is too large for this platform")
+            }
+            Self::LengthMismatch { len_a, len_b } => {
+                write!(f, "Sequence lengths don't match: {len_a} and {len_b}")
+            }
+            Self::IncompleteCodon(len) => {
+                write!(f, "Coding sequence length {len} isn't a multiple of 3")
+            }
+            Self::InvalidMotif(val) => write!(f, "Invalid motif element: {val}"),
+        }
+    }
+}
+
+impl Error for SeqError {}
+
+impl From<SeqError> for io::Error {
+    fn from(e: SeqError) -> Self {
+        io::Error::new(ErrorKind::InvalidData, e)
+    }
+}
 
 /// Reverse direction, and swap C for G, A for T.
 pub fn seq_complement(seq: &[Nucleotide]) -> Seq {
@@ -32,14 +152,35 @@ pub fn seq_complement(seq: &[Nucleotide]) -> Seq {
     result
 }
 
-/// Create a nucleotide sequence from a string. (Case insensitive)
+/// Like `seq_complement`, but modifies the sequence in place, instead of allocating a new one.
+pub fn seq_complement_in_place(seq: &mut [Nucleotide]) {
+    seq.reverse();
+
+    for nt in seq {
+        *nt = nt.complement();
+    }
+}
+
+/// Swap C for G, A for T, without reversing. E.g. for displaying the bottom strand aligned under the top.
+pub fn seq_complement_no_rev(seq: &[Nucleotide]) -> Seq {
+    seq.iter().map(|nt| nt.complement()).collect()
+}
+
+/// Create a nucleotide sequence from a string. (Case insensitive). Other characters, including RNA's
+/// `U`, are skipped; use `seq_from_str_kind` to parse RNA.
 pub fn seq_from_str(str: &str) -> Seq {
+    seq_from_str_kind(str, SeqKind::Dna)
+}
+
+/// Like `seq_from_str`, but if `kind` is `SeqKind::Rna`, also reads `U` as `T`.
+pub fn seq_from_str_kind(str: &str, kind: SeqKind) -> Seq {
     let mut result = Vec::new();
 
     for char in str.to_lowercase().chars() {
         match char {
             'a' => result.push(A),
             't' => result.push(T),
+            'u' if kind == SeqKind::Rna => result.push(T),
             'c' => result.push(C),
             'g' => result.push(G),
             _ => (),
@@ -63,6 +204,119 @@ pub fn seq_aa_from_str(str: &str) -> Vec<AminoAcid> {
     result
 }
 
+/// Like `seq_from_str`, but reports the characters it skips as warnings: One per run of ambiguous
+/// nucleotides (e.g. `N`, or another IUPAC code), or of other invalid symbols. Whitespace is skipped
+/// silently, and doesn't split a run. Positions are 0-based char indices into `str`.
+pub fn seq_from_str_with_warnings(str: &str, warnings: &mut Warnings) -> Seq {
+    let mut result = Vec::new();
+    // The warning code, start position, and length of the current run of skipped characters.
+    let mut run: Option<(WarningCode, usize, usize)> = None;
+
+    for (i, char) in str.chars().enumerate() {
+        let skipped = match char.to_ascii_lowercase() {
+            'a' => {
+                result.push(A);
+                None
+            }
+            't' => {
+                result.push(T);
+                None
+            }
+            'c' => {
+                result.push(C);
+                None
+            }
+            'g' => {
+                result.push(G);
+                None
+            }
+            c if c.is_whitespace() => continue,
+            'n' | 'r' | 'y' | 'k' | 'm' | 's' | 'w' | 'b' | 'd' | 'h' | 'v' => {
+                Some(WarningCode::AmbiguousNucleotide)
+            }
+            _ => Some(WarningCode::InvalidSymbol),
+        };
+
+        match (&mut run, skipped) {
+            (Some((code, _, len)), Some(skipped)) if *code == skipped => *len += 1,
+            _ => {
+                if let Some(run) = run.take() {
+                    warnings.push(skipped_run_warning(run));
+                }
+                run = skipped.map(|code| (code, i, 1));
+            }
+        }
+    }
+
+    if let Some(run) = run {
+        warnings.push(skipped_run_warning(run));
+    }
+
+    result
+}
+
+fn skipped_run_warning((code, start, len): (WarningCode, usize, usize)) -> Warning {
+    let kind = match code {
+        WarningCode::AmbiguousNucleotide => "ambiguous nucleotide",
+        _ => "invalid symbol",
+    };
+    let plural = if len == 1 { "" } else { "s" };
+
+    Warning::new(code, &format!("Skipped {len} {kind}{plural}"), Some(start))
+}
+
+/// Like `seq_from_str`, but returns an error on characters that aren't nucleotides, instead of
+/// skipping them. Whitespace, including newlines, is skipped. The error includes the offending character,
+/// and its 0-based position in the string.
+pub fn seq_from_str_checked(str: &str) -> Result<Seq, SeqError> {
+    let mut result = Vec::new();
+
+    for (i, char) in str.chars().enumerate() {
+        match char.to_ascii_lowercase() {
+            'a' => result.push(A),
+            't' => result.push(T),
+            'c' => result.push(C),
+            'g' => result.push(G),
+            c if c.is_whitespace() => (),
+            _ => {
+                return Err(SeqError::InvalidSymbol {
+                    symbol: char,
+                    position: i,
+                    alphabet: "nucleotide",
+                })
+            }
+        };
+    }
+
+    Ok(result)
+}
+
+/// Like `seq_aa_from_str`, but returns an error on characters that aren't amino acid identifiers, instead
+/// of skipping them. Whitespace, including newlines, is skipped. The error includes the offending character,
+/// and its 0-based position in the string.
+pub fn seq_aa_from_str_checked(str: &str) -> Result<Vec<AminoAcid>, SeqError> {
+    let mut result = Vec::new();
+
+    for (i, char) in str.chars().enumerate() {
+        if char.is_whitespace() {
+            continue;
+        }
+
+        match char.to_string().parse::<AminoAcid>() {
+            Ok(aa) => result.push(aa),
+            Err(_) => {
+                return Err(SeqError::InvalidSymbol {
+                    symbol: char,
+                    position: i,
+                    alphabet: "amino acid",
+                })
+            }
+        }
+    }
+
+    Ok(result)
+}
+
 /// Convert a nucleotide sequence to string.
 pub fn seq_to_str_lower(seq: &[Nucleotide]) -> String {
     let mut result = String::new();
@@ -85,6 +339,22 @@ pub fn seq_to_str_upper(seq: &[Nucleotide]) -> String {
     result
 }
 
+/// Convert a nucleotide sequence to an RNA string, ie with `U` in place of `T`. (Upper case)
+pub fn dna_to_rna(seq: &[Nucleotide]) -> String {
+    let mut result = String::new();
+
+    for nt in seq {
+        result.push_str(&nt.to_str_upper_rna());
+    }
+
+    result
+}
+
+/// Create a nucleotide sequence from an RNA string, with `U` read as `T`. (Case insensitive)
+pub fn rna_to_dna(str: &str) -> Seq {
+    seq_from_str_kind(str, SeqKind::Rna)
+}
+
 /// Convert an amino acid sequence to string of single-letter idents.
 pub fn seq_aa_to_str(seq: &[AminoAcid]) -> String {
     let mut result = String::new();
@@ -98,7 +368,7 @@ pub fn seq_aa_to_str(seq: &[AminoAcid]) -> String {
 
 /// Convert a sequence to bytes associated with UTF-8 letters. For compatibility with external libraries.
 pub fn seq_to_u8_upper(seq: &[Nucleotide]) -> Vec<u8> {
-    seq.iter().map(|nt| nt.to_u8_upper()).collect()
+    seq_to_letters(seq)
 }
 
 /// Convert a sequence of amino acids to bytes associated with UTF-8 letters. For compatibility with external libraries.
@@ -108,7 +378,7 @@ pub fn seq_to_u8_lower(seq: &[Nucleotide]) -> Vec<u8> {
 
 /// Convert a sequence of amino acids to bytes associated with UTF-8 letters. For compatibility with external libraries.
 pub fn seq_aa_to_u8_upper(seq: &[AminoAcid]) -> Vec<u8> {
-    seq.iter().map(|aa| aa.to_u8_upper()).collect()
+    seq_to_letters(seq)
 }
 
 /// Convert a string to bytes associated with UTF-8 letters. For compatibility with external libraries.
@@ -116,95 +386,484 @@ pub fn seq_aa_to_u8_lower(seq: &[AminoAcid]) -> Vec<u8> {
     seq.iter().map(|aa| aa.to_u8_lower()).collect()
 }
 
-/// Sequence weight, in Daltons. Assumes single-stranded.
+/// Correction to the sum of nucleotide weights for an oligo with a 5' hydroxyl: Removes the 5' phosphate's
+/// HPO2 (63.98), and adds two hydrogens (2.016). (OligoCalc)
+const OLIGO_5P_OH_CORRECTION: f32 = -61.96;
+/// The weight of a 5' phosphate (HPO3), added to an oligo with a 5' hydroxyl.
+const PHOSPHATE_WEIGHT: f32 = 79.98;
+
+/// Sequence weight, in Daltons. Assumes single-stranded, with a 5' hydroxyl, as in synthesized oligos.
 pub fn seq_weight(seq: &[Nucleotide]) -> f32 {
-    let mut result = 0.;
+    oligo_weight(seq, false)
+}
+
+/// Single-stranded sequence weight, in Daltons. Set `phosphorylated` for a 5' phosphate, e.g. from
+/// enzymatic phosphorylation, or a restriction digest. Returns 0 for an empty sequence.
+pub fn oligo_weight(seq: &[Nucleotide], phosphorylated: bool) -> f32 {
+    if seq.is_empty() {
+        return 0.;
+    }
+
+    let mut result: f32 = seq.iter().map(|nt| nt.weight()).sum();
+
+    result += OLIGO_5P_OH_CORRECTION;
+    if phosphorylated {
+        result += PHOSPHATE_WEIGHT;
+    }
+
+    result
+}
+
+/// Atom counts (C, H, N, O, P) of a nucleotide residue in a DNA chain; ie the
+/// deoxynucleoside monophosphate, minus water.
+fn residue_formula(nt: Nucleotide) -> [usize; 5] {
+    match nt {
+        A => [10, 12, 5, 5, 1],
+        T => [10, 13, 2, 7, 1],
+        G => [10, 12, 5, 6, 1],
+        C => [9, 12, 3, 6, 1],
+    }
+}
 
+/// Calculate the molecular formula of a single-stranded DNA sequence. Linear sequences are
+/// treated as having 5' and 3' hydroxyls, so have one fewer phosphate than residues; circular
+/// sequences have one phosphodiester link per residue. This matches `oligo_weight`.
+pub fn molecular_formula(seq: &[Nucleotide], topology: SeqTopology) -> HashMap<Element, usize> {
+    let mut result = HashMap::new();
+    if seq.is_empty() {
+        return result;
+    }
+
+    let mut counts = [0; 5];
     for nt in seq {
-        result += nt.weight();
+        for (count, n) in counts.iter_mut().zip(residue_formula(*nt)) {
+            *count += n;
+        }
     }
 
-    result -= 61.96;
+    if topology == SeqTopology::Linear {
+        // Add H2O to close the ends, and remove the 5' phosphate (HPO3).
+        counts[1] += 2 - 1;
+        counts[3] -= 3 - 1;
+        counts[4] -= 1;
+    }
+
+    let elements = [
+        Element::Carbon,
+        Element::Hydrogen,
+        Element::Nitrogen,
+        Element::Oxygen,
+        Element::Phosphorus,
+    ];
+    for (el, count) in elements.into_iter().zip(counts) {
+        if count > 0 {
+            result.insert(el, count);
+        }
+    }
 
     result
 }
 
-/// Calculate portion of a sequence that is either the G or C nucleotide, on a scale of 0 to 1.
+/// Calculate portion of a sequence that is either the G or C nucleotide, on a scale of 0 to 1. Returns NaN
+/// for an empty sequence; use `calc_gc_checked` to handle this explicitly.
 pub fn calc_gc(seq: &[Nucleotide]) -> f32 {
+    calc_gc_checked(seq).unwrap_or(f32::NAN)
+}
+
+/// Like `calc_gc`, but returns `None` for an empty sequence.
+pub fn calc_gc_checked(seq: &[Nucleotide]) -> Option<f32> {
+    if seq.is_empty() {
+        return None;
+    }
+
     let num_gc = seq.iter().filter(|&&nt| nt == C || nt == G).count();
-    num_gc as f32 / seq.len() as f32
+    // Divide in f64; f32 can't represent counts above 2^24 exactly.
+    Some((num_gc as f64 / seq.len() as f64) as f32)
+}
+
+/// G and C counts, and length, for windows along a sequence. Windows start every `step` nucleotides. If
+/// `include_partial`, the first window that runs past the end is included, truncated; otherwise, only full
+/// windows are. Counts are updated as the window slides, instead of recounting each window.
+fn gc_windows(
+    seq: &[Nucleotide],
+    window: usize,
+    step: usize,
+    include_partial: bool,
+) -> Vec<(usize, usize, usize)> {
+    let mut result = Vec::new();
+    if window == 0 || step == 0 {
+        return result;
+    }
+
+    // Indexed by nucleotide.
+    let mut counts = [0; 4];
+
+    // The range currently counted.
+    let (mut lo, mut hi) = (0, 0);
+    let mut start = 0;
+
+    while start < seq.len() {
+        let end = (start + window).min(seq.len());
+        if end - start < window && !include_partial {
+            break;
+        }
+
+        if start >= hi {
+            // No overlap with the previous window.
+            counts = [0; 4];
+            lo = start;
+            hi = start;
+        }
+        for nt in &seq[lo..start] {
+            counts[*nt as usize] -= 1;
+        }
+        for nt in &seq[hi..end] {
+            counts[*nt as usize] += 1;
+        }
+        (lo, hi) = (start, end);
+
+        let (g, c) = (counts[G as usize], counts[C as usize]);
+        result.push((g, c, end - start));
+
+        if end == seq.len() {
+            break;
+        }
+        start += step;
+    }
+
+    result
+}
+
+/// GC content, on a scale of 0 to 1, of windows along a sequence, starting every `step` nucleotides. If
+/// `include_partial` is true, a final window shorter than `window` covers the tail of the sequence.
+/// Returns an empty Vec if `window` or `step` is 0.
+pub fn gc_content_windowed(
+    seq: &[Nucleotide],
+    window: usize,
+    step: usize,
+    include_partial: bool,
+) -> Vec<f32> {
+    gc_windows(seq, window, step, include_partial)
+        .into_iter()
+        .map(|(g, c, len)| (g + c) as f32 / len as f32)
+        .collect()
+}
+
+/// GC skew, (G - C) / (G + C), of windows along a sequence. Windows are as in `gc_content_windowed`.
+/// Windows without G or C have a skew of 0.
+pub fn gc_skew(seq: &[Nucleotide], window: usize, step: usize, include_partial: bool) -> Vec<f32> {
+    gc_windows(seq, window, step, include_partial)
+        .into_iter()
+        .map(|(g, c, _)| {
+            if g + c == 0 {
+                0.
+            } else {
+                (g as f32 - c as f32) / (g + c) as f32
+            }
+        })
+        .collect()
+}
+
+/// The running sum of `gc_skew`. In bacterial genomes, its minimum approximately locates the origin of
+/// replication, and its maximum the terminus.
+pub fn gc_skew_cumulative(
+    seq: &[Nucleotide],
+    window: usize,
+    step: usize,
+    include_partial: bool,
+) -> Vec<f32> {
+    gc_skew(seq, window, step, include_partial)
+        .into_iter()
+        .scan(0., |sum, skew| {
+            *sum += skew;
+            Some(*sum)
+        })
+        .collect()
+}
+
+/// In the binary format, a 4-byte length with this value indicates that an 8-byte length follows.
+const BIN_LEN_EXTENDED: u32 = u32::MAX;
+
+/// Encode the length header for the binary format.
+fn bin_len_header(len: usize) -> Vec<u8> {
+    match u32::try_from(len) {
+        Ok(len) if len != BIN_LEN_EXTENDED => len.to_be_bytes().to_vec(),
+        _ => {
+            let mut result = BIN_LEN_EXTENDED.to_be_bytes().to_vec();
+            result.extend((len as u64).to_be_bytes());
+            result
+        }
+    }
+}
+
+/// Decode the length header for the binary format. Returns (sequence length, header length).
+fn parse_bin_len_header(data: &[u8]) -> Result<(usize, usize), SeqError> {
+    let truncated = |expected| SeqError::TruncatedData {
+        expected,
+        found: data.len(),
+    };
+
+    let len = u32::from_be_bytes(data.get(0..4).ok_or(truncated(4))?.try_into().unwrap());
+    if len != BIN_LEN_EXTENDED {
+        return Ok((len as usize, 4));
+    }
+
+    let len = u64::from_be_bytes(data.get(4..12).ok_or(truncated(12))?.try_into().unwrap());
+    let len = usize::try_from(len).map_err(|_| SeqError::LengthOverflow(len))?;
+
+    Ok((len, 12))
 }
 
 /// A compact binary serialization of our sequence. Useful for file storage.
 /// The first four bytes is sequence length, big endian; we need this, since one of our nucleotides necessarily serializes
-/// to 0b00.
+/// to 0b00. Sequences of `u32::MAX` nucleotides or longer instead start with four `0xff` bytes, followed by
+/// an eight-byte, big endian length. Shorter sequences use the four-byte form, as in earlier versions.
 ///
-/// MSB. Nucleotides are right-to-left in a given byte. Example: A byte containing
-/// nucleotides TCAG is `0b1110_0100`.
+/// Each following byte holds four nucleotides, using their 2-bit u8 repr. Within a byte, nucleotides are
+/// packed starting from the least significant bits: Nucleotide `i` is at bits `2 * (i % 4)` and
+/// `2 * (i % 4) + 1`, of byte `i / 4`. Example: A byte containing nucleotides TCAG is `0b1110_0100`.
+/// Unused bits in the final byte are 0. A sequence of length `n` serializes to `4 + ceil(n / 4)` bytes.
 pub fn serialize_seq_bin(seq: &[Nucleotide]) -> Vec<u8> {
-    let mut result = Vec::new();
-    result.extend(&(seq.len() as u32).to_be_bytes());
+    let mut result = Vec::with_capacity(12 + seq.len().div_ceil(4));
+    // Writing to a `Vec` can't fail.
+    write_seq_bin(seq, &mut result).unwrap();
+    result
+}
+
+/// Pack up to four nucleotides into a byte, as described in `serialize_seq_bin`.
+fn pack_bin(chunk: &[Nucleotide]) -> u8 {
+    debug_assert!(chunk.len() <= 4);
+
+    let mut val = 0;
+    for (j, nt) in chunk.iter().enumerate() {
+        val |= (*nt as u8) << (j * 2);
+    }
+    val
+}
+
+/// Nucleotides are packed and unpacked through a buffer of this many bytes, when streaming.
+const BIN_CHUNK_SIZE: usize = 4_096;
+
+/// Write a sequence in the binary format described in `serialize_seq_bin`, in fixed-size chunks, without
+/// building the whole serialization in memory.
+pub fn write_seq_bin(seq: &[Nucleotide], w: &mut impl Write) -> io::Result<()> {
+    w.write_all(&bin_len_header(seq.len()))?;
+
+    let mut buf = Vec::with_capacity(BIN_CHUNK_SIZE);
+    for chunk in seq.chunks(BIN_CHUNK_SIZE * 4) {
+        buf.clear();
+        buf.extend(chunk.chunks(4).map(pack_bin));
+        w.write_all(&buf)?;
+    }
 
-    for i in 0..seq.len() / 4 + 1 {
-        let mut val = 0;
-        for j in 0..4 {
-            let ind = i * 4 + j;
-            if ind + 1 > seq.len() {
+    Ok(())
+}
+
+/// Like `write_seq_bin`, but takes the nucleotides from an iterator, e.g. one decoding another format, so
+/// the sequence never needs to be in memory at once. `len` is written to the header first; returns an
+/// `InvalidInput` error if `seq` doesn't yield exactly that many nucleotides, in which case the written data
+/// is invalid.
+pub fn write_seq_bin_iter(
+    len: usize,
+    seq: impl IntoIterator<Item = Nucleotide>,
+    w: &mut impl Write,
+) -> io::Result<()> {
+    w.write_all(&bin_len_header(len))?;
+
+    let mut seq = seq.into_iter().fuse();
+    let mut count: usize = 0;
+    let mut buf = Vec::with_capacity(BIN_CHUNK_SIZE);
+
+    loop {
+        buf.clear();
+        while buf.len() < BIN_CHUNK_SIZE {
+            let mut chunk = [T; 4];
+            let n = chunk
+                .iter_mut()
+                .zip(&mut seq)
+                .map(|(c, nt)| *c = nt)
+                .count();
+            if n == 0 {
                 break;
             }
-            let nt = seq[ind];
-            val |= (nt as u8) << (j * 2);
+            buf.push(pack_bin(&chunk[..n]));
+            count += n;
         }
-        result.push(val);
-    }
-    result
-}
 
-/// A compact binary deserialization of our sequence. Useful for file storage.
-/// The first four bytes is sequence length, big endian; we need this, since one of our nucleotides necessarily serializes
-/// to 0b00.
-pub fn deser_seq_bin(data: &[u8]) -> io::Result<Seq> {
-    let mut result = Vec::new();
+        if buf.is_empty() {
+            break;
+        }
+        w.write_all(&buf)?;
+    }
 
-    if data.len() < 4 {
+    if count != len {
         return Err(io::Error::new(
-            ErrorKind::InvalidData,
-            "Bin nucleotide sequence is too short.",
+            ErrorKind::InvalidInput,
+            format!("Expected {len} nucleotides, but the sequence had {count}"),
         ));
     }
 
-    let seq_len = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+    Ok(())
+}
+
+/// Read a sequence in the binary format described in `serialize_seq_bin`. Reads only as far as the
+/// sequence's end, so further data, e.g. another sequence, can follow in the same reader.
+pub fn read_seq_bin(r: &mut impl Read) -> io::Result<Seq> {
+    let iter = SeqBinIter::new(r)?;
+
+    // Don't trust the header's length for the allocation, in case the data is corrupt.
+    let mut result = Vec::with_capacity(iter.size_hint().0.min(BIN_CHUNK_SIZE * 4));
+    for nt in iter {
+        result.push(nt?);
+    }
+
+    Ok(result)
+}
+
+/// Decodes nucleotides from the binary format described in `serialize_seq_bin` lazily, reading from the
+/// underlying reader in fixed-size chunks. Yields an `UnexpectedEof` error if the data ends before the
+/// sequence does, then stops.
+pub struct SeqBinIter<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    /// The byte in `buf` holding the next nucleotide, and the nucleotide's position within it.
+    buf_pos: usize,
+    nt_pos: usize,
+    /// Nucleotides not yet yielded.
+    remaining: usize,
+    /// Bytes not yet read from `reader`.
+    bytes_unread: usize,
+}
+
+impl<R: Read> SeqBinIter<R> {
+    /// Reads the length header immediately; nucleotides are read as the iterator is advanced.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; 12];
+        reader.read_exact(&mut header[..4])?;
+        let header_len = if header[..4] == BIN_LEN_EXTENDED.to_be_bytes() {
+            reader.read_exact(&mut header[4..])?;
+            12
+        } else {
+            4
+        };
+        let (len, _) = parse_bin_len_header(&header[..header_len])?;
+
+        Ok(Self {
+            reader,
+            buf: Vec::new(),
+            buf_pos: 0,
+            nt_pos: 0,
+            remaining: len,
+            bytes_unread: len.div_ceil(4),
+        })
+    }
+
+    /// Refill the buffer with the next chunk of packed bytes.
+    fn fill_buf(&mut self) -> io::Result<()> {
+        let len = self.bytes_unread.min(BIN_CHUNK_SIZE);
+        self.buf.resize(len, 0);
+        self.reader.read_exact(&mut self.buf)?;
+
+        self.bytes_unread -= len;
+        self.buf_pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for SeqBinIter<R> {
+    type Item = io::Result<Nucleotide>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        if self.buf_pos >= self.buf.len() {
+            if let Err(e) = self.fill_buf() {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+        }
+
+        // Any 2 bits are a valid nucleotide.
+        let bits = (self.buf[self.buf_pos] >> (2 * self.nt_pos)) & 0b11;
+
+        self.nt_pos += 1;
+        if self.nt_pos == 4 {
+            self.nt_pos = 0;
+            self.buf_pos += 1;
+        }
+        self.remaining -= 1;
+
+        Some(Ok(Nucleotide::try_from(bits).unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// A compact binary deserialization of our sequence. Useful for file storage. See `serialize_seq_bin`
+/// for the format. Extra bytes beyond the sequence length are ignored; too few is an error.
+pub fn deser_seq_bin(data: &[u8]) -> Result<Seq, SeqError> {
+    let (seq_len, header_len) = parse_bin_len_header(data)?;
+
+    // `seq_len` fits in a usize, but the header and data length may not.
+    let expected = seq_len
+        .div_ceil(4)
+        .checked_add(header_len)
+        .ok_or(SeqError::LengthOverflow(seq_len as u64))?;
 
-    for byte in &data[4..] {
+    if data.len() < expected {
+        return Err(SeqError::TruncatedData {
+            expected,
+            found: data.len(),
+        });
+    }
+
+    let mut result = Vec::with_capacity(seq_len);
+
+    for byte in &data[header_len..] {
         for i in 0..4 {
             // This trimming removes extra 00-serialized nucleotides.
             if result.len() >= seq_len {
                 break;
             }
 
+            // Any 2 bits are a valid nucleotide.
             let bits = (byte >> (2 * i)) & 0b11;
-            result.push(Nucleotide::try_from(bits).map_err(|_| {
-                io::Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Invalid NT serialization: {}, {}", byte, bits),
-                )
-            })?);
+            debug_assert!(bits <= 0b11);
+            result.push(Nucleotide::try_from(bits).unwrap());
         }
     }
 
     Ok(result)
 }
 
-#[derive(Clone, Copy, PartialEq, Encode, Decode)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SeqTopology {
     Linear,
+    #[default]
     Circular,
 }
 
-impl Default for SeqTopology {
-    fn default() -> Self {
-        Self::Circular
-    }
+/// Whether a sequence is DNA or RNA. Both are stored as `Nucleotide`s, with RNA's `U` represented
+/// as `T`; this only affects parsing and display.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Encode, Decode)]
+pub enum SeqKind {
+    #[default]
+    Dna,
+    Rna,
+}
+
+/// Which strand of a double-stranded sequence something is on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strand {
+    Forward,
+    /// The reverse complement of the forward strand.
+    Reverse,
 }
 
 /// Insert a segment of one sequence into another. For example, for cloning.
@@ -215,8 +874,10 @@ pub fn insert_into_seq(
     insert_loc: usize,
 ) -> Result<(), IndexError> {
     if insert_loc == 0 || insert_loc > seq_vector.len() {
-        eprintln!("Error: Insert location out of bounds: {insert_loc}");
-        return Err(IndexError {});
+        return Err(IndexError {
+            index: insert_loc,
+            len: seq_vector.len(),
+        });
     }
 
     let insert_i = insert_loc - 1; // 1-based indexing.
@@ -224,3 +885,822 @@ pub fn insert_into_seq(
 
     Ok(())
 }
+
+/// Extract a subsequence. `start` and `end` are 1-based and inclusive, as in `insert_into_seq`. For circular
+/// sequences, `start > end` wraps across the origin, and `start == end` returns the whole sequence, starting
+/// at `start`; e.g. for a feature that covers the whole plasmid. (Use a linear topology to extract a single
+/// nucleotide.) Returns an error if either index is outside `1..=seq.len()`, or if `start > end` for a
+/// linear sequence.
+pub fn subseq(
+    seq: &[Nucleotide],
+    start: usize,
+    end: usize,
+    topology: SeqTopology,
+) -> Result<Seq, IndexError> {
+    let len = seq.len();
+
+    for index in [start, end] {
+        if index == 0 || index > len {
+            return Err(IndexError { index, len });
+        }
+    }
+
+    match topology {
+        _ if start < end => Ok(seq[start - 1..end].to_vec()),
+        SeqTopology::Linear if start == end => Ok(vec![seq[start - 1]]),
+        SeqTopology::Linear => Err(IndexError { index: start, len }),
+        SeqTopology::Circular => {
+            let mut result = seq[start - 1..].to_vec();
+            // For `start == end`, stop before `start`, so the result is the whole sequence.
+            result.extend_from_slice(&seq[..end.min(start - 1)]);
+            Ok(result)
+        }
+    }
+}
+
+/// Find all occurrences of a motif, in a nucleotide or amino acid sequence. Returns 0-based start indices,
+/// including overlapping matches. Uses Boyer-Moore-Horspool, which skips ahead on mismatches; this is fast
+/// for long sequences.
+pub fn find_motif<T: Alphabet>(seq: &[T], motif: &[T]) -> Vec<usize> {
+    let mut result = Vec::new();
+
+    let m = motif.len();
+    if m == 0 || m > seq.len() {
+        return result;
+    }
+
+    // How far to shift the window, by the symbol aligned with the motif's last position: The distance
+    // from that symbol's last occurrence in the motif (excluding the final position) to the end.
+    let mut shifts = vec![m; T::SIZE];
+    for (i, s) in motif[..m - 1].iter().enumerate() {
+        shifts[s.to_index()] = m - 1 - i;
+    }
+
+    let mut i = 0;
+    while i + m <= seq.len() {
+        if seq[i..i + m] == *motif {
+            result.push(i);
+        }
+        i += shifts[seq[i + m - 1].to_index()];
+    }
+
+    result
+}
+
+/// Find all occurrences of `needle` in `haystack`, using `find_motif`. Returns 0-based start indices. For
+/// circular sequences, matches that span the origin are included, at their start near the end.
+pub fn find_seq<T: Alphabet>(haystack: &[T], needle: &[T], topology: SeqTopology) -> Vec<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+
+    match topology {
+        SeqTopology::Linear => find_motif(haystack, needle),
+        SeqTopology::Circular => {
+            // Append the start, so matches can run past the origin.
+            let mut wrapped = haystack.to_vec();
+            wrapped.extend_from_slice(&haystack[..needle.len() - 1]);
+
+            find_motif(&wrapped, needle)
+                .into_iter()
+                .filter(|i| *i < haystack.len())
+                .collect()
+        }
+    }
+}
+
+/// Like `find_seq`, but also searches the reverse strand. Indices are 0-based starts of the matching
+/// region on the forward strand, for both strands. A palindromic needle (one that's its own reverse
+/// complement) matches both strands at the same place; these are reported once, as `Forward`.
+pub fn find_seq_both_strands(
+    haystack: &[Nucleotide],
+    needle: &[Nucleotide],
+    topology: SeqTopology,
+) -> Vec<(usize, Strand)> {
+    let needle_rc = seq_complement(needle);
+
+    let mut result: Vec<_> = find_seq(haystack, needle, topology)
+        .into_iter()
+        .map(|i| (i, Strand::Forward))
+        .collect();
+
+    if needle_rc != needle {
+        result.extend(
+            find_seq(haystack, &needle_rc, topology)
+                .into_iter()
+                .map(|i| (i, Strand::Reverse)),
+        );
+    }
+
+    result.sort_by_key(|(i, _)| *i);
+    result
+}
+
+/// Find all occurrences of a motif, which may contain degenerate (IUPAC) symbols. Returns 0-based
+/// start indices, including overlapping matches.
+pub fn find_motif_general(seq: &[Nucleotide], motif: &[NucleotideGeneral]) -> Vec<usize> {
+    if motif.is_empty() || motif.len() > seq.len() {
+        return Vec::new();
+    }
+
+    let masks: Vec<_> = motif.iter().map(|m| m.mask()).collect();
+
+    seq.windows(motif.len())
+        .enumerate()
+        .filter(|(_, window)| {
+            masks
+                .iter()
+                .zip(*window)
+                .all(|(mask, nt)| mask & (1 << *nt as u8) != 0)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Like `find_motif_general`, but also searches the reverse strand. Indices are 0-based starts of the matching
+/// region on the forward strand, for both strands. A palindromic motif is reported on both strands.
+pub fn find_motif_both_strands(
+    seq: &[Nucleotide],
+    motif: &[NucleotideGeneral],
+) -> Vec<(usize, Strand)> {
+    // Searching the forward strand for the motif's reverse complement avoids copying the sequence.
+    let motif_rc: Vec<_> = motif.iter().rev().map(|m| m.complement()).collect();
+
+    let mut result: Vec<_> = find_motif_general(seq, motif)
+        .into_iter()
+        .map(|i| (i, Strand::Forward))
+        .chain(
+            find_motif_general(seq, &motif_rc)
+                .into_iter()
+                .map(|i| (i, Strand::Reverse)),
+        )
+        .collect();
+
+    result.sort_by_key(|(i, _)| *i);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{protein::MassMode, test_utils::Rng};
+
+    #[test]
+    fn molecular_formula_dna() {
+        let formula =
+            |seq, topology| formula_string(&molecular_formula(&seq_from_str(seq), topology));
+
+        // A single residue with 5' and 3' hydroxyls is the nucleoside: deoxyadenosine.
+        assert_eq!(formula("A", SeqTopology::Linear), "C10H13N5O3");
+        assert_eq!(formula("T", SeqTopology::Linear), "C10H14N2O5");
+
+        assert_eq!(formula("ACGT", SeqTopology::Linear), "C39H50N15O22P3");
+        // Circular: one more phosphodiester link, and no water for the ends.
+        assert_eq!(formula("ACGT", SeqTopology::Circular), "C39H49N15O24P4");
+
+        assert!(molecular_formula(&[], SeqTopology::Linear).is_empty());
+    }
+
+    #[test]
+    fn seq_from_str_warnings() {
+        let mut warnings = Vec::new();
+        let seq = seq_from_str_with_warnings("ACNNN\nNNGT x-?A", &mut warnings);
+
+        assert_eq!(seq, seq_from_str("ACGTA"));
+        let codes: Vec<_> = warnings.iter().map(|w| (w.code, w.position)).collect();
+        assert_eq!(
+            codes,
+            [
+                (WarningCode::AmbiguousNucleotide, Some(2)),
+                (WarningCode::InvalidSymbol, Some(11)),
+            ]
+        );
+        assert_eq!(warnings[0].message, "Skipped 5 ambiguous nucleotides");
+        assert_eq!(warnings[1].message, "Skipped 3 invalid symbols");
+
+        // Adjacent runs of different kinds are reported separately.
+        warnings.clear();
+        seq_from_str_with_warnings("ARY!", &mut warnings);
+        assert_eq!(warnings.len(), 2);
+
+        warnings.clear();
+        assert_eq!(
+            seq_from_str_with_warnings("gattaca", &mut warnings).len(),
+            7
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn dna_rna_round_trip() {
+        let seq = seq_from_str("GATTACA");
+
+        assert_eq!(dna_to_rna(&seq), "GAUUACA");
+        assert_eq!(rna_to_dna(&dna_to_rna(&seq)), seq);
+        assert_eq!(rna_to_dna("gauuaca"), seq);
+        assert_eq!(seq_from_str_kind("GAUUACA", SeqKind::Rna), seq);
+
+        // DNA parsing doesn't read U.
+        assert_eq!(seq_from_str("GAUUACA"), seq_from_str("GAACA"));
+        assert!(seq_from_str_checked("GAUUACA").is_err());
+        assert!(seq_from_str_checked("GATTACA").is_ok());
+    }
+
+    #[test]
+    fn from_str_checked() {
+        let err = |symbol, position, alphabet| SeqError::InvalidSymbol {
+            symbol,
+            position,
+            alphabet,
+        };
+
+        assert_eq!(
+            seq_from_str_checked("GAT\ntac a"),
+            Ok(seq_from_str("GATTACA"))
+        );
+        assert_eq!(seq_from_str_checked(""), Ok(Vec::new()));
+        // Positions are 0-based, and count skipped whitespace.
+        assert_eq!(
+            seq_from_str_checked("GATNACA").unwrap_err(),
+            err('N', 3, "nucleotide")
+        );
+        assert_eq!(
+            seq_from_str_checked("GA\nTT x").unwrap_err(),
+            err('x', 6, "nucleotide")
+        );
+        assert_eq!(
+            seq_from_str_checked("UA").unwrap_err(),
+            err('U', 0, "nucleotide")
+        );
+
+        assert_eq!(seq_aa_from_str_checked("mk w"), Ok(seq_aa_from_str("MKW")));
+        assert_eq!(
+            seq_aa_from_str_checked("MKBW").unwrap_err(),
+            err('B', 2, "amino acid")
+        );
+        assert_eq!(
+            seq_aa_from_str_checked("MK*").unwrap_err(),
+            err('*', 2, "amino acid")
+        );
+
+        assert_eq!(
+            seq_from_str_checked("GATNACA").unwrap_err().to_string(),
+            "Invalid nucleotide 'N' at position 3"
+        );
+    }
+
+    #[test]
+    fn seq_bin_len() {
+        let seq = seq_from_str("TCAGGATTACA");
+
+        for len in [0, 1, 3, 4, 5, 8] {
+            let data = serialize_seq_bin(&seq[..len]);
+
+            // No trailing byte when the length is a multiple of 4.
+            assert_eq!(data.len(), 4 + len.div_ceil(4), "{len}");
+            assert_eq!(data[..4], (len as u32).to_be_bytes());
+            assert_eq!(deser_seq_bin(&data).unwrap(), seq[..len]);
+        }
+
+        assert_eq!(serialize_seq_bin(&seq[..4])[4], 0b1110_0100);
+    }
+
+    #[test]
+    fn seq_bin_round_trip() {
+        let mut rng = Rng::new(0xB1_2A_11);
+
+        for len in 0..=100 {
+            for _ in 0..10 {
+                let seq = rng.seq(len);
+                let data = serialize_seq_bin(&seq);
+
+                assert_eq!(data.len(), 4 + len.div_ceil(4));
+                assert_eq!(deser_seq_bin(&data), Ok(seq.clone()));
+                assert_eq!(read_seq_bin(&mut &data[..]).unwrap(), seq);
+            }
+        }
+    }
+
+    #[test]
+    fn seq_bin_old_encoding() {
+        // Earlier versions wrote an extra, empty byte when the length was a multiple of 4.
+        let seq = seq_from_str("GATTACAG");
+        let mut data = serialize_seq_bin(&seq);
+        data.push(0);
+
+        assert_eq!(deser_seq_bin(&data).unwrap(), seq);
+        assert_eq!(read_seq_bin(&mut &data[..]).unwrap(), seq);
+
+        data.truncate(5);
+        assert!(matches!(
+            deser_seq_bin(&data),
+            Err(SeqError::TruncatedData {
+                expected: 6,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn seq_bin_write_iter() {
+        let seq = seq_from_str(&"GATTACA".repeat(5_000));
+
+        let mut data = Vec::new();
+        write_seq_bin_iter(seq.len(), seq.iter().copied(), &mut data).unwrap();
+        assert_eq!(data, serialize_seq_bin(&seq));
+
+        let mut data = Vec::new();
+        write_seq_bin_iter(0, [], &mut data).unwrap();
+        assert_eq!(data, serialize_seq_bin(&[]));
+
+        for len in [6, 8] {
+            let err =
+                write_seq_bin_iter(len, seq[..7].iter().copied(), &mut Vec::new()).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    /// Accepts the first `limit` bytes written, then fails, to stop a writer early.
+    struct LimitedWriter {
+        data: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for LimitedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.limit - self.data.len());
+            if n == 0 {
+                return Err(io::Error::other("Limit reached"));
+            }
+            self.data.extend(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn seq_bin_extended_header() {
+        // Longer than a u32 can describe. The sequence is generated lazily, and writing stops after the
+        // header and first bytes, so it's never in memory.
+        let len = u32::MAX as usize + 5;
+        let seq = [T, C, A, G].into_iter().cycle().take(len);
+
+        let mut w = LimitedWriter {
+            data: Vec::new(),
+            limit: 16,
+        };
+        assert!(write_seq_bin_iter(len, seq, &mut w).is_err());
+
+        assert_eq!(w.data[..4], [0xff; 4]);
+        assert_eq!(w.data[4..12], (len as u64).to_be_bytes());
+        assert_eq!(w.data[12..], [0b1110_0100; 4]);
+        assert_eq!(parse_bin_len_header(&w.data), Ok((len, 12)));
+
+        // Read it back, with the data also generated lazily.
+        let reader = io::Read::chain(&w.data[..12], io::repeat(0b1110_0100));
+        let mut iter = SeqBinIter::new(reader).unwrap();
+        assert_eq!(iter.size_hint(), (len, Some(len)));
+        let start: Vec<_> = iter.by_ref().take(6).map(Result::unwrap).collect();
+        assert_eq!(start, seq_from_str("TCAGTC"));
+        assert_eq!(iter.size_hint().0, len - 6);
+    }
+
+    #[test]
+    fn seq_bin_header_boundaries() {
+        // The largest length with a 4-byte header. `u32::MAX` itself marks the extended header.
+        let len = u32::MAX as usize - 1;
+        assert_eq!(bin_len_header(len), (len as u32).to_be_bytes());
+        assert_eq!(parse_bin_len_header(&bin_len_header(len)), Ok((len, 4)));
+
+        let len = u32::MAX as usize;
+        let header = bin_len_header(len);
+        assert_eq!(header.len(), 12);
+        assert_eq!(parse_bin_len_header(&header), Ok((len, 12)));
+
+        // Extended headers are read for short sequences too.
+        let mut data = BIN_LEN_EXTENDED.to_be_bytes().to_vec();
+        data.extend(3_u64.to_be_bytes());
+        data.push(0b0010_0111);
+        assert_eq!(deser_seq_bin(&data), Ok(seq_from_str("GCA")));
+        assert_eq!(read_seq_bin(&mut &data[..]).unwrap(), seq_from_str("GCA"));
+
+        assert_eq!(
+            parse_bin_len_header(&data[..8]),
+            Err(SeqError::TruncatedData {
+                expected: 12,
+                found: 8
+            })
+        );
+    }
+
+    #[test]
+    fn seq_bin_length_overflow() {
+        // A corrupt length, as large as the header allows. This must not panic, or allocate for it.
+        let mut data = BIN_LEN_EXTENDED.to_be_bytes().to_vec();
+        data.extend(u64::MAX.to_be_bytes());
+        data.extend([0; 4]);
+
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            deser_seq_bin(&data),
+            Err(SeqError::TruncatedData {
+                expected: (u64::MAX / 4 + 1 + 12) as usize,
+                found: 16
+            })
+        );
+        #[cfg(target_pointer_width = "32")]
+        assert_eq!(
+            deser_seq_bin(&data),
+            Err(SeqError::LengthOverflow(u64::MAX))
+        );
+
+        let err = read_seq_bin(&mut &data[..]).unwrap_err();
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        #[cfg(target_pointer_width = "32")]
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn molecular_formula_mass_matches_weight() {
+        let seq = seq_from_str("GATTACAGATTACACCGG");
+        let mass = formula_mass(
+            &molecular_formula(&seq, SeqTopology::Linear),
+            MassMode::Average,
+        );
+
+        assert!((mass - seq_weight(&seq) as f64).abs() < 1.);
+    }
+
+    #[test]
+    fn find_motif_amino_acids() {
+        let seq = seq_aa_from_str("MKVLKVLAKV");
+
+        assert_eq!(find_motif(&seq, &seq_aa_from_str("KV")), vec![1, 4, 8]);
+        assert_eq!(find_motif(&seq, &seq_aa_from_str("W")), Vec::<usize>::new());
+
+        // "VM" only occurs across the origin.
+        let needle = seq_aa_from_str("VM");
+        assert!(find_seq(&seq, &needle, SeqTopology::Linear).is_empty());
+        assert_eq!(find_seq(&seq, &needle, SeqTopology::Circular), vec![9]);
+    }
+
+    #[test]
+    fn insert_into_seq_bounds() {
+        let mut seq = seq_from_str("GATTACA");
+
+        insert_into_seq(&mut seq, &seq_from_str("CC"), 1).unwrap();
+        assert_eq!(seq, seq_from_str("CCGATTACA"));
+        insert_into_seq(&mut seq, &seq_from_str("TT"), 9).unwrap();
+        assert_eq!(seq, seq_from_str("CCGATTACTTA"));
+
+        let mut seq = seq_from_str("GATTACA");
+        let err = insert_into_seq(&mut seq, &seq_from_str("CC"), 0).unwrap_err();
+        assert_eq!(err, IndexError { index: 0, len: 7 });
+
+        let err = insert_into_seq(&mut seq, &seq_from_str("CC"), 8).unwrap_err();
+        assert_eq!(err, IndexError { index: 8, len: 7 });
+        assert_eq!(
+            err.to_string(),
+            "Index 8 is out of range for a sequence of length 7"
+        );
+
+        // Errors leave the sequence unchanged.
+        assert_eq!(seq, seq_from_str("GATTACA"));
+    }
+
+    #[test]
+    fn subseq_ranges() {
+        let seq = seq_from_str("GATTACAGGC");
+
+        for topology in [SeqTopology::Linear, SeqTopology::Circular] {
+            assert_eq!(subseq(&seq, 2, 5, topology), Ok(seq_from_str("ATTA")));
+            assert_eq!(subseq(&seq, 1, 10, topology), Ok(seq.clone()));
+        }
+
+        // Across the origin.
+        assert_eq!(
+            subseq(&seq, 8, 3, SeqTopology::Circular),
+            Ok(seq_from_str("GGCGAT"))
+        );
+        assert_eq!(
+            subseq(&seq, 4, 3, SeqTopology::Circular),
+            Ok(seq_from_str("TACAGGCGAT"))
+        );
+
+        // The whole sequence, for a circular one; a single nucleotide for a linear one.
+        assert_eq!(
+            subseq(&seq, 5, 5, SeqTopology::Circular),
+            Ok(seq_from_str("ACAGGCGATT"))
+        );
+        assert_eq!(subseq(&seq, 1, 1, SeqTopology::Circular), Ok(seq.clone()));
+        assert_eq!(
+            subseq(&seq, 5, 5, SeqTopology::Linear),
+            Ok(seq_from_str("A"))
+        );
+    }
+
+    #[test]
+    fn subseq_out_of_range() {
+        let seq = seq_from_str("GATTACAGGC");
+
+        assert_eq!(
+            subseq(&seq, 8, 3, SeqTopology::Linear),
+            Err(IndexError { index: 8, len: 10 })
+        );
+        for topology in [SeqTopology::Linear, SeqTopology::Circular] {
+            assert_eq!(
+                subseq(&seq, 0, 3, topology),
+                Err(IndexError { index: 0, len: 10 })
+            );
+            assert_eq!(
+                subseq(&seq, 2, 11, topology),
+                Err(IndexError { index: 11, len: 10 })
+            );
+        }
+        assert_eq!(
+            subseq(&[], 1, 1, SeqTopology::Circular),
+            Err(IndexError { index: 1, len: 0 })
+        );
+    }
+
+    #[test]
+    fn gc_content() {
+        assert_eq!(calc_gc_checked(&[]), None);
+        assert!(calc_gc(&[]).is_nan());
+        assert_eq!(seq_weight(&[]), 0.);
+
+        assert_eq!(calc_gc_checked(&[G]), Some(1.));
+        assert_eq!(calc_gc_checked(&[A]), Some(0.));
+        assert_eq!(calc_gc_checked(&seq_from_str("GATTACA")), Some(2. / 7.));
+
+        // Counts past 2^24, which an f32 can't represent exactly.
+        let len = 10_000_000;
+        let num_gc = 3_333_333;
+        let mut seq = vec![C; num_gc];
+        seq.resize(len, T);
+        seq[len - 1] = G;
+
+        let expected = ((num_gc + 1) as f64 / len as f64) as f32;
+        assert_eq!(calc_gc_checked(&seq), Some(expected));
+    }
+
+    #[test]
+    fn find_motif_overlapping() {
+        let seq = seq_from_str("AAAAA");
+        assert_eq!(find_motif(&seq, &seq_from_str("AAA")), vec![0, 1, 2]);
+        assert_eq!(find_motif(&seq, &seq_from_str("AAAAA")), vec![0]);
+        assert!(find_motif(&seq, &seq_from_str("AAAAAA")).is_empty());
+        assert!(find_motif(&seq, &[]).is_empty());
+
+        let seq = seq_from_str("GATATATC");
+        assert_eq!(find_motif(&seq, &seq_from_str("ATA")), vec![1, 3]);
+        assert_eq!(find_motif(&seq, &seq_from_str("TC")), vec![6]);
+
+        // Against a naive search, on random sequences, where the skip table matters.
+        let mut rng = Rng::new(0x5EA2C4);
+        for _ in 0..200 {
+            let seq = rng.seq(300);
+            let motif_len = 1 + rng.below(6);
+            let motif = rng.seq(motif_len);
+            let expected: Vec<_> = (0..=seq.len() - motif.len())
+                .filter(|i| seq[*i..*i + motif.len()] == motif[..])
+                .collect();
+            assert_eq!(find_motif(&seq, &motif), expected);
+        }
+    }
+
+    #[test]
+    fn find_motif_degenerate() {
+        use NucleotideGeneral as Ng;
+
+        // AvaII, GGWCC: W is A or T.
+        let motif = [Ng::G, Ng::G, Ng::W, Ng::C, Ng::C];
+        let seq = seq_from_str("GGACCTTGGTCCAGGCCCGGACC");
+
+        assert_eq!(find_motif_general(&seq, &motif), vec![0, 7, 18]);
+
+        // On the reverse strand, GAWG reads as CWTC.
+        let motif = [Ng::G, Ng::A, Ng::W, Ng::G];
+        let seq = seq_from_str("GATGCCCTTC");
+        assert_eq!(
+            find_motif_both_strands(&seq, &motif),
+            vec![(0, Strand::Forward), (6, Strand::Reverse)]
+        );
+    }
+
+    #[test]
+    fn find_seq_strands() {
+        // TTAG on the forward strand at 0; its reverse complement, CTAA, at 8.
+        let seq = seq_from_str("TTAGCCGGCTAAG");
+        let needle = seq_from_str("TTAG");
+
+        assert_eq!(find_seq(&seq, &needle, SeqTopology::Linear), vec![0]);
+        assert_eq!(
+            find_seq_both_strands(&seq, &needle, SeqTopology::Linear),
+            vec![(0, Strand::Forward), (8, Strand::Reverse)]
+        );
+
+        // CCGG is palindromic; its site is reported once.
+        assert_eq!(
+            find_seq_both_strands(&seq, &seq_from_str("CCGG"), SeqTopology::Linear),
+            vec![(4, Strand::Forward)]
+        );
+
+        assert!(find_seq_both_strands(&seq, &[], SeqTopology::Linear).is_empty());
+    }
+
+    #[test]
+    fn find_seq_across_origin() {
+        let seq = seq_from_str("TACAGGCCGA");
+        // "GATA" runs from index 8, across the origin.
+        let needle = seq_from_str("GATA");
+
+        assert!(find_seq(&seq, &needle, SeqTopology::Linear).is_empty());
+        assert_eq!(find_seq(&seq, &needle, SeqTopology::Circular), vec![8]);
+
+        // Its reverse complement, TATC, also found at 8, on the reverse strand.
+        assert_eq!(
+            find_seq_both_strands(&seq, &seq_from_str("TATC"), SeqTopology::Circular),
+            vec![(8, Strand::Reverse)]
+        );
+
+        // The needle can be as long as the sequence, starting anywhere.
+        assert_eq!(
+            find_seq(&seq, &seq_from_str("GATACAGGCC"), SeqTopology::Circular),
+            vec![8]
+        );
+        assert!(find_seq(&seq, &seq_from_str("GATACAGGCCG"), SeqTopology::Circular).is_empty());
+    }
+
+    #[test]
+    fn gc_windows_count_and_step() {
+        let seq = seq_from_str("GGGCAAAACCC");
+
+        // Windows start at 0, 2, 4 and 6; the one at 8 would run past the end.
+        assert_eq!(
+            gc_content_windowed(&seq, 4, 2, false),
+            vec![1., 0.5, 0., 0.5]
+        );
+        // Included, truncated to 3 nucleotides.
+        assert_eq!(
+            gc_content_windowed(&seq, 4, 2, true),
+            vec![1., 0.5, 0., 0.5, 1.]
+        );
+
+        // Larger than the sequence.
+        assert!(gc_content_windowed(&seq, 20, 5, false).is_empty());
+        assert_eq!(gc_content_windowed(&seq, 20, 5, true), vec![7. / 11.]);
+
+        assert!(gc_content_windowed(&seq, 0, 2, true).is_empty());
+        assert!(gc_skew(&seq, 4, 0, true).is_empty());
+    }
+
+    #[test]
+    fn gc_windows_match_naive() {
+        let mut rng = Rng::new(0x6C5);
+        let seq = rng.seq(500);
+
+        // Overlapping windows, adjacent ones, and ones with gaps between them.
+        for (window, step) in [(50, 7), (20, 20), (10, 33), (1, 1)] {
+            let expected: Vec<_> = (0..seq.len())
+                .step_by(step)
+                .take_while(|start| start + window <= seq.len())
+                .map(|start| calc_gc(&seq[start..start + window]))
+                .collect();
+
+            let windowed = gc_content_windowed(&seq, window, step, false);
+            assert_eq!(windowed.len(), expected.len());
+            for (a, b) in windowed.iter().zip(&expected) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn gc_skew_values() {
+        let seq = seq_from_str("GGGCAAAACCCG");
+
+        // (3 - 1) / 4, no G or C, then (1 - 3) / 4.
+        assert_eq!(gc_skew(&seq, 4, 4, false), vec![0.5, 0., -0.5]);
+        assert_eq!(gc_skew_cumulative(&seq, 4, 4, false), vec![0.5, 0.5, 0.]);
+        assert_eq!(gc_skew(&seq_from_str("GGGGGG"), 3, 3, false), vec![1., 1.]);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use std::fmt::Debug;
+
+    use bincode::config;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::*;
+    use crate::{
+        alphabet::Alphabet, element::Element, re_lib, restriction_enzyme::ReMatch, seq_buf::SeqBuf,
+    };
+
+    /// Round trip through JSON, and through bincode's serde support, which isn't human-readable.
+    fn round_trip<T: Serialize + DeserializeOwned>(val: &T) -> [T; 2] {
+        let json = serde_json::from_str(&serde_json::to_string(val).unwrap()).unwrap();
+
+        let bytes = to_bin(val);
+        let (bin, len) = bincode::serde::decode_from_slice(&bytes, config::standard()).unwrap();
+        assert_eq!(len, bytes.len());
+
+        [json, bin]
+    }
+
+    fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(val: &T) {
+        for result in round_trip(val) {
+            assert_eq!(&result, val);
+        }
+    }
+
+    fn to_bin<T: Serialize>(val: T) -> Vec<u8> {
+        bincode::serde::encode_to_vec(val, config::standard()).unwrap()
+    }
+
+    #[test]
+    fn nucleotides() {
+        for &nt in Nucleotide::SYMBOLS {
+            assert_round_trip(&nt);
+        }
+        for i in 0..=10 {
+            let nt = NucleotideGeneral::try_from(i).unwrap();
+            assert_round_trip(&nt);
+        }
+
+        assert_eq!(serde_json::to_string(&A).unwrap(), "\"A\"");
+        assert_eq!(serde_json::from_str::<Nucleotide>("\"g\"").unwrap(), G);
+        assert_eq!(
+            serde_json::from_str::<NucleotideGeneral>("\"w\"").unwrap(),
+            NucleotideGeneral::W
+        );
+        assert!(serde_json::from_str::<Nucleotide>("\"N\"").is_err());
+        assert!(serde_json::from_str::<Nucleotide>("\"AC\"").is_err());
+
+        // Compact in binary formats: One byte, the u8 repr.
+        assert_eq!(to_bin(A), [2]);
+        assert_eq!(to_bin(NucleotideGeneral::K), [10]);
+    }
+
+    #[test]
+    fn amino_acids() {
+        for &aa in AminoAcid::SYMBOLS {
+            assert_round_trip(&aa);
+        }
+
+        assert_eq!(serde_json::to_string(&AminoAcid::Arg).unwrap(), "\"R\"");
+        assert_eq!(
+            serde_json::from_str::<AminoAcid>("\"w\"").unwrap(),
+            AminoAcid::Trp
+        );
+        assert!(serde_json::from_str::<AminoAcid>("\"B\"").is_err());
+
+        assert_eq!(to_bin(AminoAcid::Arg), [AminoAcid::Arg.to_index() as u8]);
+    }
+
+    #[test]
+    fn other_types() {
+        for el in Element::ALL {
+            assert_round_trip(&el);
+        }
+        for topology in [SeqTopology::Linear, SeqTopology::Circular] {
+            assert_round_trip(&topology);
+        }
+        for strand in [Strand::Forward, Strand::Reverse] {
+            assert_round_trip(&strand);
+        }
+
+        let re_match = ReMatch {
+            lib_index: 3,
+            seq_index: 120,
+            match_count: 2,
+        };
+        assert_round_trip(&re_match);
+
+        // `RestrictionEnzyme`'s `PartialEq` only compares names.
+        for re in ["EcoRI", "BsaI"] {
+            let re = re_lib::find_by_name(re).unwrap();
+            for result in round_trip(re) {
+                assert_eq!(result.name, re.name);
+                assert_eq!(result.cut_seq, re.cut_seq);
+                assert_eq!(result.cut_after, re.cut_after);
+                assert_eq!(result.cut_offset_top, re.cut_offset_top);
+                assert_eq!(result.cut_offset_bottom, re.cut_offset_bottom);
+            }
+        }
+
+        let seq: SeqBuf = "GATTACA".parse().unwrap();
+        assert_round_trip(&seq);
+        // Transparent: A list of letters.
+        assert_eq!(
+            serde_json::to_string(&seq).unwrap(),
+            r#"["G","A","T","T","A","C","A"]"#
+        );
+    }
+}
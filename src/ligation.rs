@@ -2,8 +2,13 @@
 //! combine or otherwise edit DNA segments.
 
 use crate::{
-    restriction_enzyme::{ReMatch, RestrictionEnzyme},
-    Nucleotide, Seq, SeqTopology,
+    re_lib,
+    restriction_enzyme::{
+        self, find_re_matches_both_strands, EndKind, Fragment, FragmentEnd, ReMatch,
+        RestrictionEnzyme,
+    },
+    warnings::{Warning, WarningCode, Warnings},
+    Nucleotide, Seq, SeqTopology, Strand,
 };
 
 pub struct LigationFragment {
@@ -15,7 +20,8 @@ pub struct LigationFragment {
 }
 
 /// Digest the sequence with one or more REs.
-/// `matches` here is all matches; we filter by selected here.
+/// `matches` here is all matches; we filter by selected here. Matches with invalid library indices,
+/// or positions past the end of `seq`, are skipped. Matches don't need to be sorted.
 pub fn digest(
     source_name: &str,
     selected: &[RestrictionEnzyme],
@@ -23,15 +29,40 @@ pub fn digest(
     re_lib: &[RestrictionEnzyme],
     seq: &[Nucleotide],
     topology: SeqTopology,
+) -> Vec<LigationFragment> {
+    digest_with_warnings(
+        source_name,
+        selected,
+        matches,
+        re_lib,
+        seq,
+        topology,
+        &mut Vec::new(),
+    )
+}
+
+/// Like `digest`, but reports skipped matches as warnings.
+pub fn digest_with_warnings(
+    source_name: &str,
+    selected: &[RestrictionEnzyme],
+    matches: &[ReMatch],
+    re_lib: &[RestrictionEnzyme],
+    seq: &[Nucleotide],
+    topology: SeqTopology,
+    warnings: &mut Warnings,
 ) -> Vec<LigationFragment> {
     let mut result = Vec::new();
 
     // Cut index, RE. If from the start or end of the seq, it's none.
     let mut cuts = Vec::new();
 
-    for re_match in matches {
+    for (i, re_match) in matches.iter().enumerate() {
         if re_match.lib_index >= re_lib.len() {
-            eprintln!("Invalid RE selected.");
+            warnings.push(Warning::new(
+                WarningCode::InvalidReIndex,
+                "Invalid RE selected",
+                Some(i),
+            ));
             continue;
         }
         let re = &re_lib[re_match.lib_index];
@@ -40,6 +71,15 @@ pub fn digest(
             continue;
         }
 
+        if re_match.seq_index > seq.len() {
+            warnings.push(Warning::new(
+                WarningCode::MatchOutOfBounds,
+                "RE match is past the end of the sequence",
+                Some(i),
+            ));
+            continue;
+        }
+
         cuts.push((re_match.seq_index, re.clone()));
     }
 
@@ -47,6 +87,8 @@ pub fn digest(
         return result;
     }
 
+    cuts.sort_by_key(|(i, _)| *i);
+
     let mut cut = &cuts[0];
     let mut cuts_i = 0;
     let mut current_fragment = Vec::new();
@@ -112,36 +154,102 @@ pub fn digest(
     result
 }
 
-/// Ligate multiple fragments together, in each combination.
+/// A product of ligating digest fragments.
+#[derive(Clone, Debug)]
+pub struct LigatedSeq {
+    /// The top strand, 5' to 3'. For circular products, this starts at the first fragment.
+    pub seq: Seq,
+    pub topology: SeqTopology,
+    /// The fragments joined, in order, as indices into the input. `Strand::Reverse` means the
+    /// fragment was flipped before joining.
+    pub fragments: Vec<(usize, Strand)>,
+}
+
+/// Whether the right end of one fragment can be ligated to the left end of another. Sticky ends are
+/// compatible if they protrude on the same strand, with the same overhang; since overhangs are in
+/// top-strand sense, this means they're complementary. Blunt ends, including the ends of a linear
+/// sequence, are only compatible if `allow_blunt` is set.
+pub fn ends_compatible(right: &FragmentEnd, left: &FragmentEnd, allow_blunt: bool) -> bool {
+    match (right.kind, left.kind) {
+        (EndKind::Blunt, EndKind::Blunt) => allow_blunt,
+        (a, b) => a == b && right.overhang == left.overhang,
+    }
+}
+
+/// Ligate multiple fragments together, in each combination: Each fragment's right end is joined to the
+/// left end of each other fragment, if the enzymes that cut them produce compatible ends. (See
+/// `re_lib::overhangs_compatible`) Ends of the original sequence, and ends from enzymes whose overhang
+/// depends on the sequence, are never joined. Use `ligate_fragments`, or `ligate_all`, with fragments from
+/// `restriction_enzyme::digest`, to account for the overhangs themselves.
 pub fn ligate(fragments: &[LigationFragment]) -> Vec<Seq> {
     let mut result = Vec::new();
 
-    // todo: Where do you cut off the recursion?
-
-    for frag in fragments {
-        if let Some(re_l) = &frag.re_left {
-            let nt_overhang_a: Vec<_> = re_l
-                .overhang_top_left(&frag.seq[0..1]) // todo: You must update this.
-                .iter()
-                .map(|nt| nt.complement())
-                .collect();
-
-            let nt_overhang_b: Vec<_> = re_l
-                .overhang_top_right(&frag.seq[0..1]) // todo: You must update this.
-                .iter()
-                .map(|nt| nt.complement())
-                .collect();
-
-            // Match complements on the same diagonal: top left-to-top-left
-            for frag_2 in fragments {
-                if let Some(re_2_l) = &frag_2.re_left {
-                    if re_2_l.overhang_top_left(&frag.seq[0..1]) == nt_overhang_a {
-                        // todo: You must update this.
-                        // result.push(&frag.seq.iter().chain(&frag_2.seq.iter()).collect());
-                        let mut ligated = frag.seq.clone();
-                        ligated.extend(frag_2.seq.clone());
-                        result.push(ligated);
-                    }
+    for (i, frag) in fragments.iter().enumerate() {
+        let Some(re_right) = &frag.re_right else {
+            continue;
+        };
+
+        for (j, frag_2) in fragments.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let Some(re_left) = &frag_2.re_left else {
+                continue;
+            };
+
+            if re_lib::overhangs_compatible(re_right, re_left) {
+                let mut ligated = frag.seq.clone();
+                ligated.extend(&frag_2.seq);
+                result.push(ligated);
+            }
+        }
+    }
+
+    result
+}
+
+/// Ligate digest fragments: Each fragment whose ends are compatible with each other is circularized,
+/// and each pair of fragments with compatible ends is joined, in either orientation. A joined pair
+/// whose outer ends are also compatible is reported as a single circular product. Incompatible ends
+/// are never joined; blunt ends are only joined if `allow_blunt` is set. See `ligate_all` for products
+/// of more than two fragments.
+pub fn ligate_fragments(fragments: &[Fragment], allow_blunt: bool) -> Vec<LigatedSeq> {
+    let mut result = Vec::new();
+
+    for (i, frag) in fragments.iter().enumerate() {
+        if ends_compatible(&frag.right, &frag.left, allow_blunt) {
+            result.push(LigatedSeq {
+                seq: frag.strand_top.clone(),
+                topology: SeqTopology::Circular,
+                fragments: vec![(i, Strand::Forward)],
+            });
+        }
+    }
+
+    for (i, frag_a) in fragments.iter().enumerate() {
+        for (j, frag_b) in fragments.iter().enumerate().skip(i + 1) {
+            for strand in [Strand::Forward, Strand::Reverse] {
+                let frag_b = match strand {
+                    Strand::Forward => frag_b.clone(),
+                    Strand::Reverse => frag_b.reverse_complement(),
+                };
+
+                let a_b = ends_compatible(&frag_a.right, &frag_b.left, allow_blunt);
+                let b_a = ends_compatible(&frag_b.right, &frag_a.left, allow_blunt);
+
+                // If both junctions are compatible, the two joins are the same circular product.
+                if a_b {
+                    result.push(join_pair(
+                        (i, Strand::Forward, frag_a),
+                        (j, strand, &frag_b),
+                        b_a,
+                    ));
+                } else if b_a {
+                    result.push(join_pair(
+                        (j, strand, &frag_b),
+                        (i, Strand::Forward, frag_a),
+                        false,
+                    ));
                 }
             }
         }
@@ -150,16 +258,235 @@ pub fn ligate(fragments: &[LigationFragment]) -> Vec<Seq> {
     result
 }
 
+/// Ligate all the fragments into products that contain each exactly once, in either orientation, eg for
+/// a cloning with a vector and several inserts. Products whose outer ends are compatible are circular.
+/// Each product is reported once: The first fragment is always in its forward orientation, and circular
+/// products start with it. Blunt ends are only joined if `allow_blunt` is set.
+///
+/// This tries each order of the fragments, so is intended for a handful of them.
+pub fn ligate_all(fragments: &[Fragment], allow_blunt: bool) -> Vec<LigatedSeq> {
+    let mut result = Vec::new();
+    let mut used = vec![false; fragments.len()];
+    let mut chain = Vec::new();
+
+    for (i, frag) in fragments.iter().enumerate() {
+        for strand in [Strand::Forward, Strand::Reverse] {
+            used[i] = true;
+            chain.push((i, strand, oriented(frag, strand)));
+            ligate_next(fragments, allow_blunt, &mut used, &mut chain, &mut result);
+            chain.pop();
+            used[i] = false;
+        }
+    }
+
+    result
+}
+
+fn oriented(frag: &Fragment, strand: Strand) -> Fragment {
+    match strand {
+        Strand::Forward => frag.clone(),
+        Strand::Reverse => frag.reverse_complement(),
+    }
+}
+
+/// Extend a chain of fragments with each unused, compatible fragment, in either orientation, recording
+/// it once it contains all of them.
+fn ligate_next(
+    fragments: &[Fragment],
+    allow_blunt: bool,
+    used: &mut [bool],
+    chain: &mut Vec<(usize, Strand, Fragment)>,
+    result: &mut Vec<LigatedSeq>,
+) {
+    let last = &chain[chain.len() - 1].2;
+
+    if chain.len() == fragments.len() {
+        // Skip the other orientation of each product, and the other rotations of circular ones.
+        if !chain.iter().any(|c| c.0 == 0 && c.1 == Strand::Forward) {
+            return;
+        }
+        let circular = ends_compatible(&last.right, &chain[0].2.left, allow_blunt);
+        if circular && chain[0].0 != 0 {
+            return;
+        }
+
+        result.push(LigatedSeq {
+            seq: chain.iter().flat_map(|c| c.2.strand_top.clone()).collect(),
+            topology: if circular {
+                SeqTopology::Circular
+            } else {
+                SeqTopology::Linear
+            },
+            fragments: chain.iter().map(|c| (c.0, c.1)).collect(),
+        });
+        return;
+    }
+
+    for (i, frag) in fragments.iter().enumerate() {
+        if used[i] {
+            continue;
+        }
+
+        for strand in [Strand::Forward, Strand::Reverse] {
+            let frag = oriented(frag, strand);
+            if ends_compatible(&chain[chain.len() - 1].2.right, &frag.left, allow_blunt) {
+                used[i] = true;
+                chain.push((i, strand, frag));
+                ligate_next(fragments, allow_blunt, used, chain, result);
+                chain.pop();
+                used[i] = false;
+            }
+        }
+    }
+}
+
+/// Ligate the right end of `a` to the left end of `b`, if they're compatible. Blunt ends ligate to any
+/// blunt end. The result keeps the left end of `a`, and the right end of `b`; its `start` and `end` are
+/// from `a` and `b` respectively, so are only meaningful if both came from the same sequence.
+#[deprecated(note = "Use `DuplexSeq::ligate_to`; see `DuplexSeq::from` to convert fragments")]
+pub fn ligate_pair(a: &Fragment, b: &Fragment) -> Option<Fragment> {
+    if !ends_compatible(&a.right, &b.left, true) {
+        return None;
+    }
+
+    // Overhangs are included in whichever strand protrudes, so the strands join without overlap.
+    let mut strand_top = a.strand_top.clone();
+    strand_top.extend(&b.strand_top);
+
+    let mut strand_bottom = b.strand_bottom.clone();
+    strand_bottom.extend(&a.strand_bottom);
+
+    Some(Fragment {
+        strand_top,
+        strand_bottom,
+        start: a.start,
+        end: b.end,
+        left: a.left.clone(),
+        right: b.right.clone(),
+    })
+}
+
+/// Simulate a Golden Gate assembly: Digest each part with a Type IIS enzyme, keep the fragments that no
+/// longer contain its recognition site, and join them by their overhangs. `topology` applies to each part;
+/// eg circular for parts in plasmids, or linear for PCR products. Returns the top strand of each circular
+/// product that contains one fragment from every part, starting with the first part in its forward
+/// orientation; a well-designed assembly produces exactly one. Circles that leave out a part aren't
+/// returned.
+pub fn golden_gate_assemble(
+    parts: &[Seq],
+    enzyme: &RestrictionEnzyme,
+    topology: SeqTopology,
+) -> Vec<Seq> {
+    let lib = std::slice::from_ref(enzyme);
+
+    // (part index, fragment)
+    let mut fragments = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        for frag in restriction_enzyme::digest(part, lib, topology) {
+            let sticky = frag.left.kind != EndKind::Blunt && frag.right.kind != EndKind::Blunt;
+            let site_left =
+                !find_re_matches_both_strands(&frag.strand_top, lib, SeqTopology::Linear)
+                    .is_empty();
+            if sticky && !site_left {
+                fragments.push((i, frag));
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    // By part, rather than fragment, so each part is used once.
+    let mut used = vec![false; parts.len()];
+
+    for (part, frag) in &fragments {
+        if *part != 0 {
+            continue;
+        }
+        used[0] = true;
+        let mut chain = vec![frag.clone()];
+        assemble_next(&fragments, &mut used, &mut chain, &mut result);
+        used[0] = false;
+    }
+
+    result
+}
+
+/// Extend a Golden Gate chain with a compatible fragment from each unused part, in either orientation,
+/// recording it once every part is used, if it can close into a circle.
+fn assemble_next(
+    fragments: &[(usize, Fragment)],
+    used: &mut [bool],
+    chain: &mut Vec<Fragment>,
+    result: &mut Vec<Seq>,
+) {
+    if used.iter().all(|u| *u) {
+        if ends_compatible(&chain[chain.len() - 1].right, &chain[0].left, false) {
+            result.push(chain.iter().flat_map(|f| f.strand_top.clone()).collect());
+        }
+        return;
+    }
+
+    for (part, frag) in fragments {
+        if used[*part] {
+            continue;
+        }
+
+        for frag in [frag.clone(), frag.reverse_complement()] {
+            if ends_compatible(&chain[chain.len() - 1].right, &frag.left, false) {
+                used[*part] = true;
+                chain.push(frag);
+                assemble_next(fragments, used, chain, result);
+                chain.pop();
+                used[*part] = false;
+            }
+        }
+    }
+}
+
+fn join_pair(
+    first: (usize, Strand, &Fragment),
+    second: (usize, Strand, &Fragment),
+    circular: bool,
+) -> LigatedSeq {
+    let mut seq = first.2.strand_top.clone();
+    seq.extend(&second.2.strand_top);
+
+    LigatedSeq {
+        seq,
+        topology: if circular {
+            SeqTopology::Circular
+        } else {
+            SeqTopology::Linear
+        },
+        fragments: vec![(first.0, first.1), (second.0, second.1)],
+    }
+}
+
+/// Find the restriction enzymes that match any of the sequences. Matches with invalid library indices
+/// are skipped.
 pub fn find_common_res<'a>(
     re_match_set: &[&Vec<ReMatch>], // By tab
     lib: &'a [RestrictionEnzyme],
     sticky_ends_only: bool,
+) -> Vec<&'a RestrictionEnzyme> {
+    find_common_res_with_warnings(re_match_set, lib, sticky_ends_only, &mut Vec::new())
+}
+
+/// Like `find_common_res`, but reports skipped matches as warnings.
+pub fn find_common_res_with_warnings<'a>(
+    re_match_set: &[&Vec<ReMatch>], // By tab
+    lib: &'a [RestrictionEnzyme],
+    sticky_ends_only: bool,
+    warnings: &mut Warnings,
 ) -> Vec<&'a RestrictionEnzyme> {
     let mut result = Vec::new();
     for re_matches in re_match_set {
-        for re_match in *re_matches {
+        for (i, re_match) in re_matches.iter().enumerate() {
             if re_match.lib_index >= lib.len() {
-                eprintln!("Invalid restriction enzyme");
+                warnings.push(Warning::new(
+                    WarningCode::InvalidReIndex,
+                    "Invalid restriction enzyme",
+                    Some(i),
+                ));
                 continue;
             }
             let re = &lib[re_match.lib_index];
@@ -177,7 +504,8 @@ pub fn find_common_res<'a>(
     result
 }
 
-/// Filter restriction enzymes to ones that appear in at least two sequences.
+/// Filter restriction enzymes to ones that appear in at least two sequences. Matches with invalid
+/// library indices are ignored.
 pub fn filter_multiple_seqs<'a>(
     res: &'a mut Vec<&RestrictionEnzyme>,
     re_match_set: &[&Vec<ReMatch>], // By tab
@@ -196,7 +524,9 @@ pub fn filter_multiple_seqs<'a>(
         //     for re_match in &volatile[*active].restriction_enzyme_matches {
         for re_matches in re_match_set {
             for re_match in *re_matches {
-                let re_this = &lib[re_match.lib_index];
+                let Some(re_this) = lib.get(re_match.lib_index) else {
+                    continue;
+                };
                 if re_this == re {
                     count += 1;
                     break;
@@ -207,7 +537,8 @@ pub fn filter_multiple_seqs<'a>(
     });
 }
 
-/// Filter restriction enzymes to ones that are unique cutters on all the given sequences.
+/// Filter restriction enzymes to ones that are unique cutters on all the given sequences. Matches with
+/// invalid library indices are ignored.
 pub fn filter_unique_cutters<'a>(
     res: &'a mut Vec<&RestrictionEnzyme>,
     re_match_set: &[&Vec<ReMatch>], // By tab
@@ -218,7 +549,9 @@ pub fn filter_unique_cutters<'a>(
             let mut count = 0; // Note when we reset this count.
             for re_match in *re_matches {
                 // for re_match in &volatile[*active].restriction_enzyme_matches {
-                let re_this = &lib[re_match.lib_index];
+                let Some(re_this) = lib.get(re_match.lib_index) else {
+                    continue;
+                };
                 if re_this == re {
                     count += 1;
                 }
@@ -234,3 +567,286 @@ pub fn filter_unique_cutters<'a>(
         true
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_complement, seq_from_str};
+
+    fn empty_fragment() -> Fragment {
+        let end = FragmentEnd {
+            kind: EndKind::Blunt,
+            overhang: Vec::new(),
+            enzyme: None,
+        };
+
+        Fragment {
+            strand_top: Vec::new(),
+            strand_bottom: Vec::new(),
+            start: 1,
+            end: 0,
+            left: end.clone(),
+            right: end,
+        }
+    }
+
+    // Regression inputs for the no-panic contract.
+
+    #[test]
+    #[allow(deprecated)]
+    fn ligate_empty() {
+        assert!(ligate(&[]).is_empty());
+        assert!(ligate_fragments(&[], true).is_empty());
+        assert!(ligate_all(&[], true).is_empty());
+
+        let frags = [empty_fragment(), empty_fragment()];
+        for product in ligate_fragments(&frags, true)
+            .into_iter()
+            .chain(ligate_all(&frags, true))
+        {
+            assert!(product.seq.is_empty());
+        }
+        assert!(ligate_pair(&frags[0], &frags[1])
+            .unwrap()
+            .strand_top
+            .is_empty());
+    }
+
+    #[test]
+    fn digest_bad_matches() {
+        let lib = crate::re_lib::common_enzymes();
+        let matches = [
+            ReMatch {
+                lib_index: lib.len(),
+                seq_index: 1,
+                match_count: 1,
+            },
+            ReMatch {
+                lib_index: 0,
+                seq_index: 100,
+                match_count: 1,
+            },
+        ];
+
+        let mut warnings = Vec::new();
+        let frags = digest_with_warnings(
+            "",
+            &lib,
+            &matches,
+            &lib,
+            &[],
+            SeqTopology::Circular,
+            &mut warnings,
+        );
+
+        assert!(frags.is_empty());
+        let codes: Vec<_> = warnings.iter().map(|w| (w.code, w.position)).collect();
+        assert_eq!(
+            codes,
+            [
+                (WarningCode::InvalidReIndex, Some(0)),
+                (WarningCode::MatchOutOfBounds, Some(1))
+            ]
+        );
+
+        // The plain version skips the same matches, silently.
+        assert!(digest("", &lib, &matches, &lib, &[], SeqTopology::Circular).is_empty());
+    }
+
+    #[test]
+    fn find_common_res_bad_match() {
+        let lib = crate::re_lib::common_enzymes();
+        let matches = vec![
+            ReMatch {
+                lib_index: 0,
+                seq_index: 1,
+                match_count: 1,
+            },
+            ReMatch {
+                lib_index: lib.len(),
+                seq_index: 5,
+                match_count: 1,
+            },
+        ];
+
+        let mut warnings = Vec::new();
+        let res = find_common_res_with_warnings(&[&matches], &lib, false, &mut warnings);
+
+        assert_eq!(res, [&lib[0]]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, WarningCode::InvalidReIndex);
+        assert_eq!(warnings[0].position, Some(1));
+        assert_eq!(find_common_res(&[&matches], &lib, false), res);
+    }
+
+    fn enzymes(names: &[&str]) -> Vec<RestrictionEnzyme> {
+        names
+            .iter()
+            .map(|name| re_lib::find_by_name(name).unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn ligate_by_enzyme() {
+        let seq = seq_from_str("AAAGAATTCCCCGAATTCGGGGCTGCAGTTT");
+        let lib = enzymes(&["EcoRI", "PstI"]);
+        let matches = restriction_enzyme::find_re_matches(&seq, &lib);
+        let frags = digest("", &lib, &matches, &lib, &seq, SeqTopology::Linear);
+        assert_eq!(frags.len(), 4);
+
+        // EcoRI ends join 0 to 1, 0 to 2, and 1 to 2; the PstI ends join 2 to 3.
+        let products = ligate(&frags);
+        assert_eq!(products.len(), 4);
+        for product in &products {
+            assert!(frags.iter().any(|a| frags.iter().any(|b| {
+                a.re_right.as_ref().map(|re| &re.name) == b.re_left.as_ref().map(|re| &re.name)
+                    && *product == [a.seq.clone(), b.seq.clone()].concat()
+            })));
+        }
+
+        // Different enzymes that leave the same overhang: BamHI (G^GATCC) and BglII (A^GATCT).
+        let frag = |seq: &str, re_left: Option<&str>, re_right: Option<&str>| LigationFragment {
+            source_name: String::new(),
+            seq: seq_from_str(seq),
+            re_left: re_left.map(|name| re_lib::find_by_name(name).unwrap().clone()),
+            re_right: re_right.map(|name| re_lib::find_by_name(name).unwrap().clone()),
+        };
+        let frags = [
+            frag("AAAG", None, Some("BamHI")),
+            frag("GATCTCCC", Some("BglII"), None),
+            frag("AATTCGGG", Some("EcoRI"), None),
+            frag("CTGCAGG", Some("BsaI"), None),
+        ];
+        assert_eq!(ligate(&frags), [seq_from_str("AAAGGATCTCCC")]);
+    }
+
+    #[test]
+    fn ligate_fragments_pairs() {
+        // An EcoRI insert, and a vector linearized with EcoRI.
+        let lib = enzymes(&["EcoRI"]);
+        let vector = seq_from_str("GAATTCAAAAAAAA");
+        let insert = seq_from_str("CCGAATTCGGGGTTTGAATTCCC");
+        let mut frags = restriction_enzyme::digest(&vector, &lib, SeqTopology::Circular);
+        frags.push(restriction_enzyme::digest(&insert, &lib, SeqTopology::Linear)[1].clone());
+
+        let products = ligate_fragments(&frags, false);
+        let circular = products
+            .iter()
+            .filter(|p| p.topology == SeqTopology::Circular)
+            .count();
+        // Each fragment self-ligates, and the insert goes in either way round.
+        assert_eq!(circular, 4);
+        assert!(products.iter().all(|p| !p.seq.is_empty()));
+    }
+
+    #[test]
+    fn ligate_all_insert_orientations() {
+        let lib = enzymes(&["EcoRI"]);
+        let vector = seq_from_str("GAATTCAAAAAAAA");
+        let insert = seq_from_str("CCGAATTCGGGGTTTGAATTCCC");
+
+        let mut frags = restriction_enzyme::digest(&vector, &lib, SeqTopology::Circular);
+        assert_eq!(frags.len(), 1);
+        frags.push(restriction_enzyme::digest(&insert, &lib, SeqTopology::Linear)[1].clone());
+
+        let products = ligate_all(&frags, false);
+        assert_eq!(products.len(), 2);
+        for (product, strand) in products.iter().zip([Strand::Forward, Strand::Reverse]) {
+            assert_eq!(product.topology, SeqTopology::Circular);
+            assert_eq!(product.fragments, [(0, Strand::Forward), (1, strand)]);
+            assert_eq!(product.seq.len(), vector.len() + 13);
+        }
+    }
+
+    #[test]
+    fn ligate_all_three_fragments() {
+        // EcoRI (G^AATTC) and BamHI (G^GATCC) cut this into 3 fragments, with different overhangs.
+        let seq = seq_from_str("AAAAGAATTCCCCCGGATCCTTTT");
+        let lib = enzymes(&["EcoRI", "BamHI"]);
+        let frags = restriction_enzyme::digest(&seq, &lib, SeqTopology::Linear);
+        assert_eq!(frags.len(), 3);
+
+        // The shuffled fragments only go back together one way.
+        let shuffled = [
+            frags[1].clone(),
+            frags[2].reverse_complement(),
+            frags[0].clone(),
+        ];
+        let products = ligate_all(&shuffled, false);
+        assert_eq!(products.len(), 1);
+        assert_eq!(products[0].topology, SeqTopology::Linear);
+        assert_eq!(products[0].seq, seq);
+        assert_eq!(
+            products[0].fragments,
+            [
+                (2, Strand::Forward),
+                (0, Strand::Forward),
+                (1, Strand::Reverse)
+            ]
+        );
+
+        // With blunt ends allowed, the sequence's own ends join, so the product closes into a circle.
+        let products = ligate_all(&frags, true);
+        assert_eq!(products.len(), 1);
+        assert_eq!(products[0].topology, SeqTopology::Circular);
+        assert_eq!(products[0].seq, seq);
+
+        // Missing the middle fragment, nothing joins.
+        assert!(ligate_all(&[frags[0].clone(), frags[2].clone()], false).is_empty());
+    }
+
+    #[test]
+    fn golden_gate_three_parts() {
+        let bsai = re_lib::find_by_name("BsaI").unwrap().clone();
+
+        // Each part is a PCR product: BsaI sites facing inward, around an insert flanked by designed
+        // overhangs. The overhangs chain AATG -> GCTT -> CGAA -> AATG.
+        let part = |left: &str, insert: &str, right: &str| {
+            seq_from_str(&format!("TTGGTCTCA{left}{insert}{right}TGAGACCTT"))
+        };
+        let a = part("AATG", "CCACCTAC", "GCTT");
+        let b = part("GCTT", "TACCAC", "CGAA");
+        let c = part("CGAA", "CATCCA", "AATG");
+
+        let products = golden_gate_assemble(
+            &[a.clone(), b.clone(), c.clone()],
+            &bsai,
+            SeqTopology::Linear,
+        );
+        assert_eq!(products.len(), 1);
+        assert_eq!(
+            products[0],
+            seq_from_str("AATGCCACCTACGCTTTACCACCGAACATCCA")
+        );
+
+        // Part order doesn't matter, aside from the first.
+        let products = golden_gate_assemble(
+            &[a.clone(), c.clone(), b.clone()],
+            &bsai,
+            SeqTopology::Linear,
+        );
+        assert_eq!(
+            products,
+            vec![seq_from_str("AATGCCACCTACGCTTTACCACCGAACATCCA")]
+        );
+
+        // A part with an insert in the reverse orientation is flipped to fit.
+        let b_rc = seq_complement(&b);
+        let products =
+            golden_gate_assemble(&[a.clone(), b_rc, c.clone()], &bsai, SeqTopology::Linear);
+        assert_eq!(
+            products,
+            vec![seq_from_str("AATGCCACCTACGCTTTACCACCGAACATCCA")]
+        );
+
+        // Without part B, nothing closes.
+        assert!(
+            golden_gate_assemble(&[a.clone(), c.clone()], &bsai, SeqTopology::Linear).is_empty()
+        );
+
+        // An alternative to B and C, which would close a circle with A alone. Assemblies must use every
+        // part, so neither circle counts.
+        let d = part("GCTT", "CCTTCC", "AATG");
+        assert!(golden_gate_assemble(&[a, b, c, d], &bsai, SeqTopology::Linear).is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+//! This module contains code for classifying point mutations, both at the nucleotide level, and by
+//! their effect on the encoded protein.
+//!
+//! Positions are 0-based. Reading frames are given as an offset from the start of the sequence to the
+//! first nucleotide of the first codon, as in `Orf::frame`.
+
+use crate::{
+    amino_acids::{AminoAcid, CodingResult},
+    IndexError, Nucleotide,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SubstitutionKind {
+    Identical,
+    /// Purine to purine, or pyrimidine to pyrimidine.
+    Transition,
+    /// Purine to pyrimidine, or vice versa.
+    Transversion,
+}
+
+pub fn classify_substitution(from: Nucleotide, to: Nucleotide) -> SubstitutionKind {
+    if from == to {
+        SubstitutionKind::Identical
+    } else if from.is_purine() == to.is_purine() {
+        SubstitutionKind::Transition
+    } else {
+        SubstitutionKind::Transversion
+    }
+}
+
+/// The effect of a point mutation on the codon containing it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MutationEffect {
+    /// The codon encodes the same amino acid, or stop.
+    Synonymous,
+    Missense {
+        old: AminoAcid,
+        new: AminoAcid,
+    },
+    /// An amino acid codon becomes a stop codon.
+    Nonsense {
+        old: AminoAcid,
+    },
+    /// A stop codon becomes an amino acid codon.
+    StopLost {
+        new: AminoAcid,
+    },
+    /// The position isn't in a complete codon in this reading frame.
+    NonCoding,
+}
+
+#[derive(Clone, Debug)]
+pub struct MutationDescription {
+    /// 0-based.
+    pub pos: usize,
+    pub old: Nucleotide,
+    pub new: Nucleotide,
+    pub substitution: SubstitutionKind,
+    /// 0-based index of the affected codon, counting from the first codon in the frame.
+    pub codon_index: Option<usize>,
+    pub effect: MutationEffect,
+}
+
+/// Describe the effect of replacing the nucleotide at 0-based `pos` with `new`, in the reading frame that
+/// starts `frame` nucleotides into the sequence.
+pub fn describe_mutation(
+    seq: &[Nucleotide],
+    pos: usize,
+    new: Nucleotide,
+    frame: usize,
+) -> Result<MutationDescription, IndexError> {
+    let old = *seq.get(pos).ok_or(IndexError {
+        index: pos,
+        len: seq.len(),
+    })?;
+
+    let mut codon_index = None;
+    let mut effect = MutationEffect::NonCoding;
+
+    if pos >= frame {
+        let i = (pos - frame) / 3;
+        let codon_start = frame + i * 3;
+
+        if codon_start + 3 <= seq.len() {
+            let old_codon: [Nucleotide; 3] = seq[codon_start..codon_start + 3].try_into().unwrap();
+            let mut new_codon = old_codon;
+            new_codon[pos - codon_start] = new;
+
+            codon_index = Some(i);
+            effect = match (
+                AminoAcid::from_codons(old_codon),
+                AminoAcid::from_codons(new_codon),
+            ) {
+                (a, b) if a == b => MutationEffect::Synonymous,
+                (CodingResult::AminoAcid(old), CodingResult::AminoAcid(new)) => {
+                    MutationEffect::Missense { old, new }
+                }
+                (CodingResult::AminoAcid(old), CodingResult::StopCodon) => {
+                    MutationEffect::Nonsense { old }
+                }
+                (CodingResult::StopCodon, CodingResult::AminoAcid(new)) => {
+                    MutationEffect::StopLost { new }
+                }
+                (CodingResult::StopCodon, CodingResult::StopCodon) => unreachable!(),
+            };
+        }
+    }
+
+    Ok(MutationDescription {
+        pos,
+        old,
+        new,
+        substitution: classify_substitution(old, new),
+        codon_index,
+        effect,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_from_str, Nucleotide::*};
+
+    #[test]
+    fn substitutions() {
+        for from in [T, C, A, G] {
+            for to in [T, C, A, G] {
+                let expected = match (from, to) {
+                    _ if from == to => SubstitutionKind::Identical,
+                    (A, G) | (G, A) | (C, T) | (T, C) => SubstitutionKind::Transition,
+                    _ => SubstitutionKind::Transversion,
+                };
+                assert_eq!(classify_substitution(from, to), expected, "{from:?} {to:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn effects() {
+        // In frame 2, the codons are ATG GAA TGG; the leading GG and trailing CC aren't in one.
+        let seq = seq_from_str("GG ATG GAA TGG CC");
+        let describe = |pos, new| describe_mutation(&seq, pos, new, 2).unwrap();
+
+        // GAA to GAG: Both Glu.
+        let desc = describe(7, G);
+        assert_eq!((desc.old, desc.new), (A, G));
+        assert_eq!(desc.substitution, SubstitutionKind::Transition);
+        assert_eq!(desc.codon_index, Some(1));
+        assert_eq!(desc.effect, MutationEffect::Synonymous);
+
+        // ATG to CTG.
+        let desc = describe(2, C);
+        assert_eq!(desc.substitution, SubstitutionKind::Transversion);
+        assert_eq!(desc.codon_index, Some(0));
+        assert_eq!(
+            desc.effect,
+            MutationEffect::Missense {
+                old: AminoAcid::Met,
+                new: AminoAcid::Leu,
+            }
+        );
+
+        // TGG to TAG.
+        let desc = describe(9, A);
+        assert_eq!(desc.codon_index, Some(2));
+        assert_eq!(
+            desc.effect,
+            MutationEffect::Nonsense {
+                old: AminoAcid::Trp
+            }
+        );
+
+        for pos in [0, 1, 11, 12] {
+            let desc = describe(pos, T);
+            assert_eq!(desc.codon_index, None);
+            assert_eq!(desc.effect, MutationEffect::NonCoding);
+        }
+
+        // In frame 0, position 2 is the last nucleotide of GGA; GGC is also Gly.
+        let desc = describe_mutation(&seq, 2, C, 0).unwrap();
+        assert_eq!(desc.codon_index, Some(0));
+        assert_eq!(desc.effect, MutationEffect::Synonymous);
+
+        // TAA to CAA.
+        let desc = describe_mutation(&seq_from_str("ATGTAA"), 3, C, 0).unwrap();
+        assert_eq!(
+            desc.effect,
+            MutationEffect::StopLost {
+                new: AminoAcid::Gln
+            }
+        );
+
+        assert_eq!(
+            describe_mutation(&seq, 13, A, 2).unwrap_err(),
+            IndexError { index: 13, len: 13 }
+        );
+    }
+}
@@ -1,11 +1,11 @@
 //! This module contains types and functions for working with nucleotides.
 
-use std::{io, io::ErrorKind};
-
 use bincode::{Decode, Encode};
 use num_enum::TryFromPrimitive;
 use Nucleotide::*;
 
+use crate::{SeqError, SeqKind};
+
 /// A DNA nucleotide. The u8 repr is for use with a compact binary format.
 /// This is the same nucleotide mapping as [.2bit format](http://genome.ucsc.edu/FAQ/FAQformat.html#format7).
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Encode, Decode, TryFromPrimitive)]
@@ -34,21 +34,24 @@ pub enum Nucleotide {
 
 impl Nucleotide {
     /// E.g. For interop with FASTA, GenBank, and SnapGene formats.
-    pub fn from_u8_letter(val: u8) -> io::Result<Self> {
+    pub fn from_u8_letter(val: u8) -> Result<Self, SeqError> {
         Ok(match val {
             b'A' | b'a' => A,
             b'T' | b't' => T,
             b'G' | b'g' => G,
             b'C' | b'c' => C,
-            _ => {
-                return Err(io::Error::new(
-                    ErrorKind::InvalidData,
-                    "Invalid nucleotide letter",
-                ))
-            }
+            _ => return Err(SeqError::InvalidNucleotide(val)),
         })
     }
 
+    /// Like `from_u8_letter`, but if `kind` is `SeqKind::Rna`, also accepts `U`, which is mapped to `T`.
+    pub fn from_u8_letter_kind(val: u8, kind: SeqKind) -> Result<Self, SeqError> {
+        match (val, kind) {
+            (b'U' | b'u', SeqKind::Rna) => Ok(T),
+            _ => Self::from_u8_letter(val),
+        }
+    }
+
     /// Returns `b'A'` etc. For interop with FASTA, GenBank, and SnapGene formats.
     pub fn to_u8_upper(&self) -> u8 {
         match self {
@@ -87,6 +90,32 @@ impl Nucleotide {
         }
     }
 
+    /// As `to_str_upper`, but displays `T` as `U`.
+    pub fn to_str_upper_rna(&self) -> String {
+        match self {
+            T => "U".to_owned(),
+            _ => self.to_str_upper(),
+        }
+    }
+
+    /// As `to_str_lower`, but displays `T` as `U`.
+    pub fn to_str_lower_rna(&self) -> String {
+        match self {
+            T => "u".to_owned(),
+            _ => self.to_str_lower(),
+        }
+    }
+
+    /// A or G.
+    pub fn is_purine(self) -> bool {
+        matches!(self, A | G)
+    }
+
+    /// C or T.
+    pub fn is_pyrimidine(self) -> bool {
+        matches!(self, C | T)
+    }
+
     pub fn complement(self) -> Self {
         match self {
             A => T,
@@ -134,7 +163,7 @@ impl Nucleotide {
 
 /// This includes both normal nucleotides, and "either" combinations of nucleotides.
 /// The u8 repr is for use with a binary format.
-#[derive(Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, TryFromPrimitive)]
 #[repr(u8)]
 pub enum NucleotideGeneral {
     T = 0,
@@ -180,7 +209,7 @@ pub enum NucleotideGeneral {
 // }
 
 impl NucleotideGeneral {
-    pub fn from_u8_letter(val: u8) -> io::Result<Self> {
+    pub fn from_u8_letter(val: u8) -> Result<Self, SeqError> {
         Ok(match val {
             b'T' | b't' => Self::T,
             b'C' | b'c' => Self::C,
@@ -193,52 +222,71 @@ impl NucleotideGeneral {
             b'R' | b'r' => Self::R,
             b'M' | b'm' => Self::M,
             b'K' | b'k' => Self::K,
-            _ => {
-                return Err(io::Error::new(
-                    ErrorKind::InvalidData,
-                    "Invalid nucleotide letter",
-                ))
-            }
+            _ => return Err(SeqError::InvalidNucleotide(val)),
         })
     }
 
+    /// A 4-bit mask of the nucleotides this symbol matches. Bit `n` is set if the nucleotide with
+    /// u8 repr `n` matches; e.g. `W` (A or T) is `0b0101`.
+    pub const fn mask(self) -> u8 {
+        const T_: u8 = 1 << T as u8;
+        const C_: u8 = 1 << C as u8;
+        const A_: u8 = 1 << A as u8;
+        const G_: u8 = 1 << G as u8;
+
+        match self {
+            Self::T => T_,
+            Self::C => C_,
+            Self::A => A_,
+            Self::G => G_,
+            Self::N => T_ | C_ | A_ | G_,
+            Self::W => A_ | T_,
+            Self::S => C_ | G_,
+            Self::Y => C_ | T_,
+            Self::R => A_ | G_,
+            Self::M => A_ | C_,
+            Self::K => G_ | T_,
+        }
+    }
+
     /// Which nucleotides this symbol matches with.
-    fn nt_matches(&self) -> Vec<Nucleotide> {
+    pub fn nt_matches(&self) -> &'static [Nucleotide] {
         match self {
-            Self::T => vec![T],
-            Self::C => vec![C],
-            Self::A => vec![A],
-            Self::G => vec![G],
-            Self::N => vec![A, C, T, G],
-            Self::W => vec![A, T],
-            Self::S => vec![C, G],
-            Self::Y => vec![C, T],
-            Self::R => vec![A, G],
-            Self::M => vec![A, C],
-            Self::K => vec![T, T],
+            Self::T => &[T],
+            Self::C => &[C],
+            Self::A => &[A],
+            Self::G => &[G],
+            Self::N => &[A, C, T, G],
+            Self::W => &[A, T],
+            Self::S => &[C, G],
+            Self::Y => &[C, T],
+            Self::R => &[A, G],
+            Self::M => &[A, C],
+            Self::K => &[G, T],
         }
     }
 
+    /// Whether this symbol matches a nucleotide.
     pub fn matches(&self, nt: Nucleotide) -> bool {
-        self.nt_matches().contains(&nt)
+        self.mask() & (1 << nt as u8) != 0
     }
 
-    // pub fn from_u8(val: u8) -> io::Result<Self> {
-    //     Ok(match val {
-    //         b'T' | b't' => Self::T,
-    //         b'C' | b'c' => Self::C,
-    //         b'A' | b'a' => Self::A,
-    //         b'G' | b'g' => Self::G,
-    //         b'N' | b'n' => Self::N,
-    //         b'W' | b'w' => Self::W,
-    //         b'S' | b's' => Self::S,
-    //         b'Y' | b'y' => Self::Y,
-    //         b'R' | b'r' => Self::T,
-    //         b'M' | b'm' => Self::M,
-    //         b'K' | b'k' => Self::K,
-    //         _ => return Err(io::Error::new(ErrorKind::InvalidData, "Invalid nucleotide")),
-    //     })
-    // }
+    /// The symbol matching the complements of the nucleotides this one matches.
+    pub fn complement(self) -> Self {
+        match self {
+            Self::T => Self::A,
+            Self::C => Self::G,
+            Self::A => Self::T,
+            Self::G => Self::C,
+            Self::N => Self::N,
+            Self::W => Self::W,
+            Self::S => Self::S,
+            Self::Y => Self::R,
+            Self::R => Self::Y,
+            Self::M => Self::K,
+            Self::K => Self::M,
+        }
+    }
 
     pub fn to_u8_lower(&self) -> u8 {
         match self {
@@ -308,3 +356,153 @@ impl NucleotideGeneral {
         .to_owned()
     }
 }
+
+/// Nucleotides serialize as their upper-case letter, e.g. `"A"`, in human-readable formats like JSON, and
+/// deserialize from either case. In binary formats, they use their u8 repr.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Nucleotide, NucleotideGeneral};
+
+    /// Read a string containing a single letter.
+    fn letter<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        let val = String::deserialize(deserializer)?;
+        match val.as_bytes() {
+            [letter] => Ok(*letter),
+            _ => Err(de::Error::custom(format!(
+                "Expected a single nucleotide letter; got \"{val}\""
+            ))),
+        }
+    }
+
+    impl Serialize for Nucleotide {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.serialize_str(&self.to_str_upper())
+            } else {
+                serializer.serialize_u8(*self as u8)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Nucleotide {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                Self::from_u8_letter(letter(deserializer)?).map_err(de::Error::custom)
+            } else {
+                let val = u8::deserialize(deserializer)?;
+                Self::try_from(val).map_err(de::Error::custom)
+            }
+        }
+    }
+
+    impl Serialize for NucleotideGeneral {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.serialize_str(&self.to_str_upper())
+            } else {
+                serializer.serialize_u8(*self as u8)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for NucleotideGeneral {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                Self::from_u8_letter(letter(deserializer)?).map_err(de::Error::custom)
+            } else {
+                let val = u8::deserialize(deserializer)?;
+                Self::try_from(val).map_err(de::Error::custom)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn general_letters_round_trip() {
+        use NucleotideGeneral as Ng;
+
+        let letters = [
+            (b'T', Ng::T),
+            (b'C', Ng::C),
+            (b'A', Ng::A),
+            (b'G', Ng::G),
+            (b'N', Ng::N),
+            (b'W', Ng::W),
+            (b'S', Ng::S),
+            (b'Y', Ng::Y),
+            (b'R', Ng::R),
+            (b'M', Ng::M),
+            (b'K', Ng::K),
+        ];
+
+        for (letter, nt) in letters {
+            assert_eq!(Ng::from_u8_letter(letter), Ok(nt));
+            assert_eq!(Ng::from_u8_letter(letter.to_ascii_lowercase()), Ok(nt));
+            assert_eq!(nt.to_u8_upper(), letter);
+            assert_eq!(nt.to_u8_lower(), letter.to_ascii_lowercase());
+            assert_eq!(nt.to_str_upper().as_bytes(), [letter]);
+        }
+
+        assert_eq!(
+            Ng::from_u8_letter(b'U'),
+            Err(SeqError::InvalidNucleotide(b'U'))
+        );
+    }
+
+    #[test]
+    fn k_matches_g_and_t() {
+        let k = NucleotideGeneral::K;
+
+        assert_eq!(k.nt_matches(), &[G, T]);
+        assert!(k.matches(G) && k.matches(T));
+        assert!(!k.matches(A) && !k.matches(C));
+    }
+
+    #[test]
+    fn iupac_match_sets() {
+        use NucleotideGeneral as Ng;
+
+        // Each code, and the nucleotides it's defined to match.
+        let codes: [(Ng, &[Nucleotide]); 11] = [
+            (Ng::T, &[T]),
+            (Ng::C, &[C]),
+            (Ng::A, &[A]),
+            (Ng::G, &[G]),
+            (Ng::N, &[A, C, G, T]),
+            (Ng::W, &[A, T]),
+            (Ng::S, &[C, G]),
+            (Ng::Y, &[C, T]),
+            (Ng::R, &[A, G]),
+            (Ng::M, &[A, C]),
+            (Ng::K, &[G, T]),
+        ];
+
+        for (code, expected) in codes {
+            let mut matches = code.nt_matches().to_vec();
+            matches.sort_by_key(|nt| nt.to_u8_upper());
+            assert_eq!(matches, expected, "{code:?}");
+
+            for nt in [T, C, A, G] {
+                let in_set = expected.contains(&nt);
+                assert_eq!(code.matches(nt), in_set, "{code:?} {nt:?}");
+                assert_eq!(
+                    code.mask() & (1 << nt as u8) != 0,
+                    in_set,
+                    "{code:?} {nt:?}"
+                );
+            }
+            assert_eq!(code.mask().count_ones() as usize, expected.len());
+
+            // The complement matches exactly the complements.
+            for nt in [T, C, A, G] {
+                assert_eq!(code.complement().matches(nt.complement()), code.matches(nt));
+            }
+        }
+    }
+}
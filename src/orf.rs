@@ -0,0 +1,214 @@
+//! This module contains code for finding open reading frames (ORFs).
+//!
+//! Coordinates are 1-based and inclusive, consistent with `insert_into_seq`, and are always given
+//! in terms of the forward strand, regardless of which strand the ORF is on.
+
+use std::collections::HashMap;
+
+use crate::{
+    amino_acids::{AminoAcid, CodingResult},
+    seq_complement, Nucleotide,
+    Nucleotide::*,
+    SeqTopology, Strand,
+};
+
+#[derive(Clone, Debug)]
+pub struct OrfOptions {
+    /// The minimum protein length, in codons, excluding the stop codon.
+    pub min_len: usize,
+    /// If true, ORFs start at an ATG codon. If false, they span from one stop codon to the next.
+    pub require_atg: bool,
+    /// If true, scan the reverse complement in addition to the forward strand.
+    pub reverse: bool,
+    /// If circular, ORFs spanning the origin are included.
+    pub topology: SeqTopology,
+}
+
+impl Default for OrfOptions {
+    fn default() -> Self {
+        Self {
+            min_len: 30,
+            require_atg: true,
+            reverse: true,
+            topology: SeqTopology::Linear,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Orf {
+    /// 1-based index of the first nucleotide of the ORF, on the forward strand.
+    pub start: usize,
+    /// 1-based index of the last nucleotide of the ORF (Including the stop codon), on the forward strand.
+    /// If this is less than `start`, the ORF wraps around the origin of a circular sequence.
+    pub end: usize,
+    /// Reading frame offset (0, 1, or 2), from the 5' end of the strand the ORF is on.
+    pub frame: usize,
+    pub strand: Strand,
+    /// The translated protein, from the first codon, up to but not including the stop codon.
+    pub protein: Vec<AminoAcid>,
+}
+
+/// Find ORFs: Spans from a start codon (Or, optionally, any codon following a stop) to the next in-frame
+/// stop codon, in all three frames of the forward strand, and optionally the reverse strand. Only the
+/// longest ORF ending at each stop codon is reported.
+pub fn find_orfs(seq: &[Nucleotide], opts: &OrfOptions) -> Vec<Orf> {
+    let mut result = Vec::new();
+    let len = seq.len();
+
+    if len < 3 {
+        return result;
+    }
+
+    let strands = if opts.reverse {
+        vec![Strand::Forward, Strand::Reverse]
+    } else {
+        vec![Strand::Forward]
+    };
+
+    for strand in strands {
+        let mut strand_seq = match strand {
+            Strand::Forward => seq.to_vec(),
+            Strand::Reverse => seq_complement(seq),
+        };
+
+        if opts.topology == SeqTopology::Circular {
+            // Scanning two copies lets ORFs read through the origin.
+            strand_seq.extend_from_within(..);
+        }
+
+        // Keyed by stop codon position, so nested starts reached via the origin aren't double-reported.
+        let mut by_stop: HashMap<usize, Orf> = HashMap::new();
+
+        for frame in 0..3 {
+            let mut orf_start = None;
+            let mut protein = Vec::new();
+
+            for (i_codon, codon) in strand_seq[frame..].chunks_exact(3).enumerate() {
+                let i = frame + i_codon * 3;
+                let codon = [codon[0], codon[1], codon[2]];
+
+                let coding_result = AminoAcid::from_codons(codon);
+
+                if orf_start.is_none() {
+                    let is_start = if opts.require_atg {
+                        codon == [A, T, G]
+                    } else {
+                        coding_result != CodingResult::StopCodon
+                    };
+
+                    if is_start {
+                        orf_start = Some(i);
+                    } else {
+                        continue;
+                    }
+                }
+
+                match coding_result {
+                    CodingResult::AminoAcid(aa) => protein.push(aa),
+                    CodingResult::StopCodon => {
+                        let start = orf_start.take().unwrap();
+                        let end = i + 3; // Exclusive, in strand coordinates.
+                        let protein = std::mem::take(&mut protein);
+
+                        if start >= len || end - start > len || protein.len() < opts.min_len {
+                            continue;
+                        }
+
+                        // Convert to 0-based, inclusive, forward-strand coordinates.
+                        let (start_fwd, end_fwd) = match strand {
+                            Strand::Forward => (start, (end - 1) % len),
+                            Strand::Reverse => ((2 * len - end) % len, len - 1 - start),
+                        };
+
+                        let orf = Orf {
+                            start: start_fwd + 1,
+                            end: end_fwd + 1,
+                            frame,
+                            strand,
+                            protein,
+                        };
+
+                        let key = (end - 1) % len;
+                        if by_stop
+                            .get(&key)
+                            .is_none_or(|o| o.protein.len() < orf.protein.len())
+                        {
+                            by_stop.insert(key, orf);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut orfs: Vec<_> = by_stop.into_values().collect();
+        orfs.sort_by_key(|o| o.start);
+        result.extend(orfs);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_from_str, AminoAcid::*};
+
+    fn opts(topology: SeqTopology) -> OrfOptions {
+        OrfOptions {
+            min_len: 2,
+            topology,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn circular_spans_origin() {
+        // ATG AAA | AAA TAA, with the origin at the bar.
+        let seq = seq_from_str("AAATAACCCCCCCCCATGAAA");
+
+        let orfs = find_orfs(&seq, &opts(SeqTopology::Circular));
+        let fwd: Vec<_> = orfs
+            .iter()
+            .filter(|o| o.strand == Strand::Forward)
+            .collect();
+
+        assert_eq!(fwd.len(), 1);
+        assert_eq!((fwd[0].start, fwd[0].end), (16, 6));
+        assert_eq!(fwd[0].frame, 0);
+        assert_eq!(fwd[0].protein, vec![Met, Lys, Lys]);
+
+        let orfs = find_orfs(&seq, &opts(SeqTopology::Linear));
+        assert!(orfs.iter().all(|o| o.strand != Strand::Forward));
+    }
+
+    #[test]
+    fn reverse_strand_coords() {
+        // The reverse complement of ATG AAA AAA TAA, at forward positions 3 to 14.
+        let seq = seq_from_str("CCTTATTTTTTCATCC");
+
+        let orfs = find_orfs(&seq, &opts(SeqTopology::Linear));
+
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].strand, Strand::Reverse);
+        assert_eq!((orfs[0].start, orfs[0].end), (3, 14));
+        assert_eq!(orfs[0].frame, 2);
+        assert_eq!(orfs[0].protein, vec![Met, Lys, Lys]);
+    }
+
+    #[test]
+    fn reverse_strand_spans_origin() {
+        // The reverse complement of `circular_spans_origin`'s sequence.
+        let seq = seq_from_str("TTTCATGGGGGGGGGTTATTT");
+
+        let orfs = find_orfs(&seq, &opts(SeqTopology::Circular));
+        let rev: Vec<_> = orfs
+            .iter()
+            .filter(|o| o.strand == Strand::Reverse)
+            .collect();
+
+        assert_eq!(rev.len(), 1);
+        assert_eq!((rev[0].start, rev[0].end), (16, 6));
+        assert_eq!(rev[0].protein, vec![Met, Lys, Lys]);
+    }
+}
@@ -0,0 +1,248 @@
+//! This module contains `PackedSeq`, a nucleotide sequence stored at 2 bits per nucleotide. This uses a
+//! quarter of the memory of `Seq`, eg for whole genomes.
+
+use crate::{Nucleotide, Seq};
+
+/// Each byte's four nucleotides, in reverse order, and complemented.
+static REV_COMP_BYTE: [u8; 256] = {
+    let mut result = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut val = 0;
+        let mut j = 0;
+        while j < 4 {
+            // With this repr (T=0, C=1, A=2, G=3), flipping the high bit complements a nucleotide.
+            let nt = ((i >> (j * 2)) & 0b11) ^ 0b10;
+            val |= nt << ((3 - j) * 2);
+            j += 1;
+        }
+        result[i] = val as u8;
+        i += 1;
+    }
+    result
+};
+
+/// A nucleotide sequence, packed 4 nucleotides per byte. The bytes use the same layout as the body of
+/// `serialize_seq_bin`: Each nucleotide's `u8` repr, starting at the least significant bits. Unused bits
+/// in the last byte are 0.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct PackedSeq {
+    data: Vec<u8>,
+    len: usize,
+}
+
+impl PackedSeq {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty sequence, with room for `capacity` nucleotides.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity.div_ceil(4)),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `None` if `i` is out of bounds.
+    pub fn get(&self, i: usize) -> Option<Nucleotide> {
+        if i >= self.len {
+            return None;
+        }
+
+        let bits = (self.data[i / 4] >> ((i % 4) * 2)) & 0b11;
+        Some(Nucleotide::try_from(bits).unwrap())
+    }
+
+    /// Replace the nucleotide at `i`. Panics if `i` is out of bounds.
+    pub fn set(&mut self, i: usize, nt: Nucleotide) {
+        assert!(
+            i < self.len,
+            "index {i} out of bounds for length {}",
+            self.len
+        );
+
+        let shift = (i % 4) * 2;
+        let byte = &mut self.data[i / 4];
+        *byte = (*byte & !(0b11 << shift)) | ((nt as u8) << shift);
+    }
+
+    pub fn push(&mut self, nt: Nucleotide) {
+        if self.len.is_multiple_of(4) {
+            self.data.push(0);
+        }
+        self.data[self.len / 4] |= (nt as u8) << ((self.len % 4) * 2);
+        self.len += 1;
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Nucleotide> + '_ {
+        (0..self.len).map(|i| self.get(i).unwrap())
+    }
+
+    /// Unpack into a `Seq`.
+    pub fn to_vec(&self) -> Seq {
+        self.iter().collect()
+    }
+
+    /// The reverse complement, computed a byte (4 nucleotides) at a time. This is much faster than
+    /// `seq_complement`, for long sequences.
+    pub fn reverse_complement(&self) -> Self {
+        let rc = |b: &u8| REV_COMP_BYTE[*b as usize];
+
+        // Unused slots at the end of the last byte end up at the start of the first; shift them out.
+        let shift = (4 - self.len % 4) % 4 * 2;
+        let data = if shift == 0 {
+            self.data.iter().rev().map(rc).collect()
+        } else {
+            let mut data: Vec<u8> = self
+                .data
+                .windows(2)
+                .rev()
+                .map(|w| (rc(&w[1]) >> shift) | (rc(&w[0]) << (8 - shift)))
+                .collect();
+            data.push(rc(&self.data[0]) >> shift);
+            data
+        };
+
+        Self {
+            data,
+            len: self.len,
+        }
+    }
+
+    /// The packed bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl From<&[Nucleotide]> for PackedSeq {
+    fn from(seq: &[Nucleotide]) -> Self {
+        seq.iter().copied().collect()
+    }
+}
+
+impl From<&PackedSeq> for Seq {
+    fn from(seq: &PackedSeq) -> Self {
+        seq.to_vec()
+    }
+}
+
+impl FromIterator<Nucleotide> for PackedSeq {
+    fn from_iter<I: IntoIterator<Item = Nucleotide>>(iter: I) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl Extend<Nucleotide> for PackedSeq {
+    fn extend<I: IntoIterator<Item = Nucleotide>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.data.reserve(iter.size_hint().0.div_ceil(4));
+        for nt in iter {
+            self.push(nt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_complement, test_utils::Rng, Nucleotide::*};
+
+    #[test]
+    fn random_access() {
+        let mut rng = Rng::new(0xACE);
+        let mut seq = rng.seq(103);
+        let mut packed = PackedSeq::from(&seq[..]);
+
+        assert_eq!(packed.len(), 103);
+        assert_eq!(packed.as_bytes().len(), 26);
+        for (i, nt) in seq.iter().enumerate() {
+            assert_eq!(packed.get(i), Some(*nt));
+        }
+        assert_eq!(packed.get(103), None);
+
+        for _ in 0..200 {
+            let i = rng.below(seq.len());
+            let nt = rng.seq(1)[0];
+            seq[i] = nt;
+            packed.set(i, nt);
+        }
+        assert_eq!(packed.to_vec(), seq);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_out_of_bounds() {
+        let mut packed = PackedSeq::from(&[A, C][..]);
+        packed.set(2, G);
+    }
+
+    #[test]
+    fn iter_matches_seq() {
+        let mut rng = Rng::new(0xBEEF);
+
+        for len in 0..40 {
+            let seq = rng.seq(len);
+            let packed: PackedSeq = seq.iter().copied().collect();
+
+            assert_eq!(packed.is_empty(), len == 0);
+            assert_eq!(packed.iter().len(), len);
+            assert!(packed.iter().eq(seq.iter().copied()));
+            assert_eq!(Seq::from(&packed), seq);
+        }
+    }
+
+    #[test]
+    fn push_and_extend() {
+        let mut packed = PackedSeq::with_capacity(8);
+        packed.push(G);
+        packed.extend([A, T, T, A, C, A]);
+
+        assert_eq!(packed.to_vec(), vec![G, A, T, T, A, C, A]);
+        // Unused bits in the last byte are 0.
+        assert_eq!(packed.as_bytes()[1] >> 6, 0);
+    }
+
+    #[test]
+    fn reverse_complement_short() {
+        let mut rng = Rng::new(0xD1CE);
+
+        for len in 0..=9 {
+            for _ in 0..20 {
+                let seq = rng.seq(len);
+                let rc = PackedSeq::from(&seq[..]).reverse_complement();
+
+                assert_eq!(rc.len(), len);
+                assert_eq!(rc.to_vec(), seq_complement(&seq));
+                // Unused bits must stay 0, so equality and hashing work.
+                assert_eq!(rc, PackedSeq::from(&seq_complement(&seq)[..]));
+            }
+        }
+    }
+
+    #[test]
+    fn reverse_complement_random() {
+        let mut rng = Rng::new(0xFACE);
+
+        for _ in 0..100 {
+            let len = rng.below(2_000);
+            let seq = rng.seq(len);
+            let packed = PackedSeq::from(&seq[..]);
+            let rc = packed.reverse_complement();
+
+            assert_eq!(rc.to_vec(), seq_complement(&seq));
+            assert_eq!(rc.reverse_complement(), packed);
+        }
+    }
+}
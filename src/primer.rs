@@ -0,0 +1,383 @@
+//! This module contains code for designing PCR primers, and adding restriction site tails to them.
+//!
+//! Positions are 0-based indices on the forward strand, consistent with the `target` range passed to
+//! `design_primers`.
+
+use std::ops::Range;
+
+use crate::{
+    calc_gc, restriction_enzyme::RestrictionEnzyme, seq_complement, Nucleotide, Nucleotide::*, Seq,
+    Strand,
+};
+
+/// Monovalent cation (Na+) concentration used for melting temperature, in mol/L.
+const NA_CONC: f32 = 0.05;
+/// Primer concentration used for melting temperature, in mol/L.
+const PRIMER_CONC: f32 = 250e-9;
+/// Gas constant, in cal/(K·mol).
+const R: f32 = 1.987;
+
+/// The minimum stem length flagged as a hairpin, and the minimum loop size.
+const HAIRPIN_STEM_MIN: usize = 4;
+const HAIRPIN_LOOP_MIN: usize = 3;
+/// Number of 3' bases that, if paired in a self-dimer, may allow extension.
+const DIMER_3P_LEN: usize = 3;
+
+/// Extra 5' bases added ahead of a restriction site tail. Most enzymes cut poorly at sites close to the
+/// end of a fragment; 6 bases is enough for nearly all of them.
+pub const RE_TAIL_PADDING: [Nucleotide; 6] = [G, C, G, T, A, C];
+
+#[derive(Clone, Debug)]
+pub struct PrimerParams {
+    pub len_min: usize,
+    pub len_max: usize,
+    /// Melting temperature range, in °C.
+    pub tm_min: f32,
+    pub tm_max: f32,
+    /// Maximum GC content, on a scale of 0 to 1.
+    pub gc_max: f32,
+    /// Maximum self-complementarity score; see `self_complementarity`.
+    pub self_comp_max: usize,
+    /// Maximum difference in melting temperature between the primers of a pair, in °C.
+    pub tm_diff_max: f32,
+    /// Maximum length of the amplified product.
+    pub product_len_max: usize,
+    /// The maximum number of pairs to return.
+    pub num_return: usize,
+}
+
+impl Default for PrimerParams {
+    fn default() -> Self {
+        Self {
+            len_min: 18,
+            len_max: 25,
+            tm_min: 52.,
+            tm_max: 65.,
+            gc_max: 0.6,
+            self_comp_max: 10,
+            tm_diff_max: 5.,
+            product_len_max: 3_000,
+            num_return: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Primer {
+    /// 5' to 3', as synthesized.
+    pub seq: Seq,
+    /// Index of the first nucleotide of the binding site, on the forward strand.
+    pub start: usize,
+    /// `Forward` primers bind the reverse strand, and extend along the forward one; `Reverse`
+    /// primers are the reverse complement of the forward strand at their site.
+    pub strand: Strand,
+    /// Melting temperature, in °C.
+    pub tm: f32,
+    /// GC content, on a scale of 0 to 1.
+    pub gc: f32,
+    /// The 3' end is G or C.
+    pub gc_clamp: bool,
+    /// The primer contains a stem that may form a hairpin.
+    pub hairpin: bool,
+    /// The 3' end may pair with another copy of the primer.
+    pub self_dimer: bool,
+}
+
+impl Primer {
+    fn new(seq: Seq, start: usize, strand: Strand) -> Self {
+        Self {
+            tm: tm(&seq),
+            gc: calc_gc(&seq),
+            gc_clamp: matches!(seq.last(), Some(G | C)),
+            hairpin: has_hairpin(&seq),
+            self_dimer: has_3p_dimer(&seq),
+            seq,
+            start,
+            strand,
+        }
+    }
+
+    /// The primer sequence, with a recognition site for `re`, and padding, added to its 5' end. Degenerate
+    /// positions in the site use the first nucleotide they match.
+    pub fn with_re_tail(&self, re: &RestrictionEnzyme, padding: &[Nucleotide]) -> Seq {
+        let mut result = padding.to_vec();
+        result.extend(re.cut_seq.iter().map(|nt| nt.nt_matches()[0]));
+        result.extend_from_slice(&self.seq);
+
+        result
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrimerPair {
+    pub forward: Primer,
+    pub reverse: Primer,
+    pub product_len: usize,
+}
+
+/// Nearest-neighbor enthalpy (kcal/mol) and entropy (cal/(K·mol)) for a dinucleotide step, from
+/// SantaLucia, 1998.
+fn nn_params(a: Nucleotide, b: Nucleotide) -> (f32, f32) {
+    match (a, b) {
+        (A, A) | (T, T) => (-7.9, -22.2),
+        (A, T) => (-7.2, -20.4),
+        (T, A) => (-7.2, -21.3),
+        (C, A) | (T, G) => (-8.5, -22.7),
+        (G, T) | (A, C) => (-8.4, -22.4),
+        (C, T) | (A, G) => (-7.8, -21.0),
+        (G, A) | (T, C) => (-8.2, -22.2),
+        (C, G) => (-10.6, -27.2),
+        (G, C) => (-9.8, -24.4),
+        (G, G) | (C, C) => (-8.0, -19.9),
+    }
+}
+
+/// Initiation enthalpy and entropy for a terminal nucleotide.
+fn init_params(nt: Nucleotide) -> (f32, f32) {
+    match nt {
+        G | C => (0.1, -2.8),
+        A | T => (2.3, 4.1),
+    }
+}
+
+/// Total duplex enthalpy (kcal/mol) and entropy (cal/(K·mol)) in 1M NaCl, including initiation. `seq`
+/// must not be empty.
+fn nn_totals(seq: &[Nucleotide]) -> (f32, f32) {
+    let (mut dh, mut ds) = (0., 0.);
+    for nt in [seq[0], seq[seq.len() - 1]] {
+        let (h, s) = init_params(nt);
+        dh += h;
+        ds += s;
+    }
+    for pair in seq.windows(2) {
+        let (h, s) = nn_params(pair[0], pair[1]);
+        dh += h;
+        ds += s;
+    }
+
+    (dh, ds)
+}
+
+/// Calculate melting temperature, in °C, using the nearest-neighbor method (SantaLucia, 1998), with
+/// 50mM Na+ and 250nM primer. Returns NaN for sequences shorter than 2 nucleotides.
+pub fn tm(seq: &[Nucleotide]) -> f32 {
+    if seq.len() < 2 {
+        return f32::NAN;
+    }
+
+    let (dh, mut ds) = nn_totals(seq);
+
+    // Salt correction
+    ds += 0.368 * (seq.len() - 1) as f32 * NA_CONC.ln();
+
+    dh * 1_000. / (ds + R * (PRIMER_CONC / 4.).ln()) - 273.15
+}
+
+/// Score how well a primer pairs with a copy of itself: the highest number of complementary positions,
+/// over all antiparallel alignments.
+pub fn self_complementarity(seq: &[Nucleotide]) -> usize {
+    let rc = seq_complement(seq);
+    let len = seq.len();
+
+    // Slide the reverse complement along the primer; a complementary position is a match here.
+    (0..2 * len)
+        .map(|shift| {
+            (0..len)
+                .filter(|&i| {
+                    let j = i as isize + shift as isize - len as isize;
+                    j >= 0 && (j as usize) < len && seq[i] == rc[j as usize]
+                })
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Check for a stem of at least `HAIRPIN_STEM_MIN` nucleotides that pairs with a later part of the
+/// primer, separated by a loop of at least `HAIRPIN_LOOP_MIN`.
+fn has_hairpin(seq: &[Nucleotide]) -> bool {
+    let k = HAIRPIN_STEM_MIN;
+    if seq.len() < 2 * k + HAIRPIN_LOOP_MIN {
+        return false;
+    }
+
+    for i in 0..=seq.len() - (2 * k + HAIRPIN_LOOP_MIN) {
+        let stem_rc = seq_complement(&seq[i..i + k]);
+        for j in i + k + HAIRPIN_LOOP_MIN..=seq.len() - k {
+            if seq[j..j + k] == stem_rc[..] {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Check if the 3' end of the primer can pair with another copy of itself.
+fn has_3p_dimer(seq: &[Nucleotide]) -> bool {
+    if seq.len() < DIMER_3P_LEN {
+        return false;
+    }
+
+    let end_rc = seq_complement(&seq[seq.len() - DIMER_3P_LEN..]);
+    seq.windows(DIMER_3P_LEN).any(|w| w == &end_rc[..])
+}
+
+fn passes(primer: &Primer, params: &PrimerParams) -> bool {
+    primer.tm >= params.tm_min
+        && primer.tm <= params.tm_max
+        && primer.gc <= params.gc_max
+        && self_complementarity(&primer.seq) <= params.self_comp_max
+}
+
+/// Find primer pairs that amplify a region containing `target`. Forward primers bind upstream of it, and
+/// reverse primers downstream. Returns the best `num_return` pairs, sorted by melting temperature
+/// difference, then product length.
+pub fn design_primers(
+    template: &[Nucleotide],
+    target: Range<usize>,
+    params: PrimerParams,
+) -> Vec<PrimerPair> {
+    if target.start > target.end || target.end > template.len() {
+        return Vec::new();
+    }
+
+    // Limit the search to what can fit in a product.
+    let search_start = target.end.saturating_sub(params.product_len_max);
+    let search_end = (target.start + params.product_len_max).min(template.len());
+
+    let mut forward = Vec::new();
+    let mut reverse = Vec::new();
+
+    for len in params.len_min..=params.len_max {
+        // The forward primer's 3' end is at or before the target start.
+        for start in search_start..=target.start.saturating_sub(len) {
+            if start + len > target.start {
+                break;
+            }
+            let primer = Primer::new(
+                template[start..start + len].to_vec(),
+                start,
+                Strand::Forward,
+            );
+            if passes(&primer, &params) {
+                forward.push(primer);
+            }
+        }
+
+        // The reverse primer's 3' end is at or after the target end.
+        for start in target.end..=search_end.saturating_sub(len) {
+            if start + len > search_end {
+                break;
+            }
+            let seq = seq_complement(&template[start..start + len]);
+            let primer = Primer::new(seq, start, Strand::Reverse);
+            if passes(&primer, &params) {
+                reverse.push(primer);
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (i, fwd) in forward.iter().enumerate() {
+        for (j, rev) in reverse.iter().enumerate() {
+            let product_len = rev.start + rev.seq.len() - fwd.start;
+            let tm_diff = (fwd.tm - rev.tm).abs();
+            if product_len <= params.product_len_max && tm_diff <= params.tm_diff_max {
+                candidates.push((i, j, tm_diff, product_len));
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| a.2.total_cmp(&b.2).then(a.3.cmp(&b.3)));
+
+    candidates
+        .into_iter()
+        .take(params.num_return)
+        .map(|(i, j, _, product_len)| PrimerPair {
+            forward: forward[i].clone(),
+            reverse: reverse[j].clone(),
+            product_len,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{re_lib, seq_from_str, test_utils::Rng};
+
+    #[test]
+    fn tm_santalucia() {
+        // The worked example from SantaLucia, 1998: CGTTGA has ΔG°37 = -5.35 kcal/mol, from the unified
+        // free energy parameters.
+        let (dh, ds) = nn_totals(&seq_from_str("CGTTGA"));
+        assert!((dh - -41.2).abs() < 0.001);
+        assert!((ds - -115.4).abs() < 0.001);
+        assert!((dh - 310.15 * ds / 1_000. - -5.35).abs() < 0.1);
+
+        // With our salt and primer concentrations:
+        // 1000(-41.2) / (-115.4 + 0.368(5)ln(0.05) + 1.987 ln(62.5e-9)) - 273.15 = -5.40
+        assert!((tm(&seq_from_str("CGTTGA")) - -5.40).abs() < 0.01);
+
+        assert!(tm(&seq_from_str("A")).is_nan());
+    }
+
+    #[test]
+    fn primers_flank_target() {
+        let mut rng = Rng::new(0x9C4);
+        let template = rng.seq(1_200);
+        let target = 500..700;
+
+        let pairs = design_primers(&template, target.clone(), PrimerParams::default());
+        assert!(!pairs.is_empty());
+        assert!(pairs.len() <= PrimerParams::default().num_return);
+
+        for pair in &pairs {
+            let (fwd, rev) = (&pair.forward, &pair.reverse);
+
+            assert_eq!(fwd.strand, Strand::Forward);
+            assert!(fwd.start + fwd.seq.len() <= target.start);
+            assert_eq!(fwd.seq, template[fwd.start..fwd.start + fwd.seq.len()]);
+
+            assert_eq!(rev.strand, Strand::Reverse);
+            assert!(rev.start >= target.end);
+            assert_eq!(
+                rev.seq,
+                seq_complement(&template[rev.start..rev.start + rev.seq.len()])
+            );
+
+            assert_eq!(pair.product_len, rev.start + rev.seq.len() - fwd.start);
+            assert!((fwd.tm - rev.tm).abs() <= PrimerParams::default().tm_diff_max);
+        }
+
+        // Sorted by melting temperature difference.
+        assert!(pairs.windows(2).all(|w| {
+            (w[0].forward.tm - w[0].reverse.tm).abs() <= (w[1].forward.tm - w[1].reverse.tm).abs()
+        }));
+
+        assert!(design_primers(&template, 500..1_300, PrimerParams::default()).is_empty());
+    }
+
+    #[test]
+    fn re_tail() {
+        let primer = Primer::new(seq_from_str("ATGCGTACGTTAGCCTAG"), 0, Strand::Forward);
+
+        let ecori = re_lib::find_by_name("EcoRI").unwrap();
+        assert_eq!(
+            primer.with_re_tail(ecori, &RE_TAIL_PADDING),
+            seq_from_str("GCGTAC GAATTC ATGCGTACGTTAGCCTAG")
+        );
+        assert_eq!(
+            primer.with_re_tail(ecori, &[]),
+            seq_from_str("GAATTCATGCGTACGTTAGCCTAG")
+        );
+
+        // Degenerate positions use their first match: Y is C, and R is A.
+        let avai = re_lib::find_by_name("AvaI").unwrap();
+        assert_eq!(
+            primer.with_re_tail(avai, &[]),
+            seq_from_str("CCCGAGATGCGTACGTTAGCCTAG")
+        );
+    }
+}
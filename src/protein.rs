@@ -0,0 +1,530 @@
+//! This module contains functions that operate on protein (amino acid) sequences, such as
+//! motif scanning, signal peptide and cleavage site prediction, charge and isoelectric point, and
+//! UV absorbance.
+
+use crate::{
+    amino_acids::{PkaSet, WATER_WEIGHT_AVG, WATER_WEIGHT_MONO},
+    AminoAcid::{self, *},
+    SeqError,
+};
+
+/// One position of an amino-acid motif.
+#[derive(Clone, PartialEq, Debug)]
+pub enum AaMotifPos {
+    /// `x`: Matches any amino acid.
+    Any,
+    /// `[KR]`: Matches any of the included amino acids.
+    OneOf(Vec<AminoAcid>),
+    /// `{P}`: Matches any amino acid except those included.
+    NoneOf(Vec<AminoAcid>),
+}
+
+impl AaMotifPos {
+    pub fn matches(&self, aa: AminoAcid) -> bool {
+        match self {
+            Self::Any => true,
+            Self::OneOf(aas) => aas.contains(&aa),
+            Self::NoneOf(aas) => !aas.contains(&aa),
+        }
+    }
+}
+
+/// An amino acid motif, e.g. for protease cleavage sites.
+pub type AaMotif = Vec<AaMotifPos>;
+
+/// Parse a motif from a PROSITE-style pattern, e.g. `R-x-[KR]-R`. Supports single letters, `x`,
+/// `[..]` and `{..}` groups, and `(n)` repeat counts. Anchors (`<`, `>`) are not supported.
+pub fn motif_from_prosite(pattern: &str) -> Result<AaMotif, SeqError> {
+    let mut result = Vec::new();
+
+    for element in pattern.trim().trim_end_matches('.').split('-') {
+        let (body, count) = match element.find('(') {
+            Some(i) => {
+                let count = element[i + 1..]
+                    .trim_end_matches(')')
+                    .parse::<usize>()
+                    .map_err(|_| SeqError::InvalidMotif(element.to_owned()))?;
+                (&element[..i], count)
+            }
+            None => (element, 1),
+        };
+
+        let pos = if body.eq_ignore_ascii_case("x") {
+            AaMotifPos::Any
+        } else if body.starts_with('[') && body.ends_with(']') && body.len() > 2 {
+            AaMotifPos::OneOf(aas_from_letters(&body[1..body.len() - 1])?)
+        } else if body.starts_with('{') && body.ends_with('}') && body.len() > 2 {
+            AaMotifPos::NoneOf(aas_from_letters(&body[1..body.len() - 1])?)
+        } else if body.len() == 1 {
+            AaMotifPos::OneOf(aas_from_letters(body)?)
+        } else {
+            return Err(SeqError::InvalidMotif(element.to_owned()));
+        };
+
+        for _ in 0..count {
+            result.push(pos.clone());
+        }
+    }
+
+    Ok(result)
+}
+
+fn aas_from_letters(letters: &str) -> Result<Vec<AminoAcid>, SeqError> {
+    letters.chars().map(|c| c.to_string().parse()).collect()
+}
+
+/// Find all occurrences of a motif in a protein sequence. Returns 0-based start indices,
+/// including overlapping matches.
+pub fn cleavage_motif_scan(seq: &[AminoAcid], motif: &[AaMotifPos]) -> Vec<usize> {
+    if motif.is_empty() || motif.len() > seq.len() {
+        return Vec::new();
+    }
+
+    seq.windows(motif.len())
+        .enumerate()
+        .filter(|(_, window)| window.iter().zip(motif).all(|(aa, pos)| pos.matches(*aa)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Find furin cleavage sites, using the minimal `R-X-[KR]-R` consensus. Returns the 0-based index
+/// of the first residue after each cut; furin cleaves after the final Arg.
+pub fn furin_sites(seq: &[AminoAcid]) -> Vec<usize> {
+    let motif = vec![
+        AaMotifPos::OneOf(vec![Arg]),
+        AaMotifPos::Any,
+        AaMotifPos::OneOf(vec![Lys, Arg]),
+        AaMotifPos::OneOf(vec![Arg]),
+    ];
+
+    cleavage_motif_scan(seq, &motif)
+        .into_iter()
+        .map(|i| i + motif.len())
+        .collect()
+}
+
+/// The result of a signal peptide prediction.
+#[derive(Clone, Debug)]
+pub struct SignalPeptidePrediction {
+    /// The length of the signal peptide; the mature protein starts at this (0-based) index.
+    pub cleavage_index: usize,
+    /// The von Heijne weight matrix score of the cleavage site. Higher is more confident; this is a
+    /// log-odds score, not a probability.
+    pub score: f32,
+}
+
+// Signal peptides are generally 15 - 35 residues long.
+const SP_LEN_MIN: usize = 15;
+const SP_LEN_MAX: usize = 35;
+const H_WINDOW: usize = 8;
+/// Minimum mean Kyte-Doolittle hydropathicity over the H-region window.
+const H_MIN: f32 = 2.0;
+// The C-region, between the H-region and the cleavage site, is generally 3 - 7 residues long.
+const C_REGION_MIN: usize = 3;
+const C_REGION_MAX: usize = 7;
+/// Minimum cleavage site score, using `VON_HEIJNE_EUK`.
+const SITE_SCORE_MIN: f32 = 3.5;
+
+/// The amino acid order of `VON_HEIJNE_EUK`'s rows, by one-letter code.
+const VON_HEIJNE_ORDER: &str = "ACDEFGHIKLMNPQRSTVWY";
+/// The number of sequences `VON_HEIJNE_EUK` was compiled from.
+const VON_HEIJNE_N: f32 = 161.;
+/// Positions of `VON_HEIJNE_EUK`'s columns, relative to the cleavage site: -13 to +2. The site is
+/// between -1 and +1.
+const VON_HEIJNE_START: isize = -13;
+
+/// Residue counts around the signal peptide cleavage sites of 161 eukaryotic proteins, from positions
+/// -13 to +2. Rows are in `VON_HEIJNE_ORDER`. (von Heijne, 1986, Nucleic Acids Research 14:4683; as
+/// distributed with EMBOSS `sigcleave`)
+#[rustfmt::skip]
+static VON_HEIJNE_EUK: [[u8; 15]; 20] = [
+    [16, 13, 14, 15, 20, 18, 18, 17, 25, 15, 47,  6, 80, 18,  6], // A
+    [ 3,  6,  9,  7,  9, 14,  6,  8,  5,  6, 19,  3,  9,  8,  3], // C
+    [ 0,  0,  0,  0,  0,  0,  0,  0,  5,  3,  0,  5,  0, 10, 11], // D
+    [ 0,  0,  0,  1,  0,  0,  0,  0,  3,  7,  0,  7,  0, 13, 14], // E
+    [13,  9, 11, 11,  6,  7, 18, 13,  4,  5,  0, 13,  0,  6,  4], // F
+    [ 4,  4,  3,  6,  3, 13,  3,  2, 19, 34,  5,  7, 39, 10,  7], // G
+    [ 1,  1,  0,  0,  0,  1,  3,  1,  0,  2,  0,  4,  0,  5,  5], // H
+    [15, 15,  8,  6, 11,  5,  4,  8,  5,  1, 10,  5,  0,  8,  7], // I
+    [ 0,  0,  0,  1,  0,  0,  1,  0,  0,  4,  0,  2,  0, 11,  9], // K
+    [71, 68, 72, 79, 78, 45, 64, 49, 10, 23,  8, 20,  1,  8,  4], // L
+    [ 0,  3,  7,  4,  1,  6,  2,  2,  0,  0,  0,  1,  0,  1,  2], // M
+    [ 0,  1,  0,  1,  1,  0,  0,  0,  3,  3,  0, 10,  0,  4,  7], // N
+    [ 2,  0,  2,  0,  0,  4,  1,  8, 20, 14,  0,  1,  3,  0, 22], // P
+    [ 0,  0,  0,  1,  0,  6,  1,  0, 10,  8,  0, 18,  3, 19, 10], // Q
+    [ 2,  0,  0,  0,  0,  1,  0,  0,  7,  4,  0, 15,  0, 12,  9], // R
+    [ 9,  3,  8,  6, 13, 10, 15, 16, 26, 11, 23, 17, 20, 15, 10], // S
+    [ 2, 10,  5,  4,  5, 13,  7,  7, 12,  6, 17,  8,  6,  3, 10], // T
+    [20, 25, 15, 18, 13, 15, 11, 27,  0, 12, 32,  3,  0,  8, 17], // V
+    [ 4,  3,  3,  1,  1,  2,  6,  3,  1,  3,  0,  9,  0,  2,  0], // W
+    [ 0,  1,  4,  0,  0,  1,  3,  1,  1,  2,  0,  5,  0,  1,  7], // Y
+];
+
+/// Background amino acid frequencies, in `VON_HEIJNE_ORDER`, as percentages. (UniProtKB/Swiss-Prot)
+const AA_BACKGROUND: [f32; 20] = [
+    8.25, 1.37, 5.45, 6.75, 3.86, 7.07, 2.27, 5.96, 5.84, 9.66, 2.42, 4.06, 4.70, 3.93, 5.53, 6.56,
+    5.34, 6.87, 1.08, 2.92,
+];
+
+/// The von Heijne weight of an amino acid at a matrix column: ln(observed / expected). Zero counts are
+/// taken as 1, to avoid ln(0). Selenocysteine is weighted as cysteine.
+fn von_heijne_weight(aa: AminoAcid, col: usize) -> f32 {
+    let letter = match aa {
+        Sec => b'C',
+        _ => aa.to_u8_upper(),
+    };
+    let row = VON_HEIJNE_ORDER.bytes().position(|l| l == letter).unwrap();
+
+    let observed = VON_HEIJNE_EUK[row][col].max(1) as f32 / VON_HEIJNE_N;
+    (observed / (AA_BACKGROUND[row] / 100.)).ln()
+}
+
+/// Score a potential signal peptide cleavage site, before the residue at `cleavage`, using von Heijne's
+/// weight matrix. `None` if the matrix doesn't fit in the sequence there.
+pub fn cleavage_site_score(seq: &[AminoAcid], cleavage: usize) -> Option<f32> {
+    let start = cleavage.checked_add_signed(VON_HEIJNE_START)?;
+    let window = seq.get(start..start + VON_HEIJNE_EUK[0].len())?;
+
+    Some(
+        window
+            .iter()
+            .enumerate()
+            .map(|(col, aa)| von_heijne_weight(*aa, col))
+            .sum(),
+    )
+}
+
+/// Side-chain charge at neutral pH.
+fn charge(aa: AminoAcid) -> i32 {
+    match aa {
+        Lys | Arg => 1,
+        Asp | Glu => -1,
+        _ => 0,
+    }
+}
+
+/// Predict an N-terminal secretory signal peptide. Cleavage sites are scored with von Heijne's (-3, -1)
+/// weight matrix (See `cleavage_site_score`), and must be preceded by a positively-charged N-region, and
+/// a hydrophobic H-region (Using `hydropathicity`). Returns the best-scoring site, or `None` if no site
+/// satisfies all three.
+pub fn predict_signal_peptide(seq: &[AminoAcid]) -> Option<SignalPeptidePrediction> {
+    let mut best: Option<SignalPeptidePrediction> = None;
+
+    for cleavage in SP_LEN_MIN..=SP_LEN_MAX {
+        let Some(score) = cleavage_site_score(seq, cleavage) else {
+            break;
+        };
+        if score < SITE_SCORE_MIN {
+            continue;
+        }
+
+        // Find the most hydrophobic window that ends at an acceptable distance from the cleavage site.
+        let mut best_h: Option<(usize, f32)> = None;
+        for c_len in C_REGION_MIN..=C_REGION_MAX {
+            let Some(h_start) = cleavage.checked_sub(c_len + H_WINDOW) else {
+                break;
+            };
+            // Leave room for at least one N-region residue after the initial Met.
+            if h_start < 2 {
+                break;
+            }
+
+            let h: f32 = seq[h_start..h_start + H_WINDOW]
+                .iter()
+                .map(|aa| aa.hydropathicity())
+                .sum::<f32>()
+                / H_WINDOW as f32;
+
+            if best_h.is_none_or(|(_, v)| h > v) {
+                best_h = Some((h_start, h));
+            }
+        }
+
+        let Some((h_start, h)) = best_h else {
+            continue;
+        };
+
+        if h < H_MIN {
+            continue;
+        }
+
+        let n_charge: i32 = seq[..h_start].iter().map(|aa| charge(*aa)).sum();
+        if n_charge < 1 {
+            continue;
+        }
+
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(SignalPeptidePrediction {
+                cleavage_index: cleavage,
+                score,
+            });
+        }
+    }
+
+    best
+}
+
+/// Fraction of a group that's protonated at a given pH. (Henderson-Hasselbalch)
+fn frac_protonated(pka: f32, ph: f32) -> f32 {
+    1. / (1. + 10_f32.powf(ph - pka))
+}
+
+/// The net charge of a protein at a given pH, including its termini. Returns 0 for an empty sequence.
+/// This is the net charge calculation; there's no separate `net_charge` function.
+pub fn charge_at_ph(seq: &[AminoAcid], ph: f32, set: PkaSet) -> f32 {
+    let (Some(first), Some(last)) = (seq.first(), seq.last()) else {
+        return 0.;
+    };
+
+    let mut result =
+        frac_protonated(set.n_term(*first), ph) - (1. - frac_protonated(set.c_term(*last), ph));
+
+    for aa in seq {
+        let Some(pka) = aa.pka(set) else {
+            continue;
+        };
+
+        result += match aa {
+            // Basic side chains are positive when protonated; acidic ones are negative when not.
+            Lys | Arg | His => frac_protonated(pka, ph),
+            _ => -(1. - frac_protonated(pka, ph)),
+        };
+    }
+
+    result
+}
+
+/// The pH at which a protein has no net charge, found by bisection. Returns 0 for an empty sequence.
+pub fn isoelectric_point(seq: &[AminoAcid], set: PkaSet) -> f32 {
+    if seq.is_empty() {
+        return 0.;
+    }
+
+    // Charge decreases monotonically with pH.
+    let (mut low, mut high) = (0., 14.);
+    while high - low > 0.0001 {
+        let mid = (low + high) / 2.;
+        if charge_at_ph(seq, mid, set) > 0. {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    (low + high) / 2.
+}
+
+// Molar extinction coefficients at 280nm, in M^-1 cm^-1. (Pace et al, 1995)
+const EXT_COEFF_TRP: f32 = 5_500.;
+const EXT_COEFF_TYR: f32 = 1_490.;
+/// Per disulfide bond (cystine); free Cys doesn't absorb appreciably.
+const EXT_COEFF_CYSTINE: f32 = 125.;
+
+/// The molar extinction coefficient at 280nm, in M^-1 cm^-1, using the Pace method. If `cystines_paired`
+/// is true, all Cys residues are assumed to form disulfide bonds; otherwise, they're assumed reduced.
+pub fn extinction_coeff_280(seq: &[AminoAcid], cystines_paired: bool) -> f32 {
+    let count = |aa| seq.iter().filter(|s| **s == aa).count() as f32;
+
+    let cystines = if cystines_paired {
+        (seq.iter().filter(|aa| **aa == Cys).count() / 2) as f32
+    } else {
+        0.
+    };
+
+    count(Trp) * EXT_COEFF_TRP + count(Tyr) * EXT_COEFF_TYR + cystines * EXT_COEFF_CYSTINE
+}
+
+/// The absorbance at 280nm of a 1 mg/mL (0.1%) solution, with a 1cm path length. Uses the average
+/// `protein_weight` as molecular weight. Returns 0 for an empty sequence.
+pub fn absorbance_280_01_percent(seq: &[AminoAcid], cystines_paired: bool) -> f32 {
+    if seq.is_empty() {
+        return 0.;
+    }
+
+    extinction_coeff_280(seq, cystines_paired) / protein_weight(seq, MassMode::Average) as f32
+}
+
+/// Whether to use monoisotopic or average masses, e.g. for mass spectrometry, or for solution
+/// concentrations respectively.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MassMode {
+    #[default]
+    Average,
+    /// The mass of each element's most abundant isotope.
+    Monoisotopic,
+}
+
+/// The molecular weight of a peptide or protein, in Da: The sum of its residue masses, plus one water for
+/// the termini. Returns 0 for an empty sequence.
+pub fn protein_weight(seq: &[AminoAcid], mode: MassMode) -> f64 {
+    if seq.is_empty() {
+        return 0.;
+    }
+
+    match mode {
+        MassMode::Average => {
+            seq.iter().map(|aa| aa.residue_weight_avg()).sum::<f64>() + WATER_WEIGHT_AVG
+        }
+        MassMode::Monoisotopic => {
+            seq.iter().map(|aa| aa.residue_weight_mono()).sum::<f64>() + WATER_WEIGHT_MONO
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_aa_from_str;
+
+    /// Hen egg-white lysozyme, mature chain: 6 Trp, 3 Tyr, and 8 Cys.
+    const LYSOZYME: &str = "KVFGRCELAAAMKRHGLDNYRGYSLGNWVCAAKFESNFNTQATNRNTDGSTDYGILQINSRWWCNDGRTPGSRNLCNIPCSALLSSDITASVNCAKKIVSDGNGMNAWVAWRNRCKGTDVQAWIRGCRL";
+
+    #[test]
+    fn prosite() {
+        let motif = motif_from_prosite("R-x(2)-[KR]-{P}.").unwrap();
+        assert_eq!(
+            motif,
+            [
+                AaMotifPos::OneOf(vec![Arg]),
+                AaMotifPos::Any,
+                AaMotifPos::Any,
+                AaMotifPos::OneOf(vec![Lys, Arg]),
+                AaMotifPos::NoneOf(vec![Pro]),
+            ]
+        );
+
+        for (pattern, err) in [
+            ("R-x(n)", SeqError::InvalidMotif("x(n)".to_owned())),
+            ("R-KR", SeqError::InvalidMotif("KR".to_owned())),
+        ] {
+            assert_eq!(motif_from_prosite(pattern), Err(err));
+        }
+        assert!(matches!(
+            motif_from_prosite("R-[KB]"),
+            Err(SeqError::InvalidAminoAcid(_))
+        ));
+    }
+
+    #[test]
+    fn signal_peptides() {
+        // Human precursors, truncated after the first residues of the mature protein. Cleavage sites
+        // are from UniProt.
+        for (seq, cleavage) in [
+            // Serum albumin
+            (
+                "MKWVTFISLLFLFSSAYSRGVFRRDAHKSEVAHRFKDLGEENFKALVLIAFAQYLQ",
+                18,
+            ),
+            // Lysozyme C
+            (
+                "MKALIVLGLVLLSVTVQGKVFERCELARTLKRLGMDGYRGISLANWMCLAKWESGYNT",
+                18,
+            ),
+            // Growth hormone
+            (
+                "MATGSRTSLLLAFGLLCLPWLQEGSAFPTIPLSRLFDNAMLRAHRLHQLAFDTYQEF",
+                26,
+            ),
+            // Interleukin-2
+            (
+                "MYRMQLLSCIALSLALVTNSAPTSSSTKKTQLQLEHLLLDLQMILNGINNYKNPKL",
+                20,
+            ),
+            // Apolipoprotein A-I
+            (
+                "MKAAVLTLAVLFLTGSQARHFWQQDEPPQSPWDRVKDLATVYVDVLKDSGRDYVSQF",
+                18,
+            ),
+        ] {
+            let prediction = predict_signal_peptide(&seq_aa_from_str(seq)).unwrap();
+            assert!(
+                prediction.cleavage_index.abs_diff(cleavage) <= 1,
+                "{seq}: {prediction:?}"
+            );
+            assert!(prediction.score >= SITE_SCORE_MIN);
+        }
+    }
+
+    #[test]
+    fn cytoplasmic_proteins() {
+        for seq in [
+            // Ubiquitin
+            "MQIFVKTLTGKTITLEVEPSDTIENVKAKIQDKEGIPPDQQRLIFAGKQLEDGRTLSDYNIQKESTLHLVLRLRGG",
+            // GAPDH
+            "MGKVKVGVNGFGRIGRLVTRAAFNSGKVDIVAINDPFIDLNYMVYMFQYDSTHGKFHGTVKAENGKLVINGNPITIFQ",
+            // Beta-actin
+            "MDDDIAALVVDNGSGMCKAGFAGDDAPRAVFPSIVGRPRHQGVMVGMGQKDSYVGDEAQSKRGILTLKYPIEHGIVTN",
+            // GFP
+            "MSKGEELFTGVVPILVELDGDVNGHKFSVSGEGEGDATYGKLTLKFICTTGKLPVPWPTLVTTFSYGVQCFSRYPDHMK",
+        ] {
+            assert!(
+                predict_signal_peptide(&seq_aa_from_str(seq)).is_none(),
+                "{seq}"
+            );
+        }
+
+        // Too short for the matrix to fit.
+        assert!(predict_signal_peptide(&seq_aa_from_str("MKWVTFISLLFLFSS")).is_none());
+        assert!(predict_signal_peptide(&[]).is_none());
+    }
+
+    #[test]
+    fn isoelectric_points() {
+        // Theoretical pIs from ExPASy's Compute pI/Mw.
+        for (seq, expected) in [
+            // Human ubiquitin
+            (
+                "MQIFVKTLTGKTITLEVEPSDTIENVKAKIQDKEGIPPDQQRLIFAGKQLEDGRTLSDYNIQKESTLHLVLRLRGG",
+                6.56,
+            ),
+            (LYSOZYME, 9.32),
+        ] {
+            let seq = seq_aa_from_str(seq);
+            let pi = isoelectric_point(&seq, PkaSet::Bjellqvist);
+
+            assert!((pi - expected).abs() < 0.1, "{pi} vs {expected}");
+            assert!(charge_at_ph(&seq, pi, PkaSet::Bjellqvist).abs() < 0.01);
+            assert!(charge_at_ph(&seq, pi - 1., PkaSet::Bjellqvist) > 0.);
+            assert!(charge_at_ph(&seq, pi + 1., PkaSet::Bjellqvist) < 0.);
+        }
+
+        assert_eq!(isoelectric_point(&[], PkaSet::Emboss), 0.);
+        assert_eq!(charge_at_ph(&[], 7., PkaSet::Emboss), 0.);
+    }
+
+    #[test]
+    fn extinction_coeffs() {
+        let seq = seq_aa_from_str(LYSOZYME);
+
+        // ExPASy's ProtParam gives 37,970 with all 4 cystines, and 37,470 reduced; its Abs 0.1% values are
+        // 2.653 and 2.618.
+        assert_eq!(extinction_coeff_280(&seq, true), 37_970.);
+        assert_eq!(extinction_coeff_280(&seq, false), 37_470.);
+        assert!((absorbance_280_01_percent(&seq, true) - 2.653).abs() < 0.001);
+        assert!((absorbance_280_01_percent(&seq, false) - 2.618).abs() < 0.001);
+
+        // An odd Cys is left over.
+        let seq = seq_aa_from_str("WYCCC");
+        assert_eq!(extinction_coeff_280(&seq, true), 5_500. + 1_490. + 125.);
+
+        assert_eq!(extinction_coeff_280(&[], true), 0.);
+        assert_eq!(absorbance_280_01_percent(&[], true), 0.);
+    }
+
+    #[test]
+    fn tripeptide_weight() {
+        // Gly-Ala-Ser is C8H15N3O5. Its masses from elemental ones:
+        // Monoisotopic: 8(12) + 15(1.007825) + 3(14.003074) + 5(15.994915) = 233.10117
+        // Average: 8(12.0107) + 15(1.00794) + 3(14.0067) + 5(15.9994) = 233.2218
+        let seq = seq_aa_from_str("GAS");
+
+        assert!((protein_weight(&seq, MassMode::Monoisotopic) - 233.10117).abs() < 0.001);
+        assert!((protein_weight(&seq, MassMode::Average) - 233.2218).abs() < 0.01);
+
+        // A single residue weighs the same as the free amino acid.
+        let weight = protein_weight(&[Trp], MassMode::Average) as f32;
+        assert!((weight - Trp.weight()).abs() < 0.001);
+
+        assert_eq!(protein_weight(&[], MassMode::Monoisotopic), 0.);
+    }
+}
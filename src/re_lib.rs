@@ -1,71 +1,411 @@
 //! This module loads a library of Restriction enzymes.
 
+use std::{collections::HashMap, sync::OnceLock};
+
 use crate::{
-    nucleotide::NucleotideGeneral::{A, C, G, T},
+    nucleotide::{
+        NucleotideGeneral,
+        NucleotideGeneral::{A, C, G, K, M, N, R, T, W, Y},
+    },
     restriction_enzyme::RestrictionEnzyme,
+    seq_complement, Nucleotide,
 };
 
+static LIBRARY: OnceLock<Vec<RestrictionEnzyme>> = OnceLock::new();
+/// Normalized name to library index.
+static NAME_INDEX: OnceLock<HashMap<String, usize>> = OnceLock::new();
+/// Recognition sequence to library indices.
+static SITE_INDEX: OnceLock<HashMap<Vec<NucleotideGeneral>, Vec<usize>>> = OnceLock::new();
+/// End type and overhang to library indices.
+static END_INDEX: OnceLock<HashMap<EndKey, Vec<usize>>> = OnceLock::new();
+
 /// Load a set of common Restriction enzymes. Call this at program start, to load into a state field.
+/// This is the same set as `common_enzymes`.
 pub fn load_re_library() -> Vec<RestrictionEnzyme> {
+    common_enzymes()
+}
+
+/// A curated set of commonly-used restriction enzymes, in alphabetical order, followed by Type IIS enzymes.
+/// Includes enzymes with degenerate recognition sequences. Very frequent cutters (4-nt sites, like HaeIII
+/// and HhaI) are omitted.
+///
+/// [NEB: Recognition sites and cut positions](https://www.neb.com/en-us/tools-and-resources/selection-charts/alphabetized-list-of-recognition-specificities)
+pub fn common_enzymes() -> Vec<RestrictionEnzyme> {
     vec![
         RestrictionEnzyme::new("AanI", vec![T, T, A, T, A, A], 2),
         RestrictionEnzyme::new("AatI", vec![A, G, G, C, C, T], 2),
         RestrictionEnzyme::new("AatII", vec![G, A, C, G, T, C], 4),
         RestrictionEnzyme::new("AbsI", vec![C, C, T, C, G, A, G, G], 1),
+        RestrictionEnzyme::new("AccI", vec![G, T, M, K, A, C], 1),
         RestrictionEnzyme::new("Acc65I", vec![G, G, T, A, C, C], 0),
+        RestrictionEnzyme::new("AfeI", vec![A, G, C, G, C, T], 2),
         RestrictionEnzyme::new("AflII", vec![C, T, T, A, A, G], 0),
+        RestrictionEnzyme::new("AflIII", vec![A, C, R, Y, G, T], 0),
         RestrictionEnzyme::new("AgeI", vec![A, C, C, G, G, T], 0),
+        RestrictionEnzyme::new("AleI", vec![C, A, C, N, N, N, N, G, T, G], 4),
+        RestrictionEnzyme::new("AlwNI", vec![C, A, G, N, N, N, C, T, G], 5),
         RestrictionEnzyme::new("ApaI", vec![G, G, G, C, C, C], 4),
+        RestrictionEnzyme::new("ApaLI", vec![G, T, G, C, A, C], 0),
         RestrictionEnzyme::new("AscI", vec![G, G, C, G, C, G, C, C], 1),
         RestrictionEnzyme::new("AseI", vec![A, T, T, A, A, T], 1),
         RestrictionEnzyme::new("AsiSI", vec![G, C, G, A, T, C, G, C], 4),
+        RestrictionEnzyme::new("AvaI", vec![C, Y, C, G, R, G], 0),
+        RestrictionEnzyme::new("AvrII", vec![C, C, T, A, G, G], 0),
         RestrictionEnzyme::new("BamHI", vec![G, G, A, T, C, C], 0),
-        RestrictionEnzyme::new("BcII", vec![T, G, A, T, C, A], 0),
-        // RestrictionEnzyme::new("BglI", vec![], 0),
+        RestrictionEnzyme::new("BanII", vec![G, R, G, C, Y, C], 4),
+        RestrictionEnzyme::new("BclI", vec![T, G, A, T, C, A], 0),
+        RestrictionEnzyme::new("BglI", vec![G, C, C, N, N, N, N, N, G, G, C], 6),
         RestrictionEnzyme::new("BglII", vec![A, G, A, T, C, T], 0),
         RestrictionEnzyme::new("BmtI", vec![G, C, T, A, G, C], 4),
-        RestrictionEnzyme::new("BsgDI", vec![A, T, C, G, A, T], 1),
-        RestrictionEnzyme::new("BsgEI", vec![T, C, C, G, G, A], 0),
-        RestrictionEnzyme::new("BsgHI", vec![T, C, A, T, G, A], 0),
+        RestrictionEnzyme::new("BsiWI", vec![C, G, T, A, C, G], 0),
         RestrictionEnzyme::new("BspEI", vec![T, C, C, G, G, A], 0),
+        RestrictionEnzyme::new("BspHI", vec![T, C, A, T, G, A], 0),
+        RestrictionEnzyme::new("BsrFI", vec![R, C, C, G, G, Y], 0),
+        RestrictionEnzyme::new("BsrGI", vec![T, G, T, A, C, A], 0),
+        RestrictionEnzyme::new("BstAPI", vec![G, C, A, N, N, N, N, N, T, G, C], 6),
         RestrictionEnzyme::new("BstBI", vec![T, T, C, G, A, A], 1),
+        RestrictionEnzyme::new("BstEII", vec![G, G, T, N, A, C, C], 0),
+        RestrictionEnzyme::new("BstXI", vec![C, C, A, N, N, N, N, N, N, T, G, G], 7),
+        RestrictionEnzyme::new("BstZ17I", vec![G, T, A, T, A, C], 2),
         RestrictionEnzyme::new("ClaI", vec![A, T, C, G, A, T], 1),
+        RestrictionEnzyme::new("DraIII", vec![C, A, C, N, N, N, G, T, G], 5),
+        RestrictionEnzyme::new("EaeI", vec![Y, G, G, C, C, R], 0),
+        RestrictionEnzyme::new("EagI", vec![C, G, G, C, C, G], 0),
+        RestrictionEnzyme::new("EcoNI", vec![C, C, T, N, N, N, N, N, A, G, G], 4),
         RestrictionEnzyme::new("EcoRI", vec![G, A, A, T, T, C], 0),
         RestrictionEnzyme::new("EcoRV", vec![G, A, T, A, T, C], 2),
-        RestrictionEnzyme::new("HindIII", vec![A, A, G, C, T, T], 0),
+        RestrictionEnzyme::new("FseI", vec![G, G, C, C, G, G, C, C], 5),
         RestrictionEnzyme::new("FspI", vec![T, G, C, G, C, A], 2),
-        // todo: TOo common
-        // RestrictionEnzyme::new("HhaI", vec![G, C, G, C], 2),
+        RestrictionEnzyme::new("HincII", vec![G, T, Y, R, A, C], 2),
+        RestrictionEnzyme::new("HindIII", vec![A, A, G, C, T, T], 0),
         RestrictionEnzyme::new("HpaI", vec![G, T, T, A, A, C], 2),
-        RestrictionEnzyme::new("KnpI", vec![G, G, T, A, C, C], 4),
+        RestrictionEnzyme::new("KpnI", vec![G, G, T, A, C, C], 4),
         RestrictionEnzyme::new("MauBI", vec![C, G, C, G, C, G, C, G], 1),
+        RestrictionEnzyme::new("MfeI", vec![C, A, A, T, T, G], 0),
+        RestrictionEnzyme::new("MluI", vec![A, C, G, C, G, T], 0),
         RestrictionEnzyme::new("MscI", vec![T, G, G, C, C, A], 2),
+        RestrictionEnzyme::new("NarI", vec![G, G, C, G, C, C], 1),
+        RestrictionEnzyme::new("NcoI", vec![C, C, A, T, G, G], 0),
         RestrictionEnzyme::new("NdeI", vec![C, A, T, A, T, G], 1),
+        RestrictionEnzyme::new("NgoMIV", vec![G, C, C, G, G, C], 0),
+        RestrictionEnzyme::new("NheI", vec![G, C, T, A, G, C], 0),
         RestrictionEnzyme::new("NotI", vec![G, C, G, G, C, C, G, C], 1),
         RestrictionEnzyme::new("NruI", vec![T, C, G, C, G, A], 2),
         RestrictionEnzyme::new("NsiI", vec![A, T, G, C, A, T], 4),
+        RestrictionEnzyme::new("NspI", vec![R, C, A, T, G, Y], 4),
         RestrictionEnzyme::new("PacI", vec![T, T, A, A, T, T, A, A], 4),
         RestrictionEnzyme::new("PciI", vec![A, C, A, T, G, T], 0),
-        RestrictionEnzyme::new("PmeI", vec![G, T, T, T, A, A, A, C], 4),
-        RestrictionEnzyme::new("PmII", vec![C, A, C, G, T, G], 2),
+        RestrictionEnzyme::new("PflMI", vec![C, C, A, N, N, N, N, N, T, G, G], 6),
+        RestrictionEnzyme::new("PmeI", vec![G, T, T, T, A, A, A, C], 3),
         RestrictionEnzyme::new("PmlI", vec![C, A, C, G, T, G], 2),
         RestrictionEnzyme::new("PsiI", vec![T, T, A, T, A, A], 2),
         RestrictionEnzyme::new("PspOMI", vec![G, G, G, C, C, C], 0),
         RestrictionEnzyme::new("PstI", vec![C, T, G, C, A, G], 4),
+        RestrictionEnzyme::new("PvuI", vec![C, G, A, T, C, G], 3),
+        RestrictionEnzyme::new("PvuII", vec![C, A, G, C, T, G], 2),
         RestrictionEnzyme::new("SacI", vec![G, A, G, C, T, C], 4),
-        // RestrictionEnzyme::new("SapI", vec![G, C, T, C, T, T, C], 4), // todo: Unclea on the cut site
+        RestrictionEnzyme::new("SacII", vec![C, C, G, C, G, G], 3),
         RestrictionEnzyme::new("SalI", vec![G, T, C, G, A, C], 0),
+        RestrictionEnzyme::new("SbfI", vec![C, C, T, G, C, A, G, G], 5),
         RestrictionEnzyme::new("ScaI", vec![A, G, T, A, C, T], 2),
-        RestrictionEnzyme::new("SmaI", vec![C, C, C, G, G, G], 2),
-        RestrictionEnzyme::new("SfbI", vec![C, C, T, G, C, A, G, G], 5),
+        RestrictionEnzyme::new("SfiI", vec![G, G, C, C, N, N, N, N, N, G, G, C, C], 7),
         RestrictionEnzyme::new("SfoI", vec![G, G, C, G, C, C], 2),
+        RestrictionEnzyme::new("SgrAI", vec![C, R, C, C, G, G, Y, G], 1),
+        RestrictionEnzyme::new("SmaI", vec![C, C, C, G, G, G], 2),
         RestrictionEnzyme::new("SpeI", vec![A, C, T, A, G, T], 0),
         RestrictionEnzyme::new("SphI", vec![G, C, A, T, G, C], 4),
         RestrictionEnzyme::new("SrfI", vec![G, C, C, C, G, G, G, C], 3),
         RestrictionEnzyme::new("StuI", vec![A, G, G, C, C, T], 2),
+        RestrictionEnzyme::new("StyI", vec![C, C, W, W, G, G], 0),
+        RestrictionEnzyme::new("SwaI", vec![A, T, T, T, A, A, A, T], 3),
+        RestrictionEnzyme::new("Tth111I", vec![G, A, C, N, N, N, G, T, C], 3),
         RestrictionEnzyme::new("XbaI", vec![T, C, T, A, G, A], 0),
         RestrictionEnzyme::new("XhoI", vec![C, T, C, G, A, G], 0),
+        RestrictionEnzyme::new("XmaI", vec![C, C, C, G, G, G], 0),
+        RestrictionEnzyme::new("XmnI", vec![G, A, A, N, N, N, N, T, T, C], 4),
         RestrictionEnzyme::new("ZraI", vec![G, A, C, G, T, C], 2),
-        // RestrictionEnzyme::new("HaeIII", vec![G, G, C, C], 1), // Too many matches
+        // Type IIS enzymes, which cut outside their recognition sequence. Used in Golden Gate assembly.
+        RestrictionEnzyme::new_type_iis("AarI", vec![C, A, C, C, T, G, C], 4, 8),
+        RestrictionEnzyme::new_type_iis("BbsI", vec![G, A, A, G, A, C], 2, 6),
+        RestrictionEnzyme::new_type_iis("BsaI", vec![G, G, T, C, T, C], 1, 5),
+        RestrictionEnzyme::new_type_iis("BsmBI", vec![C, G, T, C, T, C], 1, 5),
+        RestrictionEnzyme::new_type_iis("BtgZI", vec![G, C, G, A, T, G], 10, 14),
+        RestrictionEnzyme::new_type_iis("Esp3I", vec![C, G, T, C, T, C], 1, 5),
+        RestrictionEnzyme::new_type_iis("SapI", vec![G, C, T, C, T, T, C], 1, 4),
     ]
 }
+
+/// The `common_enzymes` library, initialized on first use.
+pub fn library() -> &'static [RestrictionEnzyme] {
+    LIBRARY.get_or_init(common_enzymes)
+}
+
+/// Sensitivity of an enzyme to methylation overlapping its recognition sequence.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MethylationSensitivity {
+    NotSensitive,
+    /// Cuts, but more slowly.
+    Impaired,
+    Blocked,
+}
+
+/// Reaction properties of an enzyme, for planning digests.
+#[derive(Clone, Debug)]
+pub struct ReProperties {
+    /// Incubation temperature, in °C.
+    pub incubation_temp: u8,
+    /// In °C. `None` if the enzyme can't be heat-inactivated.
+    pub heat_inactivation_temp: Option<u8>,
+    /// Activity in NEBuffer r1.1, r2.1, r3.1, and rCutSmart, as a percentage. Activity listed as <10% is 0.
+    pub buffer_activity: [u8; 4],
+    /// Methylation of GATC, by E. coli dam+ strains.
+    pub dam: MethylationSensitivity,
+    /// Methylation of CCWGG, by E. coli dcm+ strains.
+    pub dcm: MethylationSensitivity,
+    /// Methylation of CG, eg in mammalian DNA.
+    pub cpg: MethylationSensitivity,
+    /// Shows star (off-target) activity under non-optimal conditions, such as high glycerol.
+    pub star_activity: bool,
+}
+
+/// Look up reaction properties for an enzyme by name. Covers the most common enzymes in `common_enzymes`;
+/// returns `None` for others. Where there are high-fidelity (HF) versions, these are for the original.
+///
+/// [NEB: Enzyme finder](https://www.neb.com/en-us/tools-and-resources/interactive-tools/enzyme-finder)
+pub fn re_properties(name: &str) -> Option<ReProperties> {
+    use MethylationSensitivity::*;
+
+    #[rustfmt::skip]
+    let (incubation_temp, heat_inactivation_temp, buffer_activity, dam, dcm, cpg, star_activity) = match name {
+        "AatII" => (37, Some(80), [0, 50, 50, 100], NotSensitive, NotSensitive, Blocked, false),
+        "AgeI" => (37, Some(65), [100, 50, 10, 100], NotSensitive, NotSensitive, Blocked, false),
+        "ApaI" => (25, Some(65), [25, 25, 0, 100], NotSensitive, Blocked, Blocked, true),
+        "AscI" => (37, Some(80), [0, 10, 0, 100], NotSensitive, NotSensitive, Blocked, false),
+        "AseI" => (37, Some(65), [0, 50, 10, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "AvrII" => (37, None, [100, 50, 50, 100], NotSensitive, NotSensitive, NotSensitive, false),
+        "BamHI" => (37, None, [75, 100, 50, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "BbsI" => (37, Some(65), [100, 100, 25, 75], NotSensitive, NotSensitive, NotSensitive, false),
+        "BclI" => (50, None, [50, 100, 75, 100], Blocked, NotSensitive, NotSensitive, false),
+        "BglII" => (37, None, [10, 10, 100, 0], NotSensitive, NotSensitive, NotSensitive, false),
+        "BsaI" => (50, Some(65), [25, 75, 100, 100], NotSensitive, Impaired, Impaired, false),
+        "BsmBI" => (55, Some(80), [0, 10, 100, 10], NotSensitive, NotSensitive, Impaired, false),
+        "ClaI" => (37, Some(65), [10, 50, 50, 100], Blocked, NotSensitive, Blocked, false),
+        "EagI" => (37, Some(65), [10, 25, 100, 10], NotSensitive, NotSensitive, Blocked, false),
+        "EcoRI" => (37, Some(65), [25, 100, 50, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "EcoRV" => (37, Some(80), [10, 50, 100, 100], NotSensitive, NotSensitive, Impaired, true),
+        "FseI" => (37, Some(65), [100, 75, 0, 100], NotSensitive, Impaired, Blocked, false),
+        "HindIII" => (37, Some(80), [25, 100, 50, 50], NotSensitive, NotSensitive, NotSensitive, true),
+        "HpaI" => (37, None, [0, 25, 0, 100], NotSensitive, NotSensitive, Impaired, true),
+        "KpnI" => (37, None, [100, 75, 0, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "MfeI" => (37, None, [75, 25, 0, 100], NotSensitive, NotSensitive, NotSensitive, false),
+        "MluI" => (37, Some(80), [10, 50, 100, 25], NotSensitive, NotSensitive, Blocked, false),
+        "NcoI" => (37, Some(80), [100, 100, 50, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "NdeI" => (37, Some(65), [75, 100, 100, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "NheI" => (37, Some(65), [100, 100, 10, 100], NotSensitive, NotSensitive, NotSensitive, false),
+        "NotI" => (37, Some(65), [0, 50, 100, 0], NotSensitive, NotSensitive, Blocked, false),
+        "NruI" => (37, None, [0, 25, 100, 10], Blocked, NotSensitive, Blocked, false),
+        "NsiI" => (37, Some(65), [75, 100, 50, 100], NotSensitive, NotSensitive, NotSensitive, false),
+        "PacI" => (37, Some(65), [100, 75, 0, 100], NotSensitive, NotSensitive, NotSensitive, false),
+        "PmeI" => (37, Some(65), [0, 50, 10, 100], NotSensitive, NotSensitive, Impaired, false),
+        "PstI" => (37, Some(80), [75, 75, 100, 50], NotSensitive, NotSensitive, NotSensitive, true),
+        "PvuI" => (37, None, [0, 25, 100, 25], NotSensitive, NotSensitive, Blocked, false),
+        "PvuII" => (37, None, [100, 100, 100, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "SacI" => (37, Some(65), [100, 50, 0, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "SacII" => (37, Some(65), [0, 10, 10, 100], NotSensitive, NotSensitive, Blocked, false),
+        "SalI" => (37, Some(65), [0, 0, 100, 0], NotSensitive, NotSensitive, Blocked, true),
+        "ScaI" => (37, Some(80), [0, 100, 100, 0], NotSensitive, NotSensitive, NotSensitive, true),
+        "SmaI" => (25, Some(65), [0, 0, 0, 100], NotSensitive, Impaired, Blocked, false),
+        "SpeI" => (37, Some(80), [75, 100, 25, 100], NotSensitive, NotSensitive, NotSensitive, false),
+        "SphI" => (37, Some(65), [100, 100, 50, 100], NotSensitive, NotSensitive, NotSensitive, true),
+        "StuI" => (37, None, [50, 100, 10, 100], NotSensitive, Blocked, NotSensitive, false),
+        "XbaI" => (37, Some(65), [0, 100, 75, 100], Blocked, NotSensitive, NotSensitive, false),
+        "XhoI" => (37, Some(65), [75, 100, 100, 100], NotSensitive, Impaired, Impaired, false),
+        "XmaI" => (37, Some(65), [25, 50, 0, 100], NotSensitive, NotSensitive, Impaired, false),
+        _ => return None,
+    };
+
+    Some(ReProperties {
+        incubation_temp,
+        heat_inactivation_temp,
+        buffer_activity,
+        dam,
+        dcm,
+        cpg,
+        star_activity,
+    })
+}
+
+/// Names are matched ignoring case, spaces, hyphens, and underscores; eg "Eco RI" matches "EcoRI".
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Find an enzyme in `library` by name. Case insensitive, and ignores spaces, hyphens, and underscores.
+pub fn find_by_name(name: &str) -> Option<&'static RestrictionEnzyme> {
+    let index = NAME_INDEX.get_or_init(|| {
+        library()
+            .iter()
+            .enumerate()
+            .map(|(i, re)| (normalize_name(&re.name), i))
+            .collect()
+    });
+
+    index.get(&normalize_name(name)).map(|&i| &library()[i])
+}
+
+/// Find enzymes in `library` with the same recognition sequence as `re`, excluding `re` itself. This
+/// includes neoschizomers, which cut the same site at a different position.
+pub fn find_isoschizomers(re: &RestrictionEnzyme) -> Vec<&'static RestrictionEnzyme> {
+    let index = SITE_INDEX.get_or_init(|| {
+        let mut result: HashMap<_, Vec<_>> = HashMap::new();
+        for (i, re) in library().iter().enumerate() {
+            result.entry(re.cut_seq.clone()).or_default().push(i);
+        }
+        result
+    });
+
+    lookup(index.get(&re.cut_seq), re)
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum EndKey {
+    Blunt,
+    /// The overhang, in whichever orientation sorts first; ends ligate with either.
+    FivePrime(Vec<Nucleotide>),
+    ThreePrime(Vec<Nucleotide>),
+}
+
+/// The ends an enzyme produces. `None` if its overhang depends on the sequence cut, ie it's degenerate,
+/// or outside the recognition sequence.
+fn end_key(re: &RestrictionEnzyme) -> Option<EndKey> {
+    if re.makes_blunt_ends() {
+        return Some(EndKey::Blunt);
+    }
+    if re.cuts_outside_site() {
+        return None;
+    }
+
+    let (top, bottom) = (re.cut_top() as usize, re.cut_bottom() as usize);
+    if re.cut_seq[top.min(bottom)..top.max(bottom)]
+        .iter()
+        .any(|nt| nt.nt_matches().len() > 1)
+    {
+        return None;
+    }
+
+    // Degenerate positions outside the overhang don't affect it.
+    let site: Vec<_> = re.cut_seq.iter().map(|nt| nt.nt_matches()[0]).collect();
+
+    let canonical = |overhang: Vec<Nucleotide>| {
+        let rc = seq_complement(&overhang);
+        let key = |seq: &[Nucleotide]| seq.iter().map(|nt| *nt as u8).collect::<Vec<_>>();
+        if key(&rc) < key(&overhang) {
+            rc
+        } else {
+            overhang
+        }
+    };
+
+    let (left, right) = re.cut_duplex(&site, 0)?;
+    let five_prime = right.five_prime_overhang_left();
+    if !five_prime.is_empty() {
+        Some(EndKey::FivePrime(canonical(five_prime)))
+    } else {
+        Some(EndKey::ThreePrime(canonical(
+            left.three_prime_overhang_right(),
+        )))
+    }
+}
+
+/// Whether the ends two enzymes produce can ligate to each other: The same overhang (or its reverse
+/// complement) on the same strand, or both blunt. False if either enzyme's overhang depends on the
+/// sequence, eg Type IIS enzymes, since this can't be known from the enzymes alone.
+pub fn overhangs_compatible(a: &RestrictionEnzyme, b: &RestrictionEnzyme) -> bool {
+    match end_key(a) {
+        Some(key) => end_key(b) == Some(key),
+        None => false,
+    }
+}
+
+/// Find enzymes in `library` that produce ends which can ligate to those `re` produces, excluding `re`
+/// itself: The same overhang (or its reverse complement) on the same strand, or blunt ends. Returns an
+/// empty Vec for enzymes whose overhang depends on the sequence, eg Type IIS enzymes.
+pub fn find_compatible_overhangs(re: &RestrictionEnzyme) -> Vec<&'static RestrictionEnzyme> {
+    let index = END_INDEX.get_or_init(|| {
+        let mut result: HashMap<_, Vec<_>> = HashMap::new();
+        for (i, re) in library().iter().enumerate() {
+            if let Some(key) = end_key(re) {
+                result.entry(key).or_default().push(i);
+            }
+        }
+        result
+    });
+
+    match end_key(re) {
+        Some(key) => lookup(index.get(&key), re),
+        None => Vec::new(),
+    }
+}
+
+fn lookup(
+    indices: Option<&Vec<usize>>,
+    exclude: &RestrictionEnzyme,
+) -> Vec<&'static RestrictionEnzyme> {
+    indices
+        .into_iter()
+        .flatten()
+        .map(|&i| &library()[i])
+        .filter(|re| *re != exclude)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn common_enzymes_sites() {
+        let lib = common_enzymes();
+
+        let ecori = lib.iter().find(|re| re.name == "EcoRI").unwrap();
+        assert_eq!(ecori.cut_seq, vec![G, A, A, T, T, C]);
+        assert_eq!(ecori.cut_after, 0);
+        assert_eq!((ecori.cut_offset_top, ecori.cut_offset_bottom), (-5, -1));
+
+        let mut names = HashSet::new();
+        for re in &lib {
+            assert!(names.insert(re.name.clone()), "{} is listed twice", re.name);
+
+            let len = re.cut_seq.len() as isize;
+            assert!(len >= 6, "{}", re.name);
+
+            if re.cuts_outside_site() {
+                // Type IIS: Both cuts are downstream of the site, leaving a 5' overhang.
+                assert!(re.cut_top() >= len, "{}", re.name);
+                assert!(re.cut_bottom() > re.cut_top(), "{}", re.name);
+                assert_eq!(re.cut_after as isize, re.cut_top() - 1, "{}", re.name);
+            } else {
+                // Palindromic sites, cut symmetrically.
+                let rc: Vec<_> = re.cut_seq.iter().rev().map(|nt| nt.complement()).collect();
+                assert_eq!(rc, re.cut_seq, "{}", re.name);
+                assert!((re.cut_after as isize) < len, "{}", re.name);
+                assert_eq!(re.cut_top() + re.cut_bottom(), len, "{}", re.name);
+            }
+        }
+    }
+
+    #[test]
+    fn properties_are_for_original_enzymes() {
+        // The original BsaI, not BsaI-HFv2, which is incubated at 37 °C with full activity in all buffers.
+        let bsai = re_properties("BsaI").unwrap();
+        assert_eq!(bsai.incubation_temp, 50);
+        assert_eq!(bsai.buffer_activity, [25, 75, 100, 100]);
+
+        assert!(re_properties("BsaI-HFv2").is_none());
+    }
+}
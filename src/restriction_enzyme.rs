@@ -7,11 +7,16 @@
 //! exact NTs.
 
 use std::{
+    cmp::Ordering,
     collections::{hash_map::Entry, HashMap},
     hash::{Hash, Hasher},
 };
 
-use crate::{Nucleotide, NucleotideGeneral, Seq};
+use crate::{
+    duplex::DuplexSeq,
+    re_lib::{re_properties, MethylationSensitivity, ReProperties},
+    seq_complement, seq_complement_no_rev, Nucleotide, NucleotideGeneral, Seq, SeqTopology, Strand,
+};
 
 pub struct LigationProduct {
     /// 5' to 3' (both strands; they are in opposite directions.)
@@ -24,26 +29,33 @@ pub struct LigationProduct {
     pub alignment: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReMatch {
     pub lib_index: usize,
-    /// Cuts after this index, in the "forward" direction.
+    /// The 1-based start of the recognition site on the forward strand, for matches on either strand.
     pub seq_index: usize,
-    // pub direction: PrimerDirection,
     /// todo: Experimenting
     /// The number of matches found for this RE.
     pub match_count: usize,
 }
 
-#[derive(Clone, Eq)]
+#[derive(Clone, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RestrictionEnzyme {
     pub name: String,
     /// From the 5' end.
     // pub seq: Seq, // todo: You may eventually need Vec<NucleotideGeneral>.
     pub cut_seq: Vec<NucleotideGeneral>,
-    /// Index to cut after, from the 5' end. For blunt ends, this will be
-    /// halfway through the seq (rounded down)
+    /// Index to cut after on the top strand, from the 5' end. For blunt ends, this will be
+    /// halfway through the seq (rounded down). For Type IIS enzymes, this may be past the end of `cut_seq`.
     pub cut_after: u8,
+    /// Cut positions on the top and bottom strands, in nucleotides downstream of the 3' end of the
+    /// recognition sequence, using top-strand coordinates. This matches the conventional notation for
+    /// Type IIS enzymes: BsaI, GGTCTC(1/5), is (1, 5). For enzymes that cut within their recognition
+    /// sequence, these are negative: EcoRI, G^AATTC, is (-5, -1).
+    pub cut_offset_top: i8,
+    pub cut_offset_bottom: i8,
 }
 
 impl Hash for RestrictionEnzyme {
@@ -59,28 +71,91 @@ impl PartialEq for RestrictionEnzyme {
 }
 
 impl RestrictionEnzyme {
+    /// Create an enzyme with a palindromic recognition sequence, cutting within it. The bottom
+    /// strand cut mirrors the top strand one. `cut_after` isn't limited to the site: If it's past the
+    /// last index of `cut_seq`, the top strand cut is after the site, and the mirrored bottom strand cut
+    /// is before it, by the same distance. Offsets that don't fit in an `i8` are clamped, so the cuts of
+    /// very large values aren't mirrored exactly.
     // pub fn new(name: &str, seq: Seq, cut_after: u8) -> Self {
     pub fn new(name: &str, cut_seq: Vec<NucleotideGeneral>, cut_after: u8) -> Self {
+        let cut = cut_after as isize + 1;
+        let len = cut_seq.len() as isize;
+        let clamp = |v: isize| v.clamp(i8::MIN as isize, i8::MAX as isize) as i8;
+
+        Self {
+            name: name.to_owned(),
+            cut_seq,
+            cut_after,
+            cut_offset_top: clamp(cut - len),
+            cut_offset_bottom: clamp(-cut),
+        }
+    }
+
+    /// Create an enzyme that cuts outside its recognition sequence, e.g. a Type IIS enzyme used in
+    /// Golden Gate assembly. Offsets are downstream of the 3' end of the recognition sequence; for
+    /// GGTCTC(1/5), they are 1 and 5.
+    pub fn new_type_iis(
+        name: &str,
+        cut_seq: Vec<NucleotideGeneral>,
+        cut_offset_top: i8,
+        cut_offset_bottom: i8,
+    ) -> Self {
+        let cut_after = (cut_seq.len() as isize + cut_offset_top as isize - 1).clamp(0, 255) as u8;
+
         Self {
             name: name.to_owned(),
             cut_seq,
             cut_after,
+            cut_offset_top,
+            cut_offset_bottom,
         }
     }
 
+    /// The top strand cut position, ie the index of the first nucleotide after the cut, relative to the
+    /// start of the recognition sequence.
+    pub fn cut_top(&self) -> isize {
+        self.cut_seq.len() as isize + self.cut_offset_top as isize
+    }
+
+    /// The bottom strand cut position, in top-strand coordinates relative to the start of the recognition
+    /// sequence.
+    pub fn cut_bottom(&self) -> isize {
+        self.cut_seq.len() as isize + self.cut_offset_bottom as isize
+    }
+
+    /// True if either cut is outside the recognition sequence.
+    pub fn cuts_outside_site(&self) -> bool {
+        let len = self.cut_seq.len() as isize;
+        let inside = |cut| (0..=len).contains(&cut);
+
+        !inside(self.cut_top()) || !inside(self.cut_bottom())
+    }
+
     pub fn makes_blunt_ends(&self) -> bool {
-        self.cut_after as isize + 1 == self.cut_seq.len() as isize / 2
+        self.cut_top() == self.cut_bottom()
     }
 
-    /// A depiction of where to cut.
+    /// A depiction of where to cut. E.g. `G | AATTC` for EcoRI, or `GGTCTC(1/5)` for enzymes that
+    /// cut outside their recognition sequence. An empty recognition sequence produces an empty string.
     pub fn cut_depiction(&self) -> String {
         let nt_chars = seq_general_to_str(&self.cut_seq);
 
+        if nt_chars.is_empty() {
+            return nt_chars;
+        }
+
+        if self.cuts_outside_site() {
+            return format!(
+                "{nt_chars}({}/{})",
+                self.cut_offset_top, self.cut_offset_bottom
+            );
+        }
+
         let mut result = String::new();
 
         for (i, nt_char) in nt_chars.chars().enumerate() {
             result.push(nt_char);
-            if i as u8 == self.cut_after {
+            if i == self.cut_after as usize {
                 result.push_str(" | ");
             }
         }
@@ -88,67 +163,154 @@ impl RestrictionEnzyme {
         result
     }
 
-    // todo: Consider replacing these with a dual-stranded model, instead of
-    // todo modeling overhangs.
+    /// Reaction properties, such as temperature and methylation sensitivity, if known.
+    pub fn properties(&self) -> Option<ReProperties> {
+        re_properties(&self.name)
+    }
+
+    /// Check if dam methylation would block cutting at a site. `site_context` is the recognition
+    /// sequence, and enough flanking nucleotides to include any GATC overlapping it. Returns false
+    /// for enzymes that aren't blocked by dam methylation, or whose properties aren't known.
+    pub fn is_blocked_by_dam(&self, site_context: &[Nucleotide]) -> bool {
+        let blocked = self
+            .properties()
+            .is_some_and(|p| p.dam == MethylationSensitivity::Blocked);
+
+        let site_len = self.cut_seq.len();
+        if !blocked || site_context.len() < site_len {
+            return false;
+        }
+
+        let dam_site = [Nucleotide::G, Nucleotide::A, Nucleotide::T, Nucleotide::C];
+        let gatcs: Vec<_> = site_context
+            .windows(dam_site.len())
+            .enumerate()
+            .filter(|(_, w)| *w == dam_site)
+            .map(|(i, _)| i)
+            .collect();
+
+        site_context
+            .windows(site_len)
+            .enumerate()
+            .filter(|(_, w)| w.iter().zip(&self.cut_seq).all(|(nt, g)| g.matches(*nt)))
+            .any(|(site, _)| {
+                gatcs
+                    .iter()
+                    .any(|&g| g < site + site_len && g + dam_site.len() > site)
+            })
+    }
+
+    /// Cut a blunt-ended, double-stranded copy of `seq` at a site, starting at the 0-based `site_start`.
+    /// Returns the pieces left and right of the cut, with the overhangs between them. Returns `None` if
+    /// either cut falls outside `seq`. The site isn't checked against `seq`; see `find_re_matches`.
+    pub fn cut_duplex(
+        &self,
+        seq: &[Nucleotide],
+        site_start: usize,
+    ) -> Option<(DuplexSeq, DuplexSeq)> {
+        let site_start = isize::try_from(site_start).ok()?;
+        let to_index = |cut: isize| {
+            usize::try_from(site_start + cut)
+                .ok()
+                .filter(|&i| i <= seq.len())
+        };
+        let top = to_index(self.cut_top())?;
+        let bottom = to_index(self.cut_bottom())?;
+
+        let left = DuplexSeq {
+            top: seq[..top].to_vec(),
+            bottom: seq_complement(&seq[..bottom]),
+            offset: 0,
+        };
+        let right = DuplexSeq {
+            top: seq[top..].to_vec(),
+            bottom: seq_complement(&seq[bottom..]),
+            offset: bottom as isize - top as isize,
+        };
+
+        Some((left, right))
+    }
 
     /// Find the overhanging NTs 5' of a sequence's top strand.
-    /// `seq_segment` must be the same size as, and aligned with the cut sequence.
+    /// `seq_segment` must be aligned with the start of the cut sequence, and extend to at least its end,
+    /// or to the furthest cut, whichever is further. Returns an empty Vec if it doesn't.
+    #[deprecated(note = "Use `cut_duplex`, and the overhang methods on `DuplexSeq`")]
     pub fn overhang_top_left(&self, seq_segment: &[Nucleotide]) -> Vec<Nucleotide> {
-        let cut = self.cut_after as usize + 1;
-        let len = self.cut_seq.len();
-
-        if cut as isize - 2 >= len as isize / 2 {
-            Vec::new() // No overhang on this strand.
-        } else {
-            if len - cut < cut {
-                eprintln!("Error with cut lens. len-cut: {}, Cut: {cut}", len - cut);
-                return Vec::new();
-            }
+        let (top, bottom) = (self.cut_top(), self.cut_bottom());
 
-            seq_segment[cut..len - cut].to_vec()
+        if top >= bottom {
+            return Vec::new(); // No overhang on this strand.
         }
+
+        overhang_slice(seq_segment, top, bottom)
     }
 
+    /// Find the overhanging NTs 3' of a sequence's top strand. See `overhang_top_left` for requirements
+    /// on `seq_segment`.
+    #[deprecated(note = "Use `cut_duplex`, and the overhang methods on `DuplexSeq`")]
     pub fn overhang_top_right(&self, seq_segment: &[Nucleotide]) -> Vec<Nucleotide> {
-        let cut = self.cut_after as usize + 1;
-        let len = self.cut_seq.len();
+        let (top, bottom) = (self.cut_top(), self.cut_bottom());
 
-        if cut as isize - 2 < len as isize / 2 {
-            Vec::new() // No overhang on this strand.
-        } else {
-            seq_segment[len - cut..cut].to_vec()
+        if top <= bottom {
+            return Vec::new(); // No overhang on this strand.
         }
+
+        overhang_slice(seq_segment, bottom, top)
     }
 
+    /// The bottom strand's overhang, aligned under the top strand: 3' to 5', left to right.
+    #[deprecated(note = "Use `cut_duplex`, and the overhang methods on `DuplexSeq`")]
+    #[allow(deprecated)]
     pub fn overhang_bottom_left(&self, seq_segment: &[Nucleotide]) -> Vec<Nucleotide> {
-        // todo: DRY implementation of reverse without compl
-        let x = self.overhang_top_right(seq_segment);
-        let mut result = x.to_vec();
-
-        for nt in &mut result {
-            *nt = nt.complement();
-        }
-
-        result
+        seq_complement_no_rev(&self.overhang_top_right(seq_segment))
     }
 
+    /// The bottom strand's overhang, aligned under the top strand: 3' to 5', left to right.
+    #[deprecated(note = "Use `cut_duplex`, and the overhang methods on `DuplexSeq`")]
+    #[allow(deprecated)]
     pub fn overhang_bottom_right(&self, seq_segment: &[Nucleotide]) -> Vec<Nucleotide> {
-        // todo: DRY implementation of reverse without compl
-        let x = self.overhang_top_left(seq_segment);
-        let mut result = x.to_vec();
-
-        for nt in &mut result {
-            *nt = nt.complement();
-        }
-
-        result
+        seq_complement_no_rev(&self.overhang_top_left(seq_segment))
     }
 }
 
-/// Go through a sequence, and attempt to match each enzyme in our RE library to the sequence.
-/// Note/todo: We currently only search in the forward direction; this works if all enzymes in our
-/// todo library are symmetric.
+/// Go through a sequence, and attempt to match each enzyme in our RE library to the sequence. Only
+/// searches the forward strand; this finds every site of palindromic enzymes, but may miss sites of
+/// others. See `find_re_matches_both_strands`.
 pub fn find_re_matches(seq: &[Nucleotide], lib: &[RestrictionEnzyme]) -> Vec<ReMatch> {
+    find_re_matches_topology(seq, lib, SeqTopology::Linear)
+}
+
+/// As `find_re_matches`, but if `topology` is circular, also finds sites that span the origin. These
+/// are reported at their start position, near the end of the sequence, so `seq_index` is always within
+/// `1..=seq.len()`. Enzymes with empty recognition sequences, or ones longer than the sequence, never match.
+pub fn find_re_matches_topology(
+    seq: &[Nucleotide],
+    lib: &[RestrictionEnzyme],
+    topology: SeqTopology,
+) -> Vec<ReMatch> {
+    find_matches(seq, lib, topology, false)
+        .into_iter()
+        .map(|(re_match, _)| re_match)
+        .collect()
+}
+
+/// As `find_re_matches_topology`, but also searches the reverse strand, with the strand each site reads
+/// 5' to 3' on. Palindromic sites are reported once, as `Strand::Forward`. `match_count` includes sites
+/// on both strands.
+pub fn find_re_matches_both_strands(
+    seq: &[Nucleotide],
+    lib: &[RestrictionEnzyme],
+    topology: SeqTopology,
+) -> Vec<(ReMatch, Strand)> {
+    find_matches(seq, lib, topology, true)
+}
+
+fn find_matches(
+    seq: &[Nucleotide],
+    lib: &[RestrictionEnzyme],
+    topology: SeqTopology,
+    both_strands: bool,
+) -> Vec<(ReMatch, Strand)> {
     let mut result = Vec::new();
     let seq_len = seq.len();
 
@@ -157,47 +319,354 @@ pub fn find_re_matches(seq: &[Nucleotide], lib: &[RestrictionEnzyme]) -> Vec<ReM
     for (lib_index, re) in lib.iter().enumerate() {
         let re_seq_len = re.cut_seq.len();
 
-        for i in 0..seq_len {
-            if i + re_seq_len + 1 >= seq_len {
-                continue;
-            }
+        if re_seq_len == 0 || re_seq_len > seq_len {
+            continue;
+        }
+
+        // The bottom strand reads as the site's reverse complement on the top strand. Palindromic sites
+        // match identically on both, so only need one pass.
+        let site_rc: Vec<_> = re.cut_seq.iter().rev().map(|nt| nt.complement()).collect();
+        let mut strands = vec![(Strand::Forward, &re.cut_seq)];
+        if both_strands && site_rc != re.cut_seq {
+            strands.push((Strand::Reverse, &site_rc));
+        }
+
+        for (strand, site) in strands {
+            let masks: Vec<_> = site.iter().map(|nt| nt.mask()).collect();
 
-            let mut matches = true;
-            // If the RE cut site doesn't match this sequence segment, continue.
-            for (j, nt) in seq[i..i + re_seq_len].iter().enumerate() {
-                if !re.cut_seq[j].matches(*nt) {
-                    matches = false;
+            for i in 0..seq_len {
+                // For linear sequences, the site must fit entirely within the sequence; it may end on its
+                // last nucleotide. For circular ones, it may wrap around the origin.
+                if topology == SeqTopology::Linear && i + re_seq_len > seq_len {
                     break;
                 }
-            }
 
-            if !matches {
-                continue;
-            }
+                // If the RE cut site doesn't match this sequence segment, continue.
+                let matches = masks
+                    .iter()
+                    .enumerate()
+                    .all(|(j, mask)| mask & (1 << seq[(i + j) % seq_len] as u8) != 0);
+
+                if !matches {
+                    continue;
+                }
 
-            result.push(ReMatch {
-                lib_index,
-                // direction: PrimerDirection::Forward,
-                seq_index: i + 1, // +1 indexing.
-                match_count: 0,   // Updated below.
-            });
-
-            if let Entry::Vacant(e) = match_counts.entry(lib_index) {
-                e.insert(1);
-            } else {
-                *match_counts.get_mut(&lib_index).unwrap() += 1;
+                result.push((
+                    ReMatch {
+                        lib_index,
+                        seq_index: i + 1, // +1 indexing.
+                        match_count: 0,   // Updated below.
+                    },
+                    strand,
+                ));
+
+                if let Entry::Vacant(e) = match_counts.entry(lib_index) {
+                    e.insert(1);
+                } else {
+                    *match_counts.get_mut(&lib_index).unwrap() += 1;
+                }
             }
         }
     }
 
     // Apply match counts.
-    for re_match in &mut result {
+    for (re_match, _) in &mut result {
         re_match.match_count = match_counts[&re_match.lib_index];
     }
 
     result
 }
 
+/// Sites found for one enzyme.
+#[derive(Clone, Debug)]
+pub struct ReSummary {
+    pub enzyme_name: String,
+    pub cut_count: usize,
+    /// 1-based starts of each recognition site, as in `ReMatch::seq_index`, in ascending order.
+    pub positions: Vec<usize>,
+}
+
+/// Count the sites of each enzyme in a library, on both strands of a linear sequence. Includes enzymes
+/// that don't cut.
+/// Sorted by cut count, ascending; enzymes with equal counts stay in library order.
+pub fn re_summary(seq: &[Nucleotide], lib: &[RestrictionEnzyme]) -> Vec<ReSummary> {
+    let mut result: Vec<_> = lib
+        .iter()
+        .map(|re| ReSummary {
+            enzyme_name: re.name.clone(),
+            cut_count: 0,
+            positions: Vec::new(),
+        })
+        .collect();
+
+    for (re_match, _) in find_re_matches_both_strands(seq, lib, SeqTopology::Linear) {
+        result[re_match.lib_index]
+            .positions
+            .push(re_match.seq_index);
+    }
+
+    for summary in &mut result {
+        summary.positions.sort();
+        summary.cut_count = summary.positions.len();
+    }
+
+    result.sort_by_key(|s| s.cut_count);
+    result
+}
+
+/// Enzymes in a library that have exactly one site, on either strand of a linear sequence. In library
+/// order.
+pub fn single_cutters<'a>(
+    seq: &[Nucleotide],
+    lib: &'a [RestrictionEnzyme],
+) -> Vec<&'a RestrictionEnzyme> {
+    let mut counts = vec![0; lib.len()];
+    for (re_match, _) in find_re_matches_both_strands(seq, lib, SeqTopology::Linear) {
+        counts[re_match.lib_index] += 1;
+    }
+
+    lib.iter()
+        .zip(counts)
+        .filter(|(_, count)| *count == 1)
+        .map(|(re, _)| re)
+        .collect()
+}
+
+/// The structure of one end of a double-stranded fragment.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EndKind {
+    Blunt,
+    /// The strand ending 5' at this end protrudes: The top strand, on the left end, or the bottom
+    /// strand, on the right end.
+    FivePrime,
+    /// The strand ending 3' at this end protrudes: The bottom strand, on the left end, or the top
+    /// strand, on the right end.
+    ThreePrime,
+}
+
+#[derive(Clone, Debug)]
+pub struct FragmentEnd {
+    pub kind: EndKind,
+    /// The single-stranded nucleotides, in top-strand sense (5' to 3' on the top strand), regardless
+    /// of which strand protrudes. Empty for blunt ends.
+    pub overhang: Seq,
+    /// The enzyme that cut here. `None` for the ends of a linear sequence.
+    pub enzyme: Option<RestrictionEnzyme>,
+}
+
+impl FragmentEnd {
+    fn seq_end() -> Self {
+        Self {
+            kind: EndKind::Blunt,
+            overhang: Vec::new(),
+            enzyme: None,
+        }
+    }
+}
+
+/// A double-stranded fragment resulting from a restriction digest.
+#[derive(Clone, Debug)]
+pub struct Fragment {
+    /// 5' to 3'.
+    pub strand_top: Seq,
+    /// 5' to 3'; ie the reverse complement of the top strand, plus or minus overhangs.
+    pub strand_bottom: Seq,
+    /// 1-based, inclusive top-strand coordinates in the original sequence. If `end` is less than
+    /// `start`, the fragment spans the origin of a circular sequence.
+    pub start: usize,
+    pub end: usize,
+    pub left: FragmentEnd,
+    pub right: FragmentEnd,
+}
+
+impl Fragment {
+    /// Flip the fragment, so the bottom strand becomes the top. Overhangs are reverse-complemented, to
+    /// remain in top-strand sense; `start` and `end` are unchanged.
+    pub fn reverse_complement(&self) -> Self {
+        let flip = |end: &FragmentEnd| FragmentEnd {
+            overhang: seq_complement(&end.overhang),
+            ..end.clone()
+        };
+
+        Self {
+            strand_top: self.strand_bottom.clone(),
+            strand_bottom: self.strand_top.clone(),
+            start: self.start,
+            end: self.end,
+            left: flip(&self.right),
+            right: flip(&self.left),
+        }
+    }
+}
+
+/// A double-stranded cut. Positions are of the first nucleotide after the cut, on each strand, in
+/// top-strand coordinates.
+struct Cut {
+    top: isize,
+    bottom: isize,
+    overhang: Seq,
+    enzyme: RestrictionEnzyme,
+}
+
+impl Cut {
+    fn end(&self) -> FragmentEnd {
+        let kind = match self.top.cmp(&self.bottom) {
+            Ordering::Less => EndKind::FivePrime,
+            Ordering::Greater => EndKind::ThreePrime,
+            Ordering::Equal => EndKind::Blunt,
+        };
+
+        FragmentEnd {
+            kind,
+            overhang: self.overhang.clone(),
+            enzyme: Some(self.enzyme.clone()),
+        }
+    }
+}
+
+/// Digest a sequence with one or more enzymes. For linear sequences, `n` cuts produce `n + 1` fragments;
+/// for circular ones, `n` fragments, with a single cut producing one linearized fragment. Enzymes cutting
+/// at the same position produce a single cut. Cuts whose overhangs would overlap a cut already made are
+/// skipped, as are cuts that fall beyond the ends of a linear sequence. On circular sequences, this
+/// includes a last cut whose overhang runs across the origin into the first.
+///
+/// If nothing cuts, the result is the whole sequence, as one fragment with blunt, enzyme-less ends. For
+/// circular sequences, this fragment is the intact circle; its ends aren't real, and it hasn't been
+/// linearized.
+pub fn digest(
+    seq: &[Nucleotide],
+    enzymes: &[RestrictionEnzyme],
+    topology: SeqTopology,
+) -> Vec<Fragment> {
+    // This can't overflow: A `Vec` can't hold more than `isize::MAX` bytes.
+    let len = seq.len() as isize;
+    if len == 0 {
+        return Vec::new();
+    }
+
+    // Handles circular wrapping, and positions past the origin from the cut position arithmetic.
+    let nt_at = |i: isize| seq[i.rem_euclid(len) as usize];
+    let range = |start: isize, end: isize| (start..end).map(nt_at).collect::<Seq>();
+
+    let mut cuts = Vec::new();
+
+    for (re_match, strand) in find_re_matches_both_strands(seq, enzymes, topology) {
+        let re = &enzymes[re_match.lib_index];
+        let site_start = re_match.seq_index as isize - 1;
+
+        let (mut top, mut bottom) = match strand {
+            Strand::Forward => (site_start + re.cut_top(), site_start + re.cut_bottom()),
+            // The enzyme's top strand is our bottom one; mirror its cuts across the site.
+            Strand::Reverse => {
+                let site_end = site_start + re.cut_seq.len() as isize;
+                (site_end - re.cut_bottom(), site_end - re.cut_top())
+            }
+        };
+
+        match topology {
+            SeqTopology::Linear => {
+                if top.min(bottom) <= 0 || top.max(bottom) >= len {
+                    continue;
+                }
+            }
+            SeqTopology::Circular => {
+                let shift = top.rem_euclid(len) - top;
+                top += shift;
+                bottom += shift;
+            }
+        }
+
+        // The single-stranded region between the cuts, in top-strand sense.
+        let overhang = range(top.min(bottom), top.max(bottom));
+
+        cuts.push(Cut {
+            top,
+            bottom,
+            overhang,
+            enzyme: re.clone(),
+        });
+    }
+
+    cuts.sort_by_key(|c| (c.top, c.bottom));
+
+    // Remove duplicate cuts, and cuts that overlap the previous one.
+    let mut cuts_filtered: Vec<Cut> = Vec::new();
+    for cut in cuts {
+        if let Some(prev) = cuts_filtered.last() {
+            if cut.top.min(cut.bottom) < prev.top.max(prev.bottom)
+                || (cut.top, cut.bottom) == (prev.top, prev.bottom)
+            {
+                continue;
+            }
+        }
+        cuts_filtered.push(cut);
+    }
+    let mut cuts = cuts_filtered;
+
+    if topology == SeqTopology::Circular {
+        // The last cut's overhang may run into the first's, across the origin. Skip it, as with other
+        // overlapping cuts.
+        while cuts.len() > 1 {
+            let (first, last) = (&cuts[0], &cuts[cuts.len() - 1]);
+            if last.top.max(last.bottom) <= first.top.min(first.bottom) + len {
+                break;
+            }
+            cuts.pop();
+        }
+    }
+
+    if cuts.is_empty() {
+        return vec![Fragment {
+            strand_top: seq.to_vec(),
+            strand_bottom: seq_complement(seq),
+            start: 1,
+            end: seq.len(),
+            left: FragmentEnd::seq_end(),
+            right: FragmentEnd::seq_end(),
+        }];
+    }
+
+    // (top, bottom, end) for each boundary, in order.
+    let mut bounds = Vec::new();
+    match topology {
+        SeqTopology::Linear => {
+            bounds.push((0, 0, FragmentEnd::seq_end()));
+            bounds.extend(cuts.iter().map(|c| (c.top, c.bottom, c.end())));
+            bounds.push((len, len, FragmentEnd::seq_end()));
+        }
+        SeqTopology::Circular => {
+            bounds.extend(cuts.iter().map(|c| (c.top, c.bottom, c.end())));
+            let first = &cuts[0];
+            bounds.push((first.top + len, first.bottom + len, first.end()));
+        }
+    }
+
+    bounds
+        .windows(2)
+        .map(|w| {
+            let (top_l, bottom_l, end_l) = &w[0];
+            let (top_r, bottom_r, end_r) = &w[1];
+
+            Fragment {
+                strand_top: range(*top_l, *top_r),
+                strand_bottom: seq_complement(&range(*bottom_l, *bottom_r)),
+                start: top_l.rem_euclid(len) as usize + 1,
+                end: (top_r - 1).rem_euclid(len) as usize + 1,
+                left: end_l.clone(),
+                right: end_r.clone(),
+            }
+        })
+        .collect()
+}
+
+/// A slice of `seq_segment` between two cut positions, or an empty Vec if the positions aren't within it.
+fn overhang_slice(seq_segment: &[Nucleotide], start: isize, end: isize) -> Vec<Nucleotide> {
+    match (usize::try_from(start), usize::try_from(end)) {
+        (Ok(start), Ok(end)) => seq_segment.get(start..end).unwrap_or_default().to_vec(),
+        _ => Vec::new(),
+    }
+}
+
 /// Convert a nucleotide sequence to string.
 pub fn seq_general_to_str(seq: &[NucleotideGeneral]) -> String {
     let mut result = String::new();
@@ -208,3 +677,260 @@ pub fn seq_general_to_str(seq: &[NucleotideGeneral]) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nucleotide::NucleotideGeneral::*, re_lib::find_by_name, seq_from_str};
+
+    fn enzyme(name: &str) -> RestrictionEnzyme {
+        find_by_name(name).unwrap().clone()
+    }
+
+    #[test]
+    fn digest_no_cuts() {
+        let seq = seq_from_str("ACGTACGTACGTTTGCA");
+
+        for topology in [SeqTopology::Linear, SeqTopology::Circular] {
+            let frags = digest(&seq, &[enzyme("EcoRI")], topology);
+
+            assert_eq!(frags.len(), 1);
+            let frag = &frags[0];
+            assert_eq!(frag.strand_top, seq);
+            assert_eq!(frag.strand_bottom, seq_complement(&seq));
+            assert_eq!((frag.start, frag.end), (1, seq.len()));
+            for end in [&frag.left, &frag.right] {
+                assert_eq!(end.kind, EndKind::Blunt);
+                assert!(end.overhang.is_empty());
+                assert!(end.enzyme.is_none());
+            }
+        }
+
+        assert!(digest(&[], &[enzyme("EcoRI")], SeqTopology::Linear).is_empty());
+    }
+
+    #[test]
+    fn digest_circular_overlap_across_origin() {
+        // Two sites, with long overhangs; the second's runs across the origin into the first's.
+        let re = RestrictionEnzyme::new_type_iis("X", vec![G, G, T, C, T, C], 1, 20);
+        let seq = seq_from_str(&format!("GGTCTC{}GGTCTC{}", "A".repeat(19), "A".repeat(9)));
+        assert_eq!(seq.len(), 40);
+
+        let frags = digest(&seq, &[re], SeqTopology::Circular);
+
+        assert_eq!(frags.len(), 1);
+        assert_eq!(frags[0].strand_top.len(), 40);
+        assert_eq!(frags[0].start, 8);
+        assert_eq!(frags[0].left.kind, EndKind::FivePrime);
+        assert_eq!(frags[0].left.overhang, frags[0].right.overhang);
+    }
+
+    #[test]
+    fn digest_circular_two_enzymes() {
+        // EcoRI (G^AATTC) at 0-based 20, and BamHI (G^GATCC) at 66, in a 102 nt plasmid.
+        let filler = |n| "ACGT".repeat(n / 4 + 1)[..n].to_owned();
+        let seq = seq_from_str(&format!(
+            "{}GAATTC{}GGATCC{}",
+            filler(20),
+            filler(40),
+            filler(30)
+        ));
+        assert_eq!(seq.len(), 102);
+
+        let mut frags = digest(
+            &seq,
+            &[enzyme("EcoRI"), enzyme("BamHI")],
+            SeqTopology::Circular,
+        );
+        frags.sort_by_key(|f| f.start);
+        assert_eq!(frags.len(), 2);
+
+        // Cuts are after 0-based 20 and 66: one fragment between them, and one across the origin.
+        assert_eq!((frags[0].start, frags[0].end), (22, 67));
+        assert_eq!(frags[0].strand_top.len(), 46);
+        assert_eq!((frags[1].start, frags[1].end), (68, 21));
+        assert_eq!(frags[1].strand_top.len(), 56);
+        assert_eq!(
+            frags.iter().map(|f| f.strand_top.len()).sum::<usize>(),
+            seq.len()
+        );
+
+        assert_eq!(frags[0].left.overhang, seq_from_str("AATT"));
+        assert_eq!(frags[0].right.overhang, seq_from_str("GATC"));
+        assert_eq!(frags[1].left.overhang, seq_from_str("GATC"));
+        assert_eq!(frags[1].right.overhang, seq_from_str("AATT"));
+    }
+
+    #[test]
+    fn summary_and_single_cutters() {
+        // EcoRI once, at 0-based 4; BamHI three times, at 14, 24 and 34; HindIII not at all.
+        let seq = seq_from_str("ACGTGAATTCACGTGGATCCACGTGGATCCACGTGGATCCACGT");
+        let lib = [enzyme("BamHI"), enzyme("HindIII"), enzyme("EcoRI")];
+
+        let singles = single_cutters(&seq, &lib);
+        assert_eq!(singles.len(), 1);
+        assert_eq!(singles[0].name, "EcoRI");
+
+        let summary = re_summary(&seq, &lib);
+        let summary: Vec<_> = summary
+            .iter()
+            .map(|s| (s.enzyme_name.as_str(), s.cut_count, s.positions.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("HindIII", 0, vec![]),
+                ("EcoRI", 1, vec![5]),
+                ("BamHI", 3, vec![15, 25, 35]),
+            ]
+        );
+    }
+
+    // Regression inputs for the no-panic contract. Each of these has panicked in the past.
+
+    #[test]
+    #[allow(deprecated)]
+    fn empty_recognition_seq() {
+        let seq = seq_from_str("ACGTACGT");
+        let re = RestrictionEnzyme::new("Empty", Vec::new(), 0);
+
+        assert_eq!(re.cut_depiction(), "");
+        assert!(re.overhang_top_left(&seq).is_empty());
+        assert!(re.overhang_top_right(&seq).is_empty());
+        assert!(find_re_matches(&seq, std::slice::from_ref(&re)).is_empty());
+        assert_eq!(digest(&seq, &[re], SeqTopology::Circular).len(), 1);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn cut_after_past_site() {
+        let seq = seq_from_str("GAATTCGAATTC");
+        let re = RestrictionEnzyme::new("Past", vec![G, A, A, T, T, C], 200);
+
+        // Clamped to fit the offsets.
+        assert_eq!((re.cut_top(), re.cut_bottom()), (133, -122));
+        assert!(re.cuts_outside_site());
+
+        // The last index: The top strand cuts at the end of the site, and the bottom at its start.
+        let site = vec![G, A, A, T, T, C];
+        let re_end = RestrictionEnzyme::new("End", site.clone(), 5);
+        assert_eq!((re_end.cut_top(), re_end.cut_bottom()), (6, 0));
+        assert!(!re_end.cuts_outside_site());
+
+        // Past it, the top strand cuts after the site, and the bottom the same distance before it.
+        let re_past = RestrictionEnzyme::new("Past", site, 9);
+        assert_eq!((re_past.cut_top(), re_past.cut_bottom()), (10, -4));
+        assert!(re_past.cuts_outside_site());
+
+        re.cut_depiction();
+        re.overhang_top_left(&seq);
+        re.overhang_top_right(&seq);
+        for topology in [SeqTopology::Linear, SeqTopology::Circular] {
+            digest(&seq, std::slice::from_ref(&re), topology);
+        }
+    }
+
+    #[test]
+    fn type_iis_cut_after_clamped() {
+        // `cut_after` is a u8; cuts past it, or before the site, are clamped rather than wrapping.
+        let long_site = vec![N; 200];
+        let re = RestrictionEnzyme::new_type_iis("Long", long_site, 100, 104);
+        assert_eq!(re.cut_after, 255);
+        assert_eq!((re.cut_top(), re.cut_bottom()), (300, 304));
+
+        let re = RestrictionEnzyme::new_type_iis("Before", vec![G], -128, -124);
+        assert_eq!(re.cut_after, 0);
+        assert_eq!((re.cut_top(), re.cut_bottom()), (-127, -123));
+
+        let re = RestrictionEnzyme::new_type_iis("BsaI", vec![G, G, T, C, T, C], 1, 5);
+        assert_eq!(re.cut_after, 6);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn segment_shorter_than_overhang() {
+        let ecori = enzyme("EcoRI");
+        let pst = enzyme("PstI");
+
+        for seg in ["", "G", "GAA"] {
+            let seg = seq_from_str(seg);
+            assert!(ecori.overhang_top_left(&seg).is_empty());
+            assert!(pst.overhang_top_right(&seg).is_empty());
+        }
+        assert_eq!(
+            ecori.overhang_top_left(&seq_from_str("GAATTC")),
+            seq_from_str("AATT")
+        );
+    }
+
+    #[test]
+    fn seq_shorter_than_site() {
+        let seq = seq_from_str("GAAT");
+        for topology in [SeqTopology::Linear, SeqTopology::Circular] {
+            let frags = digest(&seq, &[enzyme("EcoRI"), enzyme("BsaI")], topology);
+            assert_eq!(frags.len(), 1);
+        }
+    }
+
+    #[test]
+    fn seq_is_only_site() {
+        // EcoRI's cuts are one nucleotide from each end of its site.
+        let seq = seq_from_str("GAATTC");
+
+        let frags = digest(&seq, &[enzyme("EcoRI")], SeqTopology::Linear);
+        assert_eq!(frags.len(), 2);
+        assert_eq!(frags[0].strand_top, seq_from_str("G"));
+        assert_eq!(frags[1].strand_top, seq_from_str("AATTC"));
+
+        let frags = digest(&seq, &[enzyme("EcoRI")], SeqTopology::Circular);
+        assert_eq!(frags.len(), 1);
+        assert_eq!(frags[0].left.overhang, seq_from_str("AATT"));
+    }
+
+    #[test]
+    fn bsai_reverse_strand_only() {
+        // GAGACC is BsaI's site, GGTCTC, on the reverse strand.
+        let seq = seq_from_str("CCCCAGTCGAGACCTTTT");
+        let lib = [enzyme("BsaI")];
+
+        assert!(find_re_matches(&seq, &lib).is_empty());
+
+        let matches = find_re_matches_both_strands(&seq, &lib, SeqTopology::Linear);
+        assert_eq!(matches.len(), 1);
+        let (re_match, strand) = &matches[0];
+        assert_eq!(*strand, Strand::Reverse);
+        assert_eq!(re_match.seq_index, 9);
+        assert_eq!(re_match.match_count, 1);
+
+        // The cut is upstream of the site, on the forward strand: GGTCTC(1/5) reversed.
+        let frags = digest(&seq, &lib, SeqTopology::Linear);
+        assert_eq!(frags.len(), 2);
+        assert_eq!(frags[0].strand_top, seq_from_str("CCC"));
+        assert_eq!(frags[0].right.kind, EndKind::FivePrime);
+        assert_eq!(frags[0].right.overhang, seq_from_str("CAGT"));
+    }
+
+    #[test]
+    fn palindromic_site_reported_once() {
+        let seq = seq_from_str("GAATTC");
+        let matches = find_re_matches_both_strands(&seq, &[enzyme("EcoRI")], SeqTopology::Linear);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].1, Strand::Forward);
+    }
+
+    #[test]
+    fn two_ecori_sites() {
+        // Sites at 0-based positions 2 and 22, the latter ending the sequence. The near misses in between
+        // (GAATTA, and CAATTC) aren't sites. `seq_index` is 1-based.
+        let seq = seq_from_str("AAGAATTCTTGAATTACAATTCGAATTC");
+        let lib = [enzyme("EcoRI"), enzyme("BamHI")];
+
+        let matches = find_re_matches(&seq, &lib);
+        assert_eq!(matches.len(), 2);
+        for (re_match, seq_index) in matches.iter().zip([3, 23]) {
+            assert_eq!(re_match.lib_index, 0);
+            assert_eq!(re_match.seq_index, seq_index);
+        }
+    }
+}
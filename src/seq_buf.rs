@@ -0,0 +1,205 @@
+//! This module contains `SeqBuf`, an owned nucleotide sequence that can be displayed, and parsed from
+//! strings. It converts freely to and from `Seq`, so it can be used with the rest of the library.
+
+use std::{
+    fmt,
+    fmt::Write,
+    ops::{Deref, DerefMut, Index, IndexMut},
+    slice::SliceIndex,
+    str::FromStr,
+};
+
+use crate::{seq_from_str_checked, Nucleotide, Seq, SeqError};
+
+/// A nucleotide sequence, wrapping `Seq`. Derefs to `Vec<Nucleotide>`, so `Vec` and slice methods, and
+/// functions that take `&[Nucleotide]`, work directly.
+///
+/// Displays in lower case, e.g. `acgt`; use `{:#}` for upper case. Parses as `seq_from_str_checked`:
+/// Case insensitive, with whitespace skipped. Other letters, including RNA's `U`, are errors.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct SeqBuf(pub Seq);
+
+impl SeqBuf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_inner(self) -> Seq {
+        self.0
+    }
+}
+
+impl Deref for SeqBuf {
+    type Target = Seq;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SeqBuf {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl AsRef<[Nucleotide]> for SeqBuf {
+    fn as_ref(&self) -> &[Nucleotide] {
+        &self.0
+    }
+}
+
+impl From<Seq> for SeqBuf {
+    fn from(seq: Seq) -> Self {
+        Self(seq)
+    }
+}
+
+impl From<&[Nucleotide]> for SeqBuf {
+    fn from(seq: &[Nucleotide]) -> Self {
+        Self(seq.to_vec())
+    }
+}
+
+impl From<SeqBuf> for Seq {
+    fn from(seq: SeqBuf) -> Self {
+        seq.0
+    }
+}
+
+impl fmt::Display for SeqBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for nt in &self.0 {
+            let letter = if f.alternate() {
+                nt.to_u8_upper()
+            } else {
+                nt.to_u8_lower()
+            };
+            f.write_char(letter as char)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SeqBuf {
+    type Err = SeqError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        seq_from_str_checked(s).map(Self)
+    }
+}
+
+impl<I: SliceIndex<[Nucleotide]>> Index<I> for SeqBuf {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<I: SliceIndex<[Nucleotide]>> IndexMut<I> for SeqBuf {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl Extend<Nucleotide> for SeqBuf {
+    fn extend<T: IntoIterator<Item = Nucleotide>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl<'a> Extend<&'a Nucleotide> for SeqBuf {
+    fn extend<T: IntoIterator<Item = &'a Nucleotide>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl FromIterator<Nucleotide> for SeqBuf {
+    fn from_iter<T: IntoIterator<Item = Nucleotide>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for SeqBuf {
+    type Item = Nucleotide;
+    type IntoIter = std::vec::IntoIter<Nucleotide>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SeqBuf {
+    type Item = &'a Nucleotide;
+    type IntoIter = std::slice::Iter<'a, Nucleotide>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Nucleotide::*;
+
+    #[test]
+    fn display() {
+        let seq = SeqBuf(vec![G, A, T, C]);
+
+        assert_eq!(seq.to_string(), "gatc");
+        assert_eq!(format!("{seq:#}"), "GATC");
+        assert_eq!(SeqBuf::new().to_string(), "");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("GAtc".parse::<SeqBuf>(), Ok(SeqBuf(vec![G, A, T, C])));
+        assert_eq!("ga\ntc ".parse::<SeqBuf>(), Ok(SeqBuf(vec![G, A, T, C])));
+
+        for (str, symbol, position) in [("GANC", 'N', 2), ("GAUC", 'U', 2), ("-GA", '-', 0)] {
+            assert_eq!(
+                str.parse::<SeqBuf>(),
+                Err(SeqError::InvalidSymbol {
+                    symbol,
+                    position,
+                    alphabet: "nucleotide",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        let seq: Seq = vec![A, C, G, T];
+
+        let buf = SeqBuf::from(seq.clone());
+        assert_eq!(Seq::from(buf.clone()), seq);
+        assert_eq!(SeqBuf::from(&seq[..]), buf);
+        assert_eq!(buf.into_inner(), seq);
+
+        // Functions taking slices accept it directly.
+        let buf: SeqBuf = "ACGT".parse().unwrap();
+        assert_eq!(crate::seq_complement(&buf), seq);
+    }
+
+    #[test]
+    fn index_and_collect() {
+        let mut seq: SeqBuf = [A, C, G].into_iter().collect();
+
+        assert_eq!(seq[1], C);
+        assert_eq!(seq[1..], [C, G]);
+        seq[0] = T;
+        seq[1..].copy_from_slice(&[T, T]);
+        assert_eq!(seq, SeqBuf(vec![T, T, T]));
+
+        seq.extend([G, G]);
+        seq.extend(&[C]);
+        assert_eq!(seq.to_string(), "tttggc");
+        assert_eq!((&seq).into_iter().filter(|nt| **nt == T).count(), 3);
+        assert_eq!(seq.into_iter().collect::<Vec<_>>(), vec![T, T, T, G, G, C]);
+    }
+}
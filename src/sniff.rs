@@ -0,0 +1,149 @@
+//! This module contains code for detecting a sequence file's format from its contents, e.g. for files
+//! without a meaningful extension.
+
+use crate::{
+    twobit::TWOBIT_SIGNATURE,
+    warnings::{Warning, WarningCode, Warnings},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SeqFormat {
+    Fasta,
+    Fastq,
+    GenBank,
+    TwoBit,
+}
+
+/// The number of lines to examine, when looking for a format's signature past the first line.
+const LINES_SCANNED: usize = 100;
+
+/// Detect the format of a sequence file, from its start. 2bit signatures, in either byte order, a leading
+/// `LOCUS` line, `>` header, or `@` header followed by a `+` line are definitive. Otherwise, this guesses,
+/// with a `FormatGuessed` warning: GenBank for files with a `LOCUS` or `ORIGIN` line further down, FASTQ
+/// for an `@` header without the `+` line, and FASTA for headerless nucleotide text. Returns `None` if
+/// nothing matches.
+pub fn sniff_format(data: &[u8], warnings: &mut Warnings) -> Option<SeqFormat> {
+    if let Some(sig) = data.get(..4) {
+        let sig = [sig[0], sig[1], sig[2], sig[3]];
+        if u32::from_le_bytes(sig) == TWOBIT_SIGNATURE
+            || u32::from_be_bytes(sig) == TWOBIT_SIGNATURE
+        {
+            return Some(SeqFormat::TwoBit);
+        }
+    }
+
+    let text = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let mut lines = text
+        .split(|b| *b == b'\n')
+        .map(|line| line.trim_ascii())
+        .skip_while(|line| line.is_empty());
+
+    let first = lines.next()?;
+    if first.starts_with(b"LOCUS") {
+        return Some(SeqFormat::GenBank);
+    }
+    if first.starts_with(b">") {
+        return Some(SeqFormat::Fasta);
+    }
+    if first.starts_with(b"@") {
+        if lines.nth(1).is_some_and(|line| line.starts_with(b"+")) {
+            return Some(SeqFormat::Fastq);
+        }
+        return Some(guessed(
+            SeqFormat::Fastq,
+            "An @ header, without a + separator line",
+            warnings,
+        ));
+    }
+
+    if lines
+        .take(LINES_SCANNED)
+        .chain([first])
+        .any(|line| line.starts_with(b"LOCUS") || line.starts_with(b"ORIGIN"))
+    {
+        return Some(guessed(
+            SeqFormat::GenBank,
+            "A GenBank LOCUS or ORIGIN line, not at the start",
+            warnings,
+        ));
+    }
+
+    if first
+        .iter()
+        .all(|b| b"ACGTUNRYKMSWBDHV".contains(&b.to_ascii_uppercase()))
+    {
+        return Some(guessed(
+            SeqFormat::Fasta,
+            "Nucleotide text without a FASTA header",
+            warnings,
+        ));
+    }
+
+    None
+}
+
+fn guessed(format: SeqFormat, reason: &str, warnings: &mut Warnings) -> SeqFormat {
+    warnings.push(Warning::new(
+        WarningCode::FormatGuessed,
+        &format!("Guessed {format:?} format: {reason}"),
+        None,
+    ));
+    format
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq_from_str, twobit};
+
+    fn sniff(data: &[u8]) -> (Option<SeqFormat>, Vec<WarningCode>) {
+        let mut warnings = Vec::new();
+        let format = sniff_format(data, &mut warnings);
+
+        (format, warnings.iter().map(|w| w.code).collect())
+    }
+
+    #[test]
+    fn definitive() {
+        assert_eq!(sniff(b">seq1\nACGT\n"), (Some(SeqFormat::Fasta), vec![]));
+        assert_eq!(
+            sniff(b"\n\n@read1\nACGT\n+\nIIII\n"),
+            (Some(SeqFormat::Fastq), vec![])
+        );
+        assert_eq!(
+            sniff(b"\xEF\xBB\xBFLOCUS       pUC19  2686 bp    DNA     circular SYN 01-JAN-1980\n"),
+            (Some(SeqFormat::GenBank), vec![])
+        );
+
+        let record = twobit::TwoBitRecord {
+            name: "chr1".to_owned(),
+            seq: seq_from_str("ACGTACGT"),
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        twobit::write_twobit(&mut data, &[record]).unwrap();
+        assert_eq!(sniff(&data), (Some(SeqFormat::TwoBit), vec![]));
+
+        data[..4].reverse();
+        assert_eq!(sniff(&data), (Some(SeqFormat::TwoBit), vec![]));
+    }
+
+    #[test]
+    fn guessed() {
+        let guess = |format| (Some(format), vec![WarningCode::FormatGuessed]);
+
+        assert_eq!(sniff(b"@read1\nACGT\n"), guess(SeqFormat::Fastq));
+        assert_eq!(
+            sniff(b"Exported from an editor\nLOCUS       pUC19\nORIGIN\n//\n"),
+            guess(SeqFormat::GenBank)
+        );
+        assert_eq!(sniff(b"acgtnnacgt\nACGT\n"), guess(SeqFormat::Fasta));
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(sniff(b""), (None, vec![]));
+        assert_eq!(sniff(b"  \n\n"), (None, vec![]));
+        assert_eq!(sniff(b"Hello, world\n"), (None, vec![]));
+    }
+}
@@ -0,0 +1,43 @@
+//! This module contains helpers shared by unit tests.
+
+pub(crate) use crate::rng::Rng;
+use crate::{alphabet::Alphabet, Nucleotide, Seq};
+
+impl Rng {
+    /// A value in `0..max`.
+    pub fn below(&mut self, max: usize) -> usize {
+        (self.next_u64() % max as u64) as usize
+    }
+
+    /// A random sequence of any alphabet.
+    pub fn symbols<T: Alphabet>(&mut self, len: usize) -> Vec<T> {
+        (0..len).map(|_| T::SYMBOLS[self.below(T::SIZE)]).collect()
+    }
+
+    pub fn seq(&mut self, len: usize) -> Seq {
+        self.symbols(len)
+    }
+
+    /// A copy of `seq` with `count` random substitutions, insertions, and deletions.
+    pub fn mutate(&mut self, seq: &[Nucleotide], count: usize) -> Seq {
+        let mut result = seq.to_vec();
+        for _ in 0..count {
+            let nt = self.seq(1)[0];
+            match self.below(3) {
+                0 if !result.is_empty() => {
+                    let i = self.below(result.len());
+                    result[i] = nt;
+                }
+                1 if !result.is_empty() => {
+                    let i = self.below(result.len());
+                    result.remove(i);
+                }
+                _ => {
+                    let i = self.below(result.len() + 1);
+                    result.insert(i, nt);
+                }
+            }
+        }
+        result
+    }
+}
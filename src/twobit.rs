@@ -0,0 +1,418 @@
+//! This module contains code for reading and writing UCSC `.2bit` files, a compact format for genome sequences.
+//!
+//! [2bit format](https://genome.ucsc.edu/FAQ/FAQformat.html#format7)
+
+use std::{
+    io,
+    io::{ErrorKind, Read, Write},
+    ops::Range,
+};
+
+use crate::{Nucleotide, Seq};
+
+pub const TWOBIT_SIGNATURE: u32 = 0x1A41_2743;
+
+#[derive(Clone, Debug, Default)]
+pub struct TwoBitRecord {
+    pub name: String,
+    /// Regions in `n_blocks` are stored as `T`, since `Nucleotide` can't represent `N`.
+    pub seq: Seq,
+    /// 0-based, end-exclusive ranges of unknown (`N`) nucleotides.
+    pub n_blocks: Vec<Range<usize>>,
+    /// 0-based, end-exclusive ranges of masked (lower-case, e.g. repeat) nucleotides.
+    pub mask_blocks: Vec<Range<usize>>,
+}
+
+/// Reads fields from the file, in its byte order.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> Cursor<'a> {
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let result = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("2bit file is truncated at byte {}", self.pos),
+                )
+            })?;
+
+        self.pos += len;
+        Ok(result)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes(4)?.try_into().unwrap();
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let bytes = self.bytes(8)?.try_into().unwrap();
+        Ok(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+
+    /// A count, followed by that many starts, then that many sizes.
+    fn blocks(&mut self) -> io::Result<Vec<Range<usize>>> {
+        let count = self.u32()? as usize;
+
+        // Check the length up front, so a corrupt count doesn't cause a huge allocation.
+        if count.saturating_mul(8) > self.data.len().saturating_sub(self.pos) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid 2bit block count: {count}"),
+            ));
+        }
+
+        let starts = (0..count)
+            .map(|_| self.u32())
+            .collect::<io::Result<Vec<_>>>()?;
+
+        starts
+            .into_iter()
+            .map(|start| {
+                let start = start as usize;
+                Ok(start..start.saturating_add(self.u32()? as usize))
+            })
+            .collect()
+    }
+}
+
+/// Read all sequences from a 2bit file. Either byte order is accepted, as are both version 0, and
+/// version 1 (64-bit offset) files.
+pub fn read_twobit<R: Read>(mut reader: R) -> io::Result<Vec<TwoBitRecord>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    parse_twobit(&data)
+}
+
+/// Parse a 2bit file that has already been read into memory.
+pub fn parse_twobit(data: &[u8]) -> io::Result<Vec<TwoBitRecord>> {
+    let mut cursor = Cursor {
+        data,
+        pos: 0,
+        big_endian: false,
+    };
+
+    if cursor.u32()? != TWOBIT_SIGNATURE {
+        cursor.pos = 0;
+        cursor.big_endian = true;
+        if cursor.u32()? != TWOBIT_SIGNATURE {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "Invalid 2bit signature",
+            ));
+        }
+    }
+
+    let version = cursor.u32()?;
+    if version > 1 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("Unsupported 2bit version: {version}"),
+        ));
+    }
+
+    let seq_count = cursor.u32()?;
+    cursor.u32()?; // Reserved.
+
+    let mut index = Vec::new();
+    for _ in 0..seq_count {
+        let name_len = cursor.bytes(1)?[0] as usize;
+        let name = String::from_utf8_lossy(cursor.bytes(name_len)?).into_owned();
+
+        let offset = if version == 1 {
+            let offset = cursor.u64()?;
+            usize::try_from(offset).map_err(|_| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("2bit offset {offset} is too large for this platform"),
+                )
+            })?
+        } else {
+            cursor.u32()? as usize
+        };
+
+        index.push((name, offset));
+    }
+
+    let mut result = Vec::with_capacity(index.len());
+
+    for (name, offset) in index {
+        cursor.pos = offset;
+
+        let dna_size = cursor.u32()? as usize;
+        let n_blocks = cursor.blocks()?;
+        let mask_blocks = cursor.blocks()?;
+        cursor.u32()?; // Reserved.
+
+        let packed = cursor.bytes(dna_size.div_ceil(4))?;
+
+        let mut seq = Vec::with_capacity(dna_size);
+        for i in 0..dna_size {
+            // The first nucleotide is in the most significant bits. The 2bit encoding
+            // (T=0, C=1, A=2, G=3) matches our own.
+            let bits = (packed[i / 4] >> (6 - 2 * (i % 4))) & 0b11;
+            seq.push(Nucleotide::try_from(bits).unwrap());
+        }
+
+        result.push(TwoBitRecord {
+            name,
+            seq,
+            n_blocks,
+            mask_blocks,
+        });
+    }
+
+    Ok(result)
+}
+
+fn invalid_input(msg: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, msg)
+}
+
+/// Convert a length or position to the format's 32-bit representation.
+fn to_u32(val: usize, name: &str) -> io::Result<u32> {
+    u32::try_from(val)
+        .map_err(|_| invalid_input(format!("{name} {val} is too large for a 2bit file")))
+}
+
+/// The size of a record's data, in bytes, excluding the index.
+fn record_size(record: &TwoBitRecord) -> usize {
+    // DNA size, block counts, and the reserved field are 4 bytes each; each block is 8.
+    16 + 8 * (record.n_blocks.len() + record.mask_blocks.len()) + record.seq.len().div_ceil(4)
+}
+
+fn write_blocks<W: Write>(w: &mut W, blocks: &[Range<usize>]) -> io::Result<()> {
+    w.write_all(&to_u32(blocks.len(), "Block count")?.to_le_bytes())?;
+    for block in blocks {
+        w.write_all(&to_u32(block.start, "Block start")?.to_le_bytes())?;
+    }
+    for block in blocks {
+        let size = block.end.saturating_sub(block.start);
+        w.write_all(&to_u32(size, "Block size")?.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write sequences to a 2bit file, in little endian byte order. `n_blocks` and `mask_blocks` are written
+/// as-is; leave them empty for sequences without `N`s, or masking. Uses version 0, unless the file is
+/// too large for 32-bit offsets, in which case version 1 is used.
+pub fn write_twobit<W: Write>(w: &mut W, records: &[TwoBitRecord]) -> io::Result<()> {
+    for record in records {
+        if record.name.len() > u8::MAX as usize {
+            return Err(invalid_input(format!(
+                "2bit sequence names can be at most 255 bytes: {}",
+                record.name
+            )));
+        }
+        to_u32(record.seq.len(), "Sequence length")?;
+    }
+
+    let index_size = |offset_size: usize| -> usize {
+        records.iter().map(|r| 1 + r.name.len() + offset_size).sum()
+    };
+    let data_size: usize = records.iter().map(record_size).sum();
+
+    let version = if 16 + index_size(4) + data_size > u32::MAX as usize {
+        1
+    } else {
+        0
+    };
+
+    w.write_all(&TWOBIT_SIGNATURE.to_le_bytes())?;
+    w.write_all(&(version as u32).to_le_bytes())?;
+    w.write_all(&to_u32(records.len(), "Sequence count")?.to_le_bytes())?;
+    w.write_all(&0_u32.to_le_bytes())?; // Reserved.
+
+    let mut offset = 16 + index_size(if version == 1 { 8 } else { 4 });
+    for record in records {
+        w.write_all(&[record.name.len() as u8])?;
+        w.write_all(record.name.as_bytes())?;
+
+        if version == 1 {
+            w.write_all(&(offset as u64).to_le_bytes())?;
+        } else {
+            w.write_all(&(offset as u32).to_le_bytes())?;
+        }
+        offset += record_size(record);
+    }
+
+    for record in records {
+        w.write_all(&(record.seq.len() as u32).to_le_bytes())?;
+        write_blocks(w, &record.n_blocks)?;
+        write_blocks(w, &record.mask_blocks)?;
+        w.write_all(&0_u32.to_le_bytes())?; // Reserved.
+
+        let packed: Vec<u8> = record
+            .seq
+            .chunks(4)
+            .map(|chunk| {
+                // The first nucleotide is in the most significant bits.
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |byte, (i, nt)| byte | (*nt as u8) << (6 - 2 * i))
+            })
+            .collect();
+        w.write_all(&packed)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+// These are N blocks, not ranges of positions.
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+    use crate::seq_from_str;
+
+    /// A hand-built file with one 10-nt sequence, "ACNNNGCATG", with its `N`s stored as `T`.
+    fn tiny_file(big_endian: bool) -> Vec<u8> {
+        let u32_bytes = |v: u32| {
+            if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            }
+        };
+
+        let mut result = Vec::new();
+        // Header: Signature, version, sequence count, reserved.
+        for v in [TWOBIT_SIGNATURE, 0, 1, 0] {
+            result.extend(u32_bytes(v));
+        }
+        // Index: The name, and the offset of its record.
+        result.push(4);
+        result.extend(b"chr1");
+        result.extend(u32_bytes(25));
+        // Record: DNA size; N blocks (count, starts, sizes); mask blocks (count); reserved.
+        for v in [10, 1, 2, 3, 0, 0] {
+            result.extend(u32_bytes(v));
+        }
+        // ACTT TGCA TG, from the most significant bits.
+        result.extend([0b10_01_00_00, 0b00_11_01_10, 0b00_11_00_00]);
+
+        result
+    }
+
+    #[test]
+    fn parse_n_block() {
+        let records = parse_twobit(&tiny_file(false)).unwrap();
+
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.name, "chr1");
+        assert_eq!(record.seq, seq_from_str("ACTTTGCATG"));
+        assert_eq!(record.n_blocks, vec![2..5]);
+        assert!(record.mask_blocks.is_empty());
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let data = tiny_file(false);
+        assert!(parse_twobit(&data[..data.len() - 1]).is_err());
+        assert!(parse_twobit(&data[..10]).is_err());
+    }
+
+    #[test]
+    fn parse_big_endian() {
+        let records = parse_twobit(&tiny_file(true)).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].seq, seq_from_str("ACTTTGCATG"));
+        assert_eq!(records[0].n_blocks, vec![2..5]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = vec![
+            TwoBitRecord {
+                name: "chr1".to_owned(),
+                seq: seq_from_str("ACTTTGCATG"),
+                n_blocks: vec![2..5],
+                mask_blocks: vec![0..2, 7..10],
+            },
+            TwoBitRecord {
+                name: "empty".to_owned(),
+                ..Default::default()
+            },
+            TwoBitRecord {
+                name: "chr2".to_owned(),
+                seq: seq_from_str("GATTACAGATTACAG"),
+                ..Default::default()
+            },
+        ];
+
+        let mut data = Vec::new();
+        write_twobit(&mut data, &records).unwrap();
+        let read = read_twobit(&data[..]).unwrap();
+
+        assert_eq!(read.len(), records.len());
+        for (a, b) in records.iter().zip(&read) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.seq, b.seq);
+            assert_eq!(a.n_blocks, b.n_blocks);
+            assert_eq!(a.mask_blocks, b.mask_blocks);
+        }
+    }
+
+    #[test]
+    fn write_matches_hand_built() {
+        let record = TwoBitRecord {
+            name: "chr1".to_owned(),
+            seq: seq_from_str("ACTTTGCATG"),
+            n_blocks: vec![2..5],
+            mask_blocks: Vec::new(),
+        };
+
+        let mut data = Vec::new();
+        write_twobit(&mut data, &[record]).unwrap();
+        assert_eq!(data, tiny_file(false));
+    }
+
+    #[test]
+    fn parse_corrupt_offsets() {
+        // Version 1, with a 64-bit offset far past the end of the file.
+        let mut data = Vec::new();
+        for v in [TWOBIT_SIGNATURE, 1, 1, 0] {
+            data.extend(v.to_le_bytes());
+        }
+        data.push(4);
+        data.extend(b"chr1");
+        data.extend(u64::MAX.to_le_bytes());
+
+        let err = parse_twobit(&data).unwrap_err();
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        #[cfg(target_pointer_width = "32")]
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // A DNA size, and block count, larger than the file.
+        let mut data = tiny_file(false);
+        data[25..29].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_twobit(&data).is_err());
+
+        let mut data = tiny_file(false);
+        data[29..33].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            parse_twobit(&data).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
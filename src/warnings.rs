@@ -0,0 +1,41 @@
+//! This module contains a structured warning channel, for operations that succeed, but have something
+//! to report to the caller, e.g. skipped or lossy input. Functions that can warn take a `&mut Warnings`,
+//! and push to it; the library doesn't print to stderr. Where this would change an existing function's
+//! signature, it has a `_with_warnings` variant instead, e.g. `ligation::digest_with_warnings`.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WarningCode {
+    /// A restriction enzyme match referred to an index outside the enzyme library; it was skipped.
+    InvalidReIndex,
+    /// A restriction enzyme match was past the end of the sequence it was applied to; it was skipped.
+    MatchOutOfBounds,
+    /// A run of ambiguous nucleotides, e.g. `N` or another IUPAC code, was skipped, since `Nucleotide`
+    /// can't represent them.
+    AmbiguousNucleotide,
+    /// A run of characters that aren't nucleotides was skipped.
+    InvalidSymbol,
+    /// An atom name could refer to more than one element, e.g. "CA" for calcium, or an alpha carbon.
+    AmbiguousElement,
+    /// A file's format was guessed from its content, without a definitive signature.
+    FormatGuessed,
+}
+
+#[derive(Clone, Debug)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+    /// A position in the input this warning relates to, if applicable, e.g. an index into a sequence or list.
+    pub position: Option<usize>,
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: &str, position: Option<usize>) -> Self {
+        Self {
+            code,
+            message: message.to_owned(),
+            position,
+        }
+    }
+}
+
+pub type Warnings = Vec<Warning>;